@@ -1,36 +1,346 @@
+use crate::discovery;
+use crate::history;
 use crate::models::*;
 use crate::parser::BmadParser;
-use crate::watcher::{self, FileWatcherState};
+use crate::portfolio;
+use crate::registry::ProjectRegistry;
+use crate::scope::ProjectScope;
+use crate::settings;
+use crate::snapshots;
+use crate::watcher::{self, FileWatcherState, WatchStatus, WatcherInfo};
+use crate::writer::{self, document::DocumentError};
 use parking_lot::Mutex;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
+type ScopeState<'a> = State<'a, Arc<Mutex<ProjectScope>>>;
+type RegistryState<'a> = State<'a, Arc<Mutex<ProjectRegistry>>>;
+type WatcherState<'a> = State<'a, Arc<Mutex<FileWatcherState>>>;
+
+/// Resolve a path from the webview inside the opened projects
+fn scoped(scope: &ScopeState, path: &str) -> Result<PathBuf, String> {
+    scope.lock().resolve(Path::new(path)).map_err(|e| e.to_string())
+}
+
+fn scoped_writable(scope: &ScopeState, path: &str) -> Result<PathBuf, String> {
+    scope.lock().resolve_writable(Path::new(path)).map_err(|e| e.to_string())
+}
+
+/// Search `root_path`, or every scan root from the settings (the home
+/// directory if none are set), for BMAD projects. The projects found may
+/// then be added with `add_project`.
+///
+/// `root_path` must be a scan root or a folder picked in `pick_folder`, and
+/// the depth is capped like the `scanDepth` setting, so the webview cannot
+/// grant itself folders the user never chose.
+#[tauri::command]
+pub fn scan_projects(
+    scope: ScopeState,
+    root_path: Option<String>,
+    max_depth: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let settings = settings::current();
+    let scan_roots = settings::scan_roots(&settings);
+    let roots: Vec<PathBuf> = match root_path {
+        Some(root_path) => {
+            let root = PathBuf::from(&root_path);
+            if !scan_roots.contains(&root) {
+                scope.lock().resolve_granted(&root).map_err(|e| e.to_string())?;
+            }
+            vec![root]
+        }
+        None => scan_roots,
+    };
+    if roots.is_empty() {
+        return Err("No scan root is available".to_string());
+    }
+    if let Some(missing) = roots.iter().find(|root| !root.exists()) {
+        return Err(format!("Path does not exist: {}", missing.display()));
+    }
+
+    let depth = max_depth.unwrap_or(settings.scan_depth).min(settings::MAX_SCAN_DEPTH);
+    let mut projects = Vec::new();
+    let mut scope = scope.lock();
+    for root in &roots {
+        for project in BmadParser::scan_for_projects(root, depth) {
+            if let Err(e) = scope.grant(&project) {
+                eprintln!("Skipping scanned project {}: {}", project.display(), e);
+                continue;
+            }
+            let project = project.to_string_lossy().to_string();
+            if !projects.contains(&project) {
+                projects.push(project);
+            }
+        }
+    }
+
+    Ok(projects)
+}
+
+/// Parse a registered project; both folders must lie inside the roots
+/// `add_project` opened. With `project_id` the result is also cached and snapshotted.
 #[tauri::command]
-pub fn scan_projects(root_path: String, max_depth: Option<usize>) -> Result<Vec<String>, String> {
-    let path = Path::new(&root_path);
-    if !path.exists() {
-        return Err(format!("Path does not exist: {}", root_path));
+pub fn parse_project(
+    scope: ScopeState,
+    project_path: String,
+    bmad_docs_path: Option<String>,
+    project_id: Option<String>,
+) -> Result<BmadProject, String> {
+    let path = scoped(&scope, &project_path)?;
+    let bmad_dir = bmad_docs_path
+        .filter(|p| !p.is_empty())
+        .map(|p| scoped(&scope, &p))
+        .transpose()?;
+
+    let project = BmadParser::parse_project(&path, bmad_dir.as_deref()).map_err(|e| e.to_string())?;
+    if let Some(project_id) = &project_id {
+        if let Err(e) = snapshots::record(project_id, &project) {
+            eprintln!("Failed to snapshot project {}: {}", project_id, e);
+        }
     }
 
-    let depth = max_depth.unwrap_or(3);
-    let projects = BmadParser::scan_for_projects(path, depth);
+    Ok(project)
+}
 
-    Ok(projects
+/// Show the native folder picker. The picked folder may then be added
+/// with `add_project`; `None` if the user cancelled.
+#[tauri::command]
+pub async fn pick_folder(
+    app: AppHandle,
+    scope: ScopeState<'_>,
+    title: Option<String>,
+) -> Result<Option<String>, String> {
+    let mut dialog = app.dialog().file();
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+    let Some(picked) = dialog.blocking_pick_folder() else {
+        return Ok(None);
+    };
+
+    let folder = picked.into_path().map_err(|e| e.to_string())?;
+    let folder = scope.lock().grant(&folder).map_err(|e| e.to_string())?;
+    Ok(Some(folder.to_string_lossy().to_string()))
+}
+
+/// Ask the user, in a native dialog, whether the app may open these
+/// project folders (e.g. ones the webview kept from before the registry
+/// existed). Returns the given paths that may now be added with `add_project`.
+#[tauri::command]
+pub async fn grant_project_folders(
+    app: AppHandle,
+    scope: ScopeState<'_>,
+    paths: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let folders: Vec<&String> = paths.iter().filter(|p| Path::new(p).is_dir()).collect();
+    if folders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let list = folders.iter().map(|p| format!("• {}", p)).collect::<Vec<_>>().join("\n");
+    let allowed = app
+        .dialog()
+        .message(format!(
+            "Allow BMAD Dashboard to open these project folders?\n\n{}",
+            list
+        ))
+        .title("Restore projects")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Allow".into(), "Cancel".into()))
+        .blocking_show();
+    if !allowed {
+        return Ok(Vec::new());
+    }
+
+    let mut scope = scope.lock();
+    Ok(folders
         .into_iter()
-        .map(|p| p.to_string_lossy().to_string())
+        .filter(|p| scope.grant(Path::new(p)).is_ok())
+        .cloned()
         .collect())
 }
 
+/// Drop a removed project's folders from the accessible roots
 #[tauri::command]
-pub fn parse_project(project_path: String, bmad_docs_path: Option<String>) -> Result<BmadProject, String> {
+pub fn close_project(scope: ScopeState, project_path: String, bmad_docs_path: Option<String>) {
+    let mut scope = scope.lock();
+    scope.unregister(Path::new(&project_path));
+    if let Some(docs_path) = bmad_docs_path {
+        scope.unregister(Path::new(&docs_path));
+    }
+}
+
+/// Open a registered project's folders to the file commands and, unless it
+/// is archived, start watching it. Used when projects are added or changed
+/// and for every registered project at launch.
+pub fn open_project_entry(
+    app: AppHandle,
+    scope: &Mutex<ProjectScope>,
+    watchers: Arc<Mutex<FileWatcherState>>,
+    entry: &ProjectEntry,
+) -> Result<Option<WatchStatus>, String> {
+    let project_path = scope
+        .lock()
+        .register(Path::new(&entry.path))
+        .map_err(|e| e.to_string())?;
+    let bmad_docs_path = match &entry.bmad_docs_path {
+        Some(docs_path) => {
+            let mut scope = scope.lock();
+            let docs_path = Path::new(docs_path);
+            // A docs folder outside the project must have been granted
+            let resolved = match scope.resolve(docs_path) {
+                Ok(resolved) => resolved,
+                Err(e) => match scope.resolve_granted(docs_path) {
+                    Ok(granted) => scope.register(&granted).map_err(|e| e.to_string())?,
+                    Err(_) => return Err(e.to_string()),
+                },
+            };
+            Some(resolved)
+        }
+        None => None,
+    };
+
+    if entry.archived {
+        watcher::stop_watching(watchers, &entry.id);
+        return Ok(None);
+    }
+    watcher::start_watching(
+        app,
+        watchers,
+        entry.id.clone(),
+        project_path,
+        bmad_docs_path,
+        entry.watch_options,
+    )
+    .map(Some)
+}
+
+/// Registered projects, pinned ones first
+#[tauri::command]
+pub fn list_projects(registry: RegistryState) -> Vec<ProjectEntry> {
+    let mut projects = registry.lock().list().to_vec();
+    projects.sort_by_key(|p| !p.pinned);
+    projects
+}
+
+/// Register a project folder and start watching it. `id` lets the webview
+/// keep the id it already uses for a project it added before the registry existed.
+///
+/// The folder must have been picked in `pick_folder`, found by
+/// `scan_projects` or proposed by discovery; the docs folder must be inside
+/// it or picked as well.
+#[tauri::command]
+pub fn add_project(
+    app: AppHandle,
+    registry: RegistryState,
+    scope: ScopeState,
+    watchers: WatcherState,
+    project_path: String,
+    bmad_docs_path: Option<String>,
+    id: Option<String>,
+) -> Result<ProjectEntry, String> {
     let path = Path::new(&project_path);
-    if !path.exists() {
+    if !path.is_dir() {
         return Err(format!("Project path does not exist: {}", project_path));
     }
+    let discovered = discovery::pending_project(path);
+    let (path, docs_path) = {
+        let mut scope = scope.lock();
+        let path = match &discovered {
+            Some(_) => std::fs::canonicalize(path).map_err(|e| e.to_string())?,
+            None => scope.resolve_granted(path).map_err(|e| e.to_string())?,
+        };
+        let proposed_docs = discovered
+            .as_ref()
+            .and_then(|d| d.bmad_docs_path.as_deref())
+            .and_then(|p| std::fs::canonicalize(p).ok());
+        let docs_path = bmad_docs_path
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let docs = std::fs::canonicalize(&p).map_err(|e| format!("{}: {}", p, e))?;
+                if docs.starts_with(&path) {
+                    Ok(docs)
+                } else if proposed_docs.as_ref() == Some(&docs) {
+                    scope.grant(&docs).map_err(|e| e.to_string())
+                } else {
+                    scope.resolve_granted(&docs).map_err(|e| e.to_string())
+                }
+            })
+            .transpose()?;
+        (path, docs_path)
+    };
+
+    let entry = registry
+        .lock()
+        .add(&path, docs_path.as_deref(), id)
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = open_project_entry(app, &scope, watchers.inner().clone(), &entry) {
+        let _ = registry.lock().remove(&entry.id);
+        return Err(e);
+    }
+    Ok(entry)
+}
+
+/// Unregister a project, stop its watcher and close its folders to the file commands
+#[tauri::command]
+pub fn remove_project(
+    registry: RegistryState,
+    scope: ScopeState,
+    watchers: WatcherState,
+    project_id: String,
+) -> Result<(), String> {
+    let entry = registry.lock().remove(&project_id).map_err(|e| e.to_string())?;
+    watcher::stop_watching(watchers.inner().clone(), &entry.id);
+    if let Ok(store) = snapshots::store() {
+        if let Err(e) = store.forget(&entry.id) {
+            eprintln!("Failed to drop snapshots of {}: {}", entry.id, e);
+        }
+    }
+
+    let mut scope = scope.lock();
+    scope.unregister(Path::new(&entry.path));
+    if let Some(docs_path) = &entry.bmad_docs_path {
+        scope.unregister(Path::new(docs_path));
+    }
+    Ok(())
+}
+
+/// Change a registry entry; the watcher is restarted (or stopped when
+/// archiving) if its folder or watch settings changed
+#[tauri::command]
+pub fn update_project(
+    app: AppHandle,
+    registry: RegistryState,
+    scope: ScopeState,
+    watchers: WatcherState,
+    project_id: String,
+    mut update: ProjectEntryUpdate,
+) -> Result<ProjectEntry, String> {
+    let mut registry = registry.lock();
+    let before = registry.get(&project_id).map_err(|e| e.to_string())?.clone();
+    // Same rule as `add_project`: inside the project, or picked by the user
+    if let Some(docs_path) = update.bmad_docs_path.as_deref().filter(|p| !p.is_empty()) {
+        let docs = std::fs::canonicalize(docs_path).map_err(|e| format!("{}: {}", docs_path, e))?;
+        let project = std::fs::canonicalize(&before.path).map_err(|e| format!("{}: {}", before.path, e))?;
+        if !docs.starts_with(&project) {
+            scope.lock().resolve_granted(&docs).map_err(|e| e.to_string())?;
+        }
+        update.bmad_docs_path = Some(docs.to_string_lossy().to_string());
+    }
+    let entry = registry
+        .update(&project_id, update)
+        .map_err(|e| e.to_string())?;
+    drop(registry);
 
-    let bmad_dir = bmad_docs_path.map(|p| std::path::PathBuf::from(p));
-    BmadParser::parse_project(path, bmad_dir.as_deref()).map_err(|e| e.to_string())
+    let rewatch = entry.bmad_docs_path != before.bmad_docs_path
+        || entry.archived != before.archived
+        || entry.watch_options != before.watch_options;
+    if rewatch {
+        open_project_entry(app, &scope, watchers.inner().clone(), &entry)?;
+    }
+    Ok(entry)
 }
 
 #[tauri::command]
@@ -38,33 +348,34 @@ pub fn is_bmad_project(path: String) -> bool {
     BmadParser::is_bmad_project(Path::new(&path))
 }
 
-/// Search for potential bmad-docs directories (1 level deep)
+/// Search for potential bmad-docs directories (1 level deep), looking for
+/// the folder names from the `docsFolderNames` setting. Only folders that
+/// may be added as projects are searched.
 #[tauri::command]
-pub fn find_bmad_docs_candidates(project_path: String) -> Vec<String> {
-    let path = Path::new(&project_path);
+pub fn find_bmad_docs_candidates(scope: ScopeState, project_path: String) -> Result<Vec<String>, String> {
+    let path = &scope
+        .lock()
+        .resolve_granted(Path::new(&project_path))
+        .map_err(|e| e.to_string())?;
+    let folder_names = settings::current().docs_folder_names;
     let mut candidates = Vec::new();
+    let mut add_candidate = |dir: &Path| {
+        let dir = dir.to_string_lossy().to_string();
+        if !candidates.contains(&dir) {
+            candidates.push(dir);
+        }
+    };
 
     // Check root level first
-    let bmad_output = path.join("_bmad-output");
-    if bmad_output.exists() && bmad_output.is_dir() {
-        candidates.push(bmad_output.to_string_lossy().to_string());
-    }
-
-    let bmad_docs = path.join("bmad-docs");
-    if bmad_docs.exists() && bmad_docs.is_dir() {
-        candidates.push(bmad_docs.to_string_lossy().to_string());
-    }
-
-    let dot_bmad = path.join(".bmad");
-    if dot_bmad.exists() && dot_bmad.is_dir() {
-        candidates.push(dot_bmad.to_string_lossy().to_string());
+    for name in &folder_names {
+        let dir = path.join(name);
+        if dir.is_dir() {
+            add_candidate(&dir);
+        }
     }
 
     if BmadParser::is_bmad_docs_dir(path) {
-        let root = path.to_string_lossy().to_string();
-        if !candidates.contains(&root) {
-            candidates.push(root);
-        }
+        add_candidate(path);
     }
 
     // Search 1 level deep
@@ -72,109 +383,268 @@ pub fn find_bmad_docs_candidates(project_path: String) -> Vec<String> {
         for entry in entries.filter_map(|e| e.ok()) {
             let entry_path = entry.path();
             if entry_path.is_dir() {
-                let sub_bmad_output = entry_path.join("_bmad-output");
-                if sub_bmad_output.exists() && sub_bmad_output.is_dir() {
-                    candidates.push(sub_bmad_output.to_string_lossy().to_string());
-                }
-
-                // Check for bmad-docs inside subdirectory
-                let sub_bmad_docs = entry_path.join("bmad-docs");
-                if sub_bmad_docs.exists() && sub_bmad_docs.is_dir() {
-                    candidates.push(sub_bmad_docs.to_string_lossy().to_string());
-                }
-
-                let sub_dot_bmad = entry_path.join(".bmad");
-                if sub_dot_bmad.exists() && sub_dot_bmad.is_dir() {
-                    candidates.push(sub_dot_bmad.to_string_lossy().to_string());
+                for name in &folder_names {
+                    let dir = entry_path.join(name);
+                    if dir.is_dir() {
+                        add_candidate(&dir);
+                    }
                 }
 
                 // Also check if the subdirectory itself looks like a bmad-docs folder
                 let dir_name = entry_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if dir_name == "bmad-docs" || dir_name == ".bmad" || dir_name.contains("bmad") {
+                if folder_names.iter().any(|name| name == dir_name) || dir_name.contains("bmad") {
                     // Verify it contains typical BMAD files
                     if entry_path.join("prd.md").exists()
                         || entry_path.join("epics").exists()
                         || entry_path.join("sprint-status.yaml").exists()
                         || entry_path.join("architecture.md").exists() {
-                        if !candidates.contains(&entry_path.to_string_lossy().to_string()) {
-                            candidates.push(entry_path.to_string_lossy().to_string());
-                        }
+                        add_candidate(&entry_path);
                     }
                 }
             }
         }
     }
 
-    candidates
+    Ok(candidates)
+}
+
+/// Agents installed in a project's `_bmad` modules, with customizations applied
+#[tauri::command]
+pub fn list_agents(scope: ScopeState, project_path: String) -> Result<Vec<Agent>, String> {
+    BmadParser::parse_agents(&scoped(&scope, &project_path)?).map_err(|e| e.to_string())
+}
+
+/// Teams defined in a project's `_bmad` modules, with their members and
+/// party rosters linked to the installed agents
+#[tauri::command]
+pub fn list_teams(scope: ScopeState, project_path: String) -> Result<Vec<Team>, String> {
+    BmadParser::parse_teams(&scoped(&scope, &project_path)?).map_err(|e| e.to_string())
+}
+
+/// Workflows installed in a project's `_bmad` modules, grouped by phase,
+/// with resolved inputs and outputs and whether they exist yet
+#[tauri::command]
+pub fn list_workflows(scope: ScopeState, project_path: String) -> Result<Vec<WorkflowGroup>, String> {
+    BmadParser::parse_workflows(&scoped(&scope, &project_path)?).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn get_project_stats(project: BmadProject) -> ProjectStats {
-    let total_epics = project.epics.len();
-    let completed_epics = project.epics.iter().filter(|e| e.status == EpicStatus::Done).count();
-
-    let all_stories: Vec<&Story> = project.epics.iter().flat_map(|e| &e.stories).collect();
-    let total_stories = all_stories.len();
-
-    let mut stories_by_status: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-    stories_by_status.insert("backlog".to_string(), 0);
-    stories_by_status.insert("ready-for-dev".to_string(), 0);
-    stories_by_status.insert("in-progress".to_string(), 0);
-    stories_by_status.insert("review".to_string(), 0);
-    stories_by_status.insert("done".to_string(), 0);
-
-    for story in &all_stories {
-        let status_key = match story.status {
-            StoryStatus::Backlog => "backlog",
-            StoryStatus::ReadyForDev => "ready-for-dev",
-            StoryStatus::InProgress => "in-progress",
-            StoryStatus::Review => "review",
-            StoryStatus::Done => "done",
-        };
-        *stories_by_status.get_mut(status_key).unwrap() += 1;
-    }
+    ProjectStats::from_project(&project)
+}
+
+/// Read a document along with a version token for `write_document`
+#[tauri::command]
+pub fn read_document(scope: ScopeState, file_path: String) -> Result<VersionedDocument, DocumentError> {
+    let path = scope.lock().resolve(Path::new(&file_path))?;
+    writer::document::read_document(&path)
+}
+
+/// Write a document atomically. If the file changed on disk since
+/// `expected_version` was read, the write is rejected with a conflict
+/// carrying the current content. An empty `hash` creates a new document.
+#[tauri::command]
+pub fn write_document(
+    scope: ScopeState,
+    file_path: String,
+    content: String,
+    expected_version: DocumentVersion,
+) -> Result<DocumentVersion, DocumentError> {
+    let path = scope.lock().resolve_writable(Path::new(&file_path))?;
+    writer::document::write_document(&path, &content, &expected_version)
+}
+
+/// Update a story's status in sprint-status.yaml and in the story file's `Status:` line.
+/// `story_key` may be the full key ("2-3-login-form") or the story number ("2.3").
+#[tauri::command]
+pub fn set_story_status(
+    scope: ScopeState,
+    bmad_docs_path: String,
+    story_key: String,
+    status: StoryStatus,
+) -> Result<String, String> {
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    writer::set_story_status(&scope.lock(), &bmad_dir, &story_key, &status).map_err(|e| e.to_string())
+}
+
+/// Tick or untick a task checkbox in a story file and return the reparsed story.
+/// `completed` defaults to flipping the current state.
+#[tauri::command]
+pub fn toggle_story_task(
+    scope: ScopeState,
+    bmad_docs_path: String,
+    file_path: String,
+    task_id: String,
+    completed: Option<bool>,
+) -> Result<Story, String> {
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    let story_path = scoped_writable(&scope, &file_path)?;
+    writer::toggle_story_task(&story_path, &task_id, completed).map_err(|e| e.to_string())?;
+    BmadParser::parse_story_at(&bmad_dir, &story_path).map_err(|e| e.to_string())
+}
 
-    let completed_stories = *stories_by_status.get("done").unwrap_or(&0);
-    let progress_percentage = if total_stories > 0 {
-        ((completed_stories as f64 / total_stories as f64) * 100.0) as u8
-    } else {
-        0
+/// Create a story file from the create-story template and add it to sprint-status.yaml.
+/// `template_path` overrides the project's installed template.
+#[tauri::command]
+pub fn create_story(
+    scope: ScopeState,
+    project_path: String,
+    bmad_docs_path: String,
+    epic_number: u32,
+    story_number: Option<u32>,
+    title: Option<String>,
+    template_path: Option<String>,
+) -> Result<Story, String> {
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    let template_path = template_path
+        .map(|p| scoped(&scope, &p))
+        .transpose()?;
+    let project_path = scoped(&scope, &project_path)?;
+    let story_path = writer::create_story(
+        &scope.lock(),
+        &project_path,
+        &bmad_dir,
+        epic_number,
+        story_number,
+        title.as_deref(),
+        template_path.as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    BmadParser::parse_story_at(&bmad_dir, &story_path).map_err(|e| e.to_string())
+}
+
+/// Move a story to another epic or number, renaming its file and sprint-status key.
+/// `to_number` defaults to the next free number in the target epic.
+#[tauri::command]
+pub fn move_story(
+    scope: ScopeState,
+    bmad_docs_path: String,
+    story_key: String,
+    to_epic: u32,
+    to_number: Option<u32>,
+    renumber_siblings: Option<bool>,
+) -> Result<Vec<StoryRename>, String> {
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    writer::move_story(
+        &scope.lock(),
+        &bmad_dir,
+        &story_key,
+        to_epic,
+        to_number,
+        renumber_siblings.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Generate or refresh sprint-status.yaml from the epics. With `dry_run` the
+/// file is left alone and the result only previews the changes.
+#[tauri::command]
+pub fn plan_sprint_status(
+    scope: ScopeState,
+    project_path: String,
+    bmad_docs_path: String,
+    dry_run: Option<bool>,
+) -> Result<SprintPlan, String> {
+    let project_path = scoped(&scope, &project_path)?;
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    writer::plan_sprint_status(&scope.lock(), &project_path, &bmad_dir, dry_run.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Revisions recorded for a file before the app changed it, newest first
+#[tauri::command]
+pub fn list_file_history(scope: ScopeState, file_path: String) -> Result<Vec<FileRevision>, String> {
+    let path = scoped(&scope, &file_path)?;
+    history::store()
+        .and_then(|store| store.list(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Diff two revisions of a file; `to_revision` defaults to the file as it is now
+#[tauri::command]
+pub fn diff_file_revisions(
+    scope: ScopeState,
+    file_path: String,
+    from_revision: String,
+    to_revision: Option<String>,
+) -> Result<RevisionDiff, String> {
+    let path = &scoped(&scope, &file_path)?;
+    let store = history::store().map_err(|e| e.to_string())?;
+    let old = store.content(path, &from_revision).map_err(|e| e.to_string())?;
+    let (new, new_label) = match &to_revision {
+        Some(id) => (store.content(path, id).map_err(|e| e.to_string())?, id.as_str()),
+        None => (std::fs::read_to_string(path).unwrap_or_default(), "current"),
     };
 
-    ProjectStats {
-        total_epics,
-        completed_epics,
-        total_stories,
-        stories_by_status,
-        progress_percentage,
-    }
+    Ok(history::diff(&old, &new, &from_revision, new_label))
 }
 
+/// Put a file back to an earlier revision. The content being replaced is
+/// recorded too, so a restore can itself be undone.
 #[tauri::command]
-pub fn read_document(file_path: String) -> Result<String, String> {
-    std::fs::read_to_string(&file_path).map_err(|e| e.to_string())
+pub fn restore_file_revision(
+    scope: ScopeState,
+    file_path: String,
+    revision_id: String,
+) -> Result<DocumentVersion, String> {
+    let path = &scoped_writable(&scope, &file_path)?;
+    let content = history::store()
+        .and_then(|store| store.content(path, &revision_id))
+        .map_err(|e| e.to_string())?;
+    writer::write_file(path, &content).map_err(|e| e.to_string())?;
+    Ok(writer::document::version_of(path, &content))
 }
 
 #[tauri::command]
-pub fn write_document(file_path: String, content: String) -> Result<(), String> {
-    std::fs::write(&file_path, content).map_err(|e| e.to_string())
+pub fn set_epic_status(
+    scope: ScopeState,
+    bmad_docs_path: String,
+    epic_number: u32,
+    status: EpicStatus,
+) -> Result<(), String> {
+    let bmad_dir = scoped(&scope, &bmad_docs_path)?;
+    writer::set_epic_status(&scope.lock(), &bmad_dir, epic_number, &status).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn start_project_watch(
     app: AppHandle,
     state: State<Arc<Mutex<FileWatcherState>>>,
+    scope: ScopeState,
     project_id: String,
-    bmad_docs_path: String,
-) -> Result<(), String> {
+    project_path: String,
+    bmad_docs_path: Option<String>,
+    options: Option<WatchOptions>,
+) -> Result<WatchStatus, String> {
+    let bmad_docs_path = bmad_docs_path
+        .filter(|p| !p.is_empty())
+        .map(|p| scoped(&scope, &p))
+        .transpose()?;
     watcher::start_watching(
         app,
         state.inner().clone(),
         project_id,
-        PathBuf::from(bmad_docs_path),
+        scoped(&scope, &project_path)?,
+        bmad_docs_path,
+        options.unwrap_or_default(),
     )
 }
 
+#[tauri::command]
+pub fn restart_project_watch(
+    app: AppHandle,
+    state: State<Arc<Mutex<FileWatcherState>>>,
+    project_id: String,
+) -> Result<WatchStatus, String> {
+    watcher::restart_watching(app, state.inner().clone(), project_id)
+}
+
+#[tauri::command]
+pub fn list_project_watches(state: State<Arc<Mutex<FileWatcherState>>>) -> Vec<WatcherInfo> {
+    watcher::list_watchers(state.inner().clone())
+}
+
 #[tauri::command]
 pub fn stop_project_watch(
     state: State<Arc<Mutex<FileWatcherState>>>,
@@ -188,6 +658,107 @@ pub fn stop_all_watchers(state: State<Arc<Mutex<FileWatcherState>>>) {
     watcher::stop_all(state.inner().clone());
 }
 
+/// Last parsed state of every project, for rendering before the first parse finishes
+#[tauri::command]
+pub fn get_cached_projects() -> Result<Vec<BmadProject>, String> {
+    match snapshots::store() {
+        Ok(store) => store.cached_projects().map_err(|e| e.to_string()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// A project's snapshots, oldest first; `since` is an RFC 3339 time and
+/// `limit` keeps only the most recent ones
+#[tauri::command]
+pub fn list_project_snapshots(
+    project_id: String,
+    since: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ProjectSnapshot>, String> {
+    snapshots::store()
+        .and_then(|store| store.list(&project_id, since.as_deref(), limit))
+        .map_err(|e| e.to_string())
+}
+
+/// Story status changes in a project since an RFC 3339 time
+#[tauri::command]
+pub fn get_project_changes_since(project_id: String, since: String) -> Result<ProjectChangesSince, String> {
+    snapshots::store()
+        .and_then(|store| store.changes_since(&project_id, &since))
+        .map_err(|e| e.to_string())
+}
+
+/// Progress, review and blocked stories and staleness of every registered
+/// project, for the cross-project dashboard
+#[tauri::command]
+pub fn get_portfolio_overview(
+    registry: RegistryState,
+    query: Option<PortfolioQuery>,
+) -> PortfolioOverview {
+    // Parsing uncached projects can take a while; do it without holding the registry
+    let entries = registry.lock().list().to_vec();
+    portfolio::overview(&entries, &query.unwrap_or_default())
+}
+
+/// Projects background discovery found that are not registered or dismissed yet
+#[tauri::command]
+pub fn list_discovered_projects(registry: RegistryState) -> Vec<DiscoveredProject> {
+    discovery::pending(&registry.lock())
+}
+
+#[tauri::command]
+pub fn dismiss_discovered_project(project_path: String) {
+    discovery::dismiss(&project_path);
+}
+
+#[tauri::command]
+pub fn get_settings() -> AppSettings {
+    settings::current()
+}
+
+/// Validate and save new settings, then send them as `bmad-settings-changed`.
+/// Running watchers are restarted when the watch settings or the docs folder
+/// names changed, and background discovery when its roots, depth or
+/// schedule did.
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    scope: ScopeState,
+    watchers: WatcherState,
+    settings: AppSettings,
+) -> Result<AppSettings, String> {
+    let previous = settings::current();
+    // Like `scan_projects` roots: a new scan root must be a picked folder
+    for root in &settings.scan_roots {
+        let root = root.trim();
+        if !root.is_empty() && !previous.scan_roots.iter().any(|r| r == root) {
+            scope.lock().resolve_granted(Path::new(root)).map_err(|e| e.to_string())?;
+        }
+    }
+    let settings = settings::update(settings).map_err(|e| e.to_string())?;
+    scope.lock().allow_write_extensions(&settings.writable_extensions);
+
+    if settings.auto_discover != previous.auto_discover
+        || settings.discover_interval_secs != previous.discover_interval_secs
+        || settings.scan_roots != previous.scan_roots
+        || settings.scan_depth != previous.scan_depth
+        || settings.docs_folder_names != previous.docs_folder_names
+    {
+        discovery::reconfigure();
+    }
+
+    if settings.watch != previous.watch || settings.docs_folder_names != previous.docs_folder_names {
+        for info in watcher::list_watchers(watchers.inner().clone()) {
+            if let Err(e) = watcher::restart_watching(app.clone(), watchers.inner().clone(), info.project_id) {
+                eprintln!("Failed to restart watcher: {}", e);
+            }
+        }
+    }
+
+    let _ = app.emit("bmad-settings-changed", &settings);
+    Ok(settings)
+}
+
 #[tauri::command]
 pub fn get_home_directory() -> Result<String, String> {
     dirs::home_dir()
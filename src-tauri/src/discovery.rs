@@ -0,0 +1,340 @@
+use crate::models::{AppSettings, DiscoveredProject};
+use crate::parser::BmadParser;
+use crate::registry::{self, ProjectRegistry};
+use crate::settings;
+use chrono::Utc;
+use notify::event::{EventKind, ModifyKind};
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Wait after a folder appears before scanning, so an installer or a clone
+/// has written the BMAD files by then
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// How long to sleep while discovery is turned off; settings changes wake it up
+const IDLE_WAIT: Duration = Duration::from_secs(3600);
+
+static CONTROL: OnceLock<Sender<Control>> = OnceLock::new();
+
+/// Discovered projects not registered or dismissed yet, for a webview that
+/// missed the events
+static PENDING: Mutex<Vec<DiscoveredProject>> = Mutex::new(Vec::new());
+
+enum Control {
+    /// The settings changed
+    Reconfigure,
+    /// Something appeared under a scan root
+    Rescan,
+}
+
+/// Start the discovery thread; called once at startup, after the registry
+/// is managed. It only scans while the `autoDiscover` setting is on.
+///
+/// Projects already present when discovery (re)starts are the baseline;
+/// any BMAD project that shows up under a scan root afterwards and is not
+/// registered is sent once as `bmad-project-discovered`, with a proposed
+/// docs folder, for the user to add or dismiss. Scans run every
+/// `discoverIntervalSecs`, and right away when a folder is created directly
+/// under a scan root or one of its subfolders.
+pub fn start(app: AppHandle) {
+    let (tx, rx) = mpsc::channel();
+    if CONTROL.set(tx.clone()).is_err() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let mut known: Option<HashSet<PathBuf>> = None;
+        let mut roots = RootWatch::default();
+
+        loop {
+            let settings = settings::current();
+            let wait = if settings.auto_discover {
+                roots.sync(&settings, &tx);
+                for path in newly_found(&mut known, scan(&settings)) {
+                    propose(&app, &path);
+                }
+                Duration::from_secs(settings.discover_interval_secs)
+            } else {
+                roots = RootWatch::default();
+                known = None;
+                IDLE_WAIT
+            };
+
+            match rx.recv_timeout(wait) {
+                Ok(Control::Reconfigure) => {
+                    roots = RootWatch::default();
+                    known = None;
+                }
+                Ok(Control::Rescan) => {
+                    // Let the burst of events from one install or clone settle
+                    loop {
+                        match rx.recv_timeout(SETTLE_DELAY) {
+                            Ok(Control::Rescan) => continue,
+                            Ok(Control::Reconfigure) => {
+                                roots = RootWatch::default();
+                                known = None;
+                            }
+                            Err(RecvTimeoutError::Timeout) => {}
+                            Err(RecvTimeoutError::Disconnected) => return,
+                        }
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    });
+}
+
+/// Restart discovery with the current settings, resetting the baseline
+pub fn reconfigure() {
+    if let Some(control) = CONTROL.get() {
+        let _ = control.send(Control::Reconfigure);
+    }
+}
+
+/// Discovered projects that have not been registered or dismissed
+pub fn pending(registry: &ProjectRegistry) -> Vec<DiscoveredProject> {
+    let mut pending = PENDING.lock();
+    pending.retain(|project| !registry.contains_path(Path::new(&project.path)));
+    pending.clone()
+}
+
+/// The pending discovered project at `project_path`, if any
+pub fn pending_project(project_path: &Path) -> Option<DiscoveredProject> {
+    PENDING
+        .lock()
+        .iter()
+        .find(|project| same_folder(Path::new(&project.path), project_path))
+        .cloned()
+}
+
+/// Stop offering a discovered project; it is not sent again this session
+pub fn dismiss(project_path: &str) {
+    PENDING.lock().retain(|project| project.path != project_path);
+}
+
+/// Every BMAD project under the scan roots, including fresh `_bmad`
+/// installs whose docs folder no workflow has created yet. Docs folders
+/// inside a found project are not projects of their own.
+fn scan(settings: &AppSettings) -> HashSet<PathBuf> {
+    outermost(
+        settings::scan_roots(settings)
+            .iter()
+            .filter(|root| root.is_dir())
+            .flat_map(|root| BmadParser::scan_for_projects(root, settings.scan_depth))
+            .collect(),
+    )
+}
+
+/// The found folders that are not inside another found folder
+fn outermost(found: HashSet<PathBuf>) -> HashSet<PathBuf> {
+    found
+        .iter()
+        .filter(|path| !path.ancestors().skip(1).any(|parent| found.contains(parent)))
+        .cloned()
+        .collect()
+}
+
+/// Projects in `found` that the previous scan did not see. The first scan
+/// (`known` is None) only sets the baseline, so nothing already on disk
+/// when discovery starts is proposed.
+fn newly_found(known: &mut Option<HashSet<PathBuf>>, found: HashSet<PathBuf>) -> Vec<PathBuf> {
+    let new = match known {
+        Some(known) => found.difference(known).cloned().collect(),
+        None => Vec::new(),
+    };
+    *known = Some(found);
+    new
+}
+
+/// The docs folder the parser would pick, else the BMM config's output
+/// folder; None while a fresh install has neither, so the parser decides
+/// once the project is added
+fn docs_dir(project_path: &Path) -> Option<PathBuf> {
+    BmadParser::find_bmad_docs_dir(project_path).or_else(|| {
+        BmadParser::bmm_config_path(project_path, "output_folder").filter(|dir| dir.is_dir())
+    })
+}
+
+fn propose(app: &AppHandle, path: &Path) {
+    let registered = app
+        .try_state::<Arc<Mutex<ProjectRegistry>>>()
+        .is_some_and(|registry| registry.lock().contains_path(path));
+    if registered {
+        return;
+    }
+
+    let project = DiscoveredProject {
+        path: path.to_string_lossy().to_string(),
+        name: registry::project_name(path),
+        bmad_docs_path: docs_dir(path).map(|dir| dir.to_string_lossy().to_string()),
+        discovered_at: Utc::now().to_rfc3339(),
+    };
+    remember(project.clone());
+    let _ = app.emit("bmad-project-discovered", project);
+}
+
+/// Keep a proposed project until it is added or dismissed
+fn remember(project: DiscoveredProject) {
+    let mut pending = PENDING.lock();
+    pending.retain(|p| p.path != project.path);
+    pending.push(project);
+}
+
+/// Non-recursive watches on the scan roots and their direct subfolders.
+///
+/// Watching the roots recursively would cover every file in the user's
+/// workspace (or home directory); one level catches new project folders
+/// and a `_bmad` folder installed into an existing one, and deeper
+/// projects are found by the periodic scan.
+#[derive(Default)]
+struct RootWatch {
+    watcher: Option<RecommendedWatcher>,
+    watched: HashSet<PathBuf>,
+}
+
+impl RootWatch {
+    /// Watch the roots and their current subfolders, starting the watcher if needed
+    fn sync(&mut self, settings: &AppSettings, tx: &Sender<Control>) {
+        if self.watcher.is_none() {
+            let tx = tx.clone();
+            let handler = move |res: notify::Result<Event>| {
+                if res.is_ok_and(|event| appeared(&event)) {
+                    let _ = tx.send(Control::Rescan);
+                }
+            };
+            match RecommendedWatcher::new(handler, Config::default()) {
+                Ok(watcher) => self.watcher = Some(watcher),
+                // Periodic scans still find new projects
+                Err(e) => {
+                    eprintln!("Failed to watch scan roots: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let desired: HashSet<PathBuf> = settings::scan_roots(settings)
+            .into_iter()
+            .filter(|root| root.is_dir())
+            .flat_map(|root| {
+                let children: Vec<PathBuf> = std::fs::read_dir(&root)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir() && !is_hidden(path))
+                    .collect();
+                std::iter::once(root).chain(children)
+            })
+            .collect();
+
+        for path in self.watched.difference(&desired) {
+            let _ = watcher.unwatch(path);
+        }
+        self.watched.retain(|path| desired.contains(path));
+        for path in desired {
+            if !self.watched.contains(&path) && watcher.watch(&path, RecursiveMode::NonRecursive).is_ok() {
+                self.watched.insert(path);
+            }
+        }
+    }
+}
+
+/// A file or folder was created or moved into place
+fn appeared(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+/// Dot folders (caches, `.git`, `.config`) never hold projects of their own
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn same_folder(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (std::fs::canonicalize(a), std::fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> HashSet<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    fn discovered(path: &Path) -> DiscoveredProject {
+        DiscoveredProject {
+            path: path.to_string_lossy().to_string(),
+            name: registry::project_name(path),
+            bmad_docs_path: None,
+            discovered_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn only_projects_after_the_baseline_are_new() {
+        let mut known = None;
+        assert!(newly_found(&mut known, paths(&["/work/a", "/work/b"])).is_empty());
+        assert!(newly_found(&mut known, paths(&["/work/a", "/work/b"])).is_empty());
+        assert_eq!(newly_found(&mut known, paths(&["/work/a", "/work/c"])), [PathBuf::from("/work/c")]);
+        // A project that went away and came back is new again
+        assert_eq!(
+            newly_found(&mut known, paths(&["/work/a", "/work/b", "/work/c"])),
+            [PathBuf::from("/work/b")]
+        );
+
+        // Reconfiguring resets the baseline
+        let mut known = None;
+        assert!(newly_found(&mut known, paths(&["/work/d"])).is_empty());
+    }
+
+    #[test]
+    fn docs_folders_inside_a_project_are_not_projects() {
+        assert_eq!(
+            outermost(paths(&["/work/a", "/work/a/_bmad-output", "/work/b", "/work/ab"])),
+            paths(&["/work/a", "/work/b", "/work/ab"])
+        );
+    }
+
+    #[test]
+    fn pending_projects_leave_when_added_or_dismissed() {
+        let base = std::env::temp_dir().join(format!("bmad-discovery-{}", uuid::Uuid::new_v4()));
+        let added = base.join("added");
+        let dismissed = base.join("dismissed");
+        std::fs::create_dir_all(&added).unwrap();
+        std::fs::create_dir_all(&dismissed).unwrap();
+        let mut registry = ProjectRegistry::load(base.join("projects.json")).unwrap();
+
+        remember(discovered(&added));
+        remember(discovered(&dismissed));
+        remember(discovered(&dismissed));
+        let is_ours = |p: &DiscoveredProject| Path::new(&p.path).starts_with(&base);
+        assert_eq!(pending(&registry).iter().filter(|p| is_ours(p)).count(), 2);
+        assert!(pending_project(&added).is_some());
+
+        registry.add(&added, None, None).unwrap();
+        dismiss(&dismissed.to_string_lossy());
+        assert_eq!(pending(&registry).iter().filter(|p| is_ours(p)).count(), 0);
+        assert!(pending_project(&added).is_none());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::models::{FileRevision, RevisionDiff};
+use crate::writer::document::content_hash;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Revisions kept per file; older ones are pruned on the next write
+const MAX_REVISIONS: usize = 100;
+
+/// Folders that mark the root of a project the history is grouped under
+const PROJECT_MARKERS: &[&str] = &["_bmad", "_bmad-output", "bmad-docs", ".bmad", ".git"];
+
+static STORE: OnceLock<HistoryStore> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid history index: {0}")]
+    InvalidIndex(#[from] serde_json::Error),
+    #[error("History is not available")]
+    Unavailable,
+    #[error("Revision not found: {0}")]
+    UnknownRevision(String),
+}
+
+/// Revisions recorded for one file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryIndex {
+    file_path: String,
+    revisions: Vec<FileRevision>,
+}
+
+/// Per-project store of file contents taken before each app write.
+///
+/// Layout: `<root>/<project>-<hash>/<file hash>/index.json` plus one
+/// `<revision id>.snapshot` per revision.
+pub struct HistoryStore {
+    root: PathBuf,
+}
+
+/// Set up the store under the app data directory; called once at startup
+pub fn init(root: PathBuf) {
+    let _ = STORE.set(HistoryStore { root });
+}
+
+pub fn store() -> Result<&'static HistoryStore, HistoryError> {
+    STORE.get().ok_or(HistoryError::Unavailable)
+}
+
+/// Record the current content of `path` before the app overwrites or removes it.
+///
+/// Does nothing if history is not set up or the file does not exist yet.
+pub fn record(path: &Path) -> Result<(), HistoryError> {
+    match STORE.get() {
+        Some(store) => store.record(path),
+        None => Ok(()),
+    }
+}
+
+impl HistoryStore {
+    pub fn record(&self, path: &Path) -> Result<(), HistoryError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let file_path = canonical(path);
+        let dir = self.file_dir(&file_path);
+        let mut index = Self::load_index(&dir, &file_path)?;
+
+        let hash = content_hash(&content);
+        if index.revisions.last().map(|r| &r.hash) == Some(&hash) {
+            return Ok(());
+        }
+
+        let now = Local::now();
+        let id = format!(
+            "{}-{}",
+            now.format("%Y%m%dT%H%M%S%3f"),
+            &uuid::Uuid::new_v4().simple().to_string()[..6]
+        );
+
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{}.snapshot", id)), &content)?;
+        index.revisions.push(FileRevision {
+            id,
+            file_path: file_path.to_string_lossy().to_string(),
+            created_at: now.to_rfc3339(),
+            hash,
+            size: content.len() as u64,
+        });
+
+        if index.revisions.len() > MAX_REVISIONS {
+            let pruned = index.revisions.len() - MAX_REVISIONS;
+            for revision in index.revisions.drain(..pruned) {
+                let _ = fs::remove_file(dir.join(format!("{}.snapshot", revision.id)));
+            }
+        }
+
+        fs::write(dir.join("index.json"), serde_json::to_string_pretty(&index)?)?;
+        Ok(())
+    }
+
+    /// Revisions for a file, newest first
+    pub fn list(&self, path: &Path) -> Result<Vec<FileRevision>, HistoryError> {
+        let file_path = canonical(path);
+        let mut revisions = Self::load_index(&self.file_dir(&file_path), &file_path)?.revisions;
+        revisions.reverse();
+        Ok(revisions)
+    }
+
+    pub fn content(&self, path: &Path, revision_id: &str) -> Result<String, HistoryError> {
+        let file_path = canonical(path);
+        let dir = self.file_dir(&file_path);
+        let index = Self::load_index(&dir, &file_path)?;
+        if !index.revisions.iter().any(|r| r.id == revision_id) {
+            return Err(HistoryError::UnknownRevision(revision_id.to_string()));
+        }
+        Ok(fs::read_to_string(dir.join(format!("{}.snapshot", revision_id)))?)
+    }
+
+    fn file_dir(&self, file_path: &Path) -> PathBuf {
+        let project = project_root(file_path);
+        let project_name = project
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "root".to_string());
+        let project_key = format!(
+            "{}-{}",
+            project_name,
+            &content_hash(&project.to_string_lossy())[..12]
+        );
+
+        self.root
+            .join(project_key)
+            .join(&content_hash(&file_path.to_string_lossy())[..16])
+    }
+
+    fn load_index(dir: &Path, file_path: &Path) -> Result<HistoryIndex, HistoryError> {
+        match fs::read_to_string(dir.join("index.json")) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HistoryIndex {
+                file_path: file_path.to_string_lossy().to_string(),
+                revisions: Vec::new(),
+            }),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Unified diff between two versions of a file
+pub fn diff(old: &str, new: &str, old_label: &str, new_label: &str) -> RevisionDiff {
+    let text_diff = TextDiff::from_lines(old, new);
+    let (mut additions, mut deletions) = (0, 0);
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    RevisionDiff {
+        unified: text_diff
+            .unified_diff()
+            .context_radius(3)
+            .header(old_label, new_label)
+            .to_string(),
+        additions,
+        deletions,
+    }
+}
+
+/// Resolve symlinks so every path to a file shares one history; files that
+/// no longer exist keep their canonical parent
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| {
+        match (path.parent().and_then(|p| fs::canonicalize(p).ok()), path.file_name()) {
+            (Some(parent), Some(name)) => parent.join(name),
+            _ => path.to_path_buf(),
+        }
+    })
+}
+
+/// Nearest ancestor that looks like a project root, else the file's folder
+fn project_root(file_path: &Path) -> PathBuf {
+    let parent = file_path.parent().unwrap_or(file_path);
+    parent
+        .ancestors()
+        .find(|dir| PROJECT_MARKERS.iter().any(|marker| dir.join(marker).is_dir()))
+        .unwrap_or(parent)
+        .to_path_buf()
+}
@@ -1,33 +1,106 @@
 mod commands;
+mod discovery;
+mod history;
 mod models;
 mod parser;
+mod portfolio;
+mod registry;
+mod scope;
+mod settings;
+mod snapshots;
 mod watcher;
+mod writer;
 
 use commands::*;
 use parking_lot::Mutex;
+use registry::ProjectRegistry;
+use scope::ProjectScope;
+use std::path::Path;
 use std::sync::Arc;
+use tauri::Manager;
 use watcher::FileWatcherState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let watcher_state = Arc::new(Mutex::new(FileWatcherState::default()));
+    let project_scope = Arc::new(Mutex::new(ProjectScope::default()));
 
     tauri::Builder::default()
-        .manage(watcher_state)
+        .manage(watcher_state.clone())
+        .manage(project_scope.clone())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .setup(move |app| {
+            let data_dir = app.path().app_data_dir()?;
+            history::init(data_dir.join("history"));
+            settings::init(data_dir.join("settings.json"));
+            project_scope
+                .lock()
+                .allow_write_extensions(&settings::current().writable_extensions);
+            snapshots::init(data_dir.join("snapshots.db"));
+
+            // Reopen and watch every registered project
+            let registry = ProjectRegistry::load_or_empty(data_dir.join("projects.json"));
+            for entry in registry.list() {
+                // Docs folders in projects.json passed `add_project` or
+                // `update_project`, which only accept granted folders
+                if let Some(docs_path) = &entry.bmad_docs_path {
+                    let _ = project_scope.lock().grant(Path::new(docs_path));
+                }
+                if let Err(e) = open_project_entry(
+                    app.handle().clone(),
+                    &project_scope,
+                    watcher_state.clone(),
+                    entry,
+                ) {
+                    eprintln!("Failed to open project {}: {}", entry.path, e);
+                }
+            }
+            app.manage(Arc::new(Mutex::new(registry)));
+            discovery::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             scan_projects,
             parse_project,
+            pick_folder,
+            grant_project_folders,
+            close_project,
+            list_projects,
+            add_project,
+            remove_project,
+            update_project,
             is_bmad_project,
             find_bmad_docs_candidates,
             get_project_stats,
+            list_agents,
+            list_teams,
+            list_workflows,
             read_document,
             write_document,
+            set_story_status,
+            set_epic_status,
+            toggle_story_task,
+            create_story,
+            move_story,
+            plan_sprint_status,
+            list_file_history,
+            diff_file_revisions,
+            restore_file_revision,
             start_project_watch,
+            restart_project_watch,
+            list_project_watches,
             stop_project_watch,
             stop_all_watchers,
+            get_cached_projects,
+            list_project_snapshots,
+            get_project_changes_since,
+            get_portfolio_overview,
+            list_discovered_projects,
+            dismiss_discovered_project,
+            get_settings,
+            update_settings,
             get_home_directory,
         ])
         .run(tauri::generate_context!())
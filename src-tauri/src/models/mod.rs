@@ -8,6 +8,17 @@ pub enum EpicStatus {
     Done,
 }
 
+impl EpicStatus {
+    /// Value as written in sprint-status.yaml
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EpicStatus::Backlog => "backlog",
+            EpicStatus::InProgress => "in-progress",
+            EpicStatus::Done => "done",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum StoryStatus {
@@ -18,6 +29,19 @@ pub enum StoryStatus {
     Done,
 }
 
+impl StoryStatus {
+    /// Value as written in sprint-status.yaml and story files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StoryStatus::Backlog => "backlog",
+            StoryStatus::ReadyForDev => "ready-for-dev",
+            StoryStatus::InProgress => "in-progress",
+            StoryStatus::Review => "review",
+            StoryStatus::Done => "done",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum RetrospectiveStatus {
@@ -41,6 +65,8 @@ pub struct Task {
     pub id: String,
     pub title: String,
     pub completed: bool,
+    #[serde(default)]
+    pub subtasks: Vec<Task>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +82,9 @@ pub struct Story {
     #[serde(default)]
     pub acceptance_criteria: Vec<AcceptanceCriteria>,
     pub status: StoryStatus,
+    /// Marked `blocked` in sprint-status.yaml or its `Status:` line
+    #[serde(default)]
+    pub blocked: bool,
     #[serde(default)]
     pub tasks: Vec<Task>,
     pub file_path: Option<String>,
@@ -63,6 +92,16 @@ pub struct Story {
     pub updated_at: String,
 }
 
+/// A story whose key and number changed during a move or renumbering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryRename {
+    pub from_key: String,
+    pub to_key: String,
+    pub from_number: String,
+    pub to_number: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Epic {
@@ -94,12 +133,70 @@ pub struct BmadDocument {
     pub updated_at: String,
 }
 
+/// Version token for a file on disk, used to detect concurrent edits
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentVersion {
+    /// SHA-256 of the file content
+    pub hash: String,
+    /// Last modification time (RFC 3339)
+    pub modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedDocument {
+    pub content: String,
+    pub version: DocumentVersion,
+}
+
+/// A saved copy of a file taken before the app overwrote or removed it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRevision {
+    pub id: String,
+    pub file_path: String,
+    pub created_at: String,
+    /// SHA-256 of the saved content
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Result of generating sprint-status.yaml from the epics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintPlan {
+    pub file_path: String,
+    /// The file did not exist before
+    pub created: bool,
+    /// False for a dry run or when nothing changed
+    pub written: bool,
+    pub added_keys: Vec<String>,
+    /// Stories moved from backlog to ready-for-dev because their file exists
+    pub upgraded_keys: Vec<String>,
+    /// Stories tracked in sprint-status.yaml that no epic lists any more
+    pub unplanned_keys: Vec<String>,
+    pub diff: RevisionDiff,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiff {
+    /// Unified diff from the older to the newer content
+    pub unified: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EpicSprintStatus {
     pub status: EpicStatus,
     #[serde(default)]
     pub stories: std::collections::HashMap<String, StoryStatus>,
+    /// Story keys whose value is `blocked`; `stories` reads them as backlog
+    #[serde(default)]
+    pub blocked: Vec<String>,
     pub retrospective: Option<RetrospectiveStatus>,
 }
 
@@ -130,10 +227,171 @@ pub struct BmadProject {
     #[serde(default)]
     pub documents: Vec<BmadDocument>,
     pub sprint_status: Option<SprintStatus>,
-    pub last_activity: String,
+    /// Newest modification time of the project's files; None if none could be read
+    pub last_activity: Option<String>,
     pub created_at: String,
 }
 
+/// One semantic change between two parses of a project
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ProjectChange {
+    #[serde(rename_all = "camelCase")]
+    EpicAdded { epic_number: u32, title: String },
+    #[serde(rename_all = "camelCase")]
+    EpicRemoved { epic_number: u32 },
+    #[serde(rename_all = "camelCase")]
+    EpicStatusChanged {
+        epic_number: u32,
+        from: EpicStatus,
+        to: EpicStatus,
+    },
+    /// Title, goal or retrospective changed
+    #[serde(rename_all = "camelCase")]
+    EpicUpdated { epic_number: u32 },
+    #[serde(rename_all = "camelCase")]
+    StoryAdded {
+        story_number: String,
+        title: String,
+        status: StoryStatus,
+    },
+    #[serde(rename_all = "camelCase")]
+    StoryRemoved { story_number: String },
+    #[serde(rename_all = "camelCase")]
+    StoryStatusChanged {
+        story_number: String,
+        from: StoryStatus,
+        to: StoryStatus,
+    },
+    /// Title, user story, acceptance criteria or file changed
+    #[serde(rename_all = "camelCase")]
+    StoryUpdated { story_number: String },
+    #[serde(rename_all = "camelCase")]
+    TaskAdded {
+        story_number: String,
+        task_id: String,
+        title: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskRemoved { story_number: String, task_id: String },
+    #[serde(rename_all = "camelCase")]
+    TaskToggled {
+        story_number: String,
+        task_id: String,
+        completed: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentAdded {
+        file_path: String,
+        doc_type: String,
+        title: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentRemoved { file_path: String },
+    #[serde(rename_all = "camelCase")]
+    DocumentUpdated { file_path: String },
+}
+
+/// Per-project file watcher settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOptions {
+    /// Poll even if native watching works, e.g. for network mounts where
+    /// inotify stays silent
+    #[serde(default)]
+    pub force_poll: bool,
+    pub poll_interval_ms: Option<u64>,
+}
+
+/// A project in the backend registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEntry {
+    pub id: String,
+    pub path: String,
+    /// Docs folder chosen by the user; detected when unset
+    pub bmad_docs_path: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Archived projects stay registered but are not watched
+    #[serde(default)]
+    pub archived: bool,
+    pub added_at: String,
+    #[serde(default)]
+    pub watch_options: WatchOptions,
+}
+
+/// Fields to change on a registry entry; unset fields are left alone
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEntryUpdate {
+    pub name: Option<String>,
+    /// An empty string clears the custom docs folder
+    pub bmad_docs_path: Option<String>,
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+    pub watch_options: Option<WatchOptions>,
+}
+
+/// App-wide settings for scanning, parsing and watching. Fields missing
+/// from the settings file take their default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// Folders `scan_projects` searches; the home directory when empty
+    pub scan_roots: Vec<String>,
+    pub scan_depth: usize,
+    /// How project folders are watched; running watchers restart when it changes
+    pub watch: WatchSettings,
+    /// File extensions the document commands may write besides Markdown and
+    /// YAML; only ones in `scope::OPTIONAL_WRITE_EXTENSIONS` are accepted
+    pub writable_extensions: Vec<String>,
+    /// Folder names looked for when detecting a project's docs folder, in order of preference
+    pub docs_folder_names: Vec<String>,
+    /// Days without activity after which the portfolio marks a project stale
+    pub stale_after_days: u32,
+    /// Look for new projects under the scan roots in the background
+    pub auto_discover: bool,
+    /// Seconds between background scans; new folders directly under a
+    /// scan root are also picked up as they appear
+    pub discover_interval_secs: u64,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            scan_roots: Vec::new(),
+            scan_depth: 3,
+            watch: WatchSettings::default(),
+            writable_extensions: Vec::new(),
+            docs_folder_names: ["_bmad-output", "bmad-docs", ".bmad"].map(String::from).to_vec(),
+            stale_after_days: 14,
+            auto_discover: false,
+            discover_interval_secs: 300,
+        }
+    }
+}
+
+/// Watcher settings, grouped so that a change to any of them restarts the watchers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchSettings {
+    /// Quiet period after the last file change before the watcher reports a batch
+    pub debounce_ms: u64,
+    /// File extensions (without the dot) whose changes the watcher reports
+    pub extensions: Vec<String>,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 500,
+            extensions: ["md", "yaml", "yml", "csv"].map(String::from).to_vec(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectStats {
@@ -143,3 +401,325 @@ pub struct ProjectStats {
     pub stories_by_status: std::collections::HashMap<String, usize>,
     pub progress_percentage: u8,
 }
+
+impl ProjectStats {
+    pub fn from_project(project: &BmadProject) -> Self {
+        let total_epics = project.epics.len();
+        let completed_epics = project.epics.iter().filter(|e| e.status == EpicStatus::Done).count();
+
+        let all_stories: Vec<&Story> = project.epics.iter().flat_map(|e| &e.stories).collect();
+        let total_stories = all_stories.len();
+
+        let mut stories_by_status: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        stories_by_status.insert("backlog".to_string(), 0);
+        stories_by_status.insert("ready-for-dev".to_string(), 0);
+        stories_by_status.insert("in-progress".to_string(), 0);
+        stories_by_status.insert("review".to_string(), 0);
+        stories_by_status.insert("done".to_string(), 0);
+
+        for story in &all_stories {
+            *stories_by_status.get_mut(story.status.as_str()).unwrap() += 1;
+        }
+
+        let completed_stories = *stories_by_status.get("done").unwrap_or(&0);
+        let progress_percentage = if total_stories > 0 {
+            ((completed_stories as f64 / total_stories as f64) * 100.0) as u8
+        } else {
+            0
+        };
+
+        ProjectStats {
+            total_epics,
+            completed_epics,
+            total_stories,
+            stories_by_status,
+            progress_percentage,
+        }
+    }
+}
+
+/// A project's state at one point in time, kept in the snapshot store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSnapshot {
+    pub id: i64,
+    pub project_id: String,
+    pub taken_at: String,
+    pub phase: u8,
+    pub stats: ProjectStats,
+    /// Story status by story number ("1.2")
+    pub story_statuses: std::collections::BTreeMap<String, StoryStatus>,
+}
+
+/// A story whose status differs between two snapshots; `None` when the
+/// story did not exist on that side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryStatusChange {
+    pub story_number: String,
+    pub from: Option<StoryStatus>,
+    pub to: Option<StoryStatus>,
+}
+
+/// What changed in a project between the last snapshot taken at or before
+/// `since` and the latest one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectChangesSince {
+    pub project_id: String,
+    pub since: String,
+    pub baseline: Option<ProjectSnapshot>,
+    pub latest: Option<ProjectSnapshot>,
+    pub story_changes: Vec<StoryStatusChange>,
+}
+
+/// A BMAD project that appeared under a scan root and is not registered yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredProject {
+    pub path: String,
+    pub name: String,
+    /// Docs folder to register it with; `None` to let the parser detect it
+    pub bmad_docs_path: Option<String>,
+    pub discovered_at: String,
+}
+
+/// Ordering of the portfolio overview
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PortfolioSort {
+    /// Pinned projects first, then by name
+    #[default]
+    Pinned,
+    Name,
+    Progress,
+    LastActivity,
+    Staleness,
+    Phase,
+}
+
+/// Which registered projects `get_portfolio_overview` returns and in what order
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PortfolioQuery {
+    pub sort_by: PortfolioSort,
+    pub descending: bool,
+    /// Case-insensitive match on the project name or path
+    pub search: Option<String>,
+    pub phase: Option<u8>,
+    pub stale_only: bool,
+    pub include_archived: bool,
+}
+
+/// Story reference in the portfolio, without its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryRef {
+    pub number: String,
+    pub title: String,
+}
+
+/// One project's line in the portfolio overview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub pinned: bool,
+    pub archived: bool,
+    pub phase: u8,
+    pub progress_percentage: u8,
+    pub total_epics: usize,
+    pub total_stories: usize,
+    pub done_stories: usize,
+    pub in_progress_stories: usize,
+    pub review_stories: Vec<StoryRef>,
+    /// Stories marked `blocked` in sprint-status.yaml or their story file
+    pub blocked_stories: Vec<StoryRef>,
+    pub last_activity: Option<String>,
+    /// Whole days since the last activity
+    pub idle_days: Option<i64>,
+    pub stale: bool,
+    /// Set when the project could not be read; the counts are then zero
+    pub error: Option<String>,
+}
+
+/// Totals over the projects in the overview
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioTotals {
+    pub projects: usize,
+    pub stale_projects: usize,
+    pub total_stories: usize,
+    pub done_stories: usize,
+    pub in_progress_stories: usize,
+    pub review_stories: usize,
+    pub blocked_stories: usize,
+    pub progress_percentage: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioOverview {
+    pub generated_at: String,
+    pub projects: Vec<PortfolioProject>,
+    pub totals: PortfolioTotals,
+}
+
+/// An agent installed in a project's `_bmad/<module>/agents` folder, with
+/// the project's customization applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Agent {
+    /// Agent file name without the extension, e.g. `dev`
+    pub id: String,
+    /// Module the agent comes from: `core`, `bmm` or a custom one
+    pub module: String,
+    /// Persona name, e.g. `Amelia`
+    pub name: String,
+    pub title: String,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub persona: AgentPersona,
+    pub menu: Vec<AgentMenuItem>,
+    pub file_path: String,
+    /// Changed by `_bmad/_config/agents/<module>-<id>.customize.yaml`
+    pub customized: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentPersona {
+    pub role: String,
+    pub identity: String,
+    pub communication_style: String,
+    pub principles: Vec<String>,
+}
+
+/// An entry in an agent's menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentMenuItem {
+    /// Short command typed to run it, e.g. `DS`
+    pub trigger: String,
+    /// Phrase the agent also accepts, e.g. `dev-story`
+    pub fuzzy_match: Option<String>,
+    pub description: String,
+    /// Workflow file the item runs, with `{project-root}` resolved
+    pub workflow: Option<String>,
+    /// Task or workflow file executed directly
+    pub exec: Option<String>,
+    /// Inline prompt or `#id` of a prompt in the agent file
+    pub action: Option<String>,
+}
+
+/// A workflow installed in a project's `_bmad/<module>/workflows` folder,
+/// with its paths resolved against the project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workflow {
+    /// Folder the workflow lives in, e.g. `create-story`
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub author: Option<String>,
+    pub module: String,
+    /// Phase folder under `workflows`, e.g. `4-implementation` or `testarch`
+    pub phase: Option<String>,
+    /// `workflow.yaml`, or `workflow.md` for step-file workflows
+    pub file_path: String,
+    pub standalone: bool,
+    pub instructions: Option<String>,
+    pub template: Option<String>,
+    /// Validation checklist
+    pub validation: Option<String>,
+    /// Values the workflow takes from its module config (`{config_source}:key`)
+    pub config: std::collections::BTreeMap<String, String>,
+    pub variables: std::collections::BTreeMap<String, String>,
+    pub inputs: Vec<WorkflowInput>,
+    pub outputs: Vec<WorkflowOutput>,
+}
+
+/// A file a workflow reads, from `input_file_patterns` or `required_inputs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowInput {
+    pub key: String,
+    pub description: Option<String>,
+    /// Path or glob of the whole document
+    pub pattern: Option<String>,
+    /// Glob of the sharded version of the document
+    pub sharded: Option<String>,
+    pub load_strategy: Option<String>,
+    /// Some file matches the whole or sharded pattern
+    pub exists: bool,
+}
+
+/// A file a workflow writes. Placeholders filled at run time
+/// (`{{story_key}}`, `{date}`) are kept; they match any name when checking
+/// for existing outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowOutput {
+    pub key: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Workflows in one phase folder; `phase` is `None` for workflows placed
+/// directly in a module's `workflows` folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowGroup {
+    pub phase: Option<String>,
+    pub workflows: Vec<Workflow>,
+}
+
+/// A team from `_bmad/<module>/teams/*.yaml`: which agents work together,
+/// in order, and the roster used for party mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Team {
+    /// Team file name without the extension, e.g. `team-fullstack`
+    pub id: String,
+    pub module: String,
+    pub name: String,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub members: Vec<TeamMember>,
+    pub party: Option<PartyRoster>,
+    pub file_path: String,
+}
+
+/// An agent named by a team, linked to the installed agent if there is one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMember {
+    pub agent_id: String,
+    /// Module of the installed agent
+    pub module: Option<String>,
+    pub installed: bool,
+}
+
+/// A party-mode roster CSV (e.g. `default-party.csv`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyRoster {
+    pub file_path: String,
+    pub members: Vec<PartyMember>,
+}
+
+/// A row of a party roster; agents from modules the project does not have
+/// are listed but not installed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyMember {
+    pub agent_id: String,
+    pub display_name: String,
+    pub title: String,
+    pub icon: Option<String>,
+    pub role: String,
+    pub module: String,
+    pub installed: bool,
+}
@@ -0,0 +1,307 @@
+use super::{front_matter, BmadParser, ParseError};
+use crate::models::{Agent, AgentMenuItem, AgentPersona};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Folder in `_bmad` holding installer state rather than a module
+const CONFIG_DIR: &str = "_config";
+
+/// Front matter of an agent file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AgentFrontMatter {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+/// `_bmad/_config/agents/<module>-<id>.customize.yaml`. Empty values mean
+/// "keep the installed one".
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AgentCustomization {
+    agent: CustomAgent,
+    persona: CustomPersona,
+    menu: Vec<CustomMenuItem>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CustomAgent {
+    metadata: CustomMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CustomMetadata {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CustomPersona {
+    role: String,
+    identity: String,
+    communication_style: String,
+    principles: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CustomMenuItem {
+    trigger: String,
+    description: String,
+    workflow: Option<String>,
+    exec: Option<String>,
+    action: Option<String>,
+}
+
+impl BmadParser {
+    /// Agents installed in `_bmad/*/agents`, by module then file name.
+    ///
+    /// Each agent file embeds its definition as XML (`<agent>` with a
+    /// `<persona>` and a `<menu>`); customizations from
+    /// `_bmad/_config/agents` are applied on top. Files without an
+    /// `<agent>` element are skipped.
+    pub fn parse_agents(project_path: &Path) -> Result<Vec<Agent>, ParseError> {
+        let bmad_dir = project_path.join("_bmad");
+        if !bmad_dir.is_dir() {
+            return Err(ParseError::InvalidStructure(format!(
+                "No _bmad folder in {}",
+                project_path.display()
+            )));
+        }
+
+        let mut modules: Vec<_> = fs::read_dir(&bmad_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("agents").is_dir() && !path.ends_with(CONFIG_DIR))
+            .collect();
+        modules.sort();
+
+        let mut agents = Vec::new();
+        for module_dir in modules {
+            let module = module_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut files: Vec<_> = fs::read_dir(module_dir.join("agents"))?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+                .collect();
+            files.sort();
+
+            for file in files {
+                let content = match fs::read_to_string(&file) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        eprintln!("Failed to read agent {}: {}", file.display(), e);
+                        continue;
+                    }
+                };
+                let id = file
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if let Some(mut agent) = Self::parse_agent(project_path, &module, &id, &content) {
+                    agent.file_path = file.to_string_lossy().to_string();
+                    Self::customize_agent(project_path, &mut agent);
+                    agents.push(agent);
+                }
+            }
+        }
+
+        Ok(agents)
+    }
+
+    fn parse_agent(project_path: &Path, module: &str, id: &str, content: &str) -> Option<Agent> {
+        let agent_regex = Regex::new(r"<agent\s([^>]*)>").unwrap();
+        let attributes = xml_attributes(agent_regex.captures(content)?.get(1)?.as_str());
+        let front_matter = front_matter(content)
+            .and_then(|yaml| serde_yaml::from_str::<AgentFrontMatter>(yaml).ok())
+            .unwrap_or_default();
+
+        let persona_regex = Regex::new(r"(?s)<persona>(.*?)</persona>").unwrap();
+        let persona = persona_regex
+            .captures(content)
+            .map(|caps| {
+                let body = &caps[1];
+                AgentPersona {
+                    role: xml_element(body, "role").unwrap_or_default(),
+                    identity: xml_element(body, "identity").unwrap_or_default(),
+                    communication_style: xml_element(body, "communication_style").unwrap_or_default(),
+                    principles: xml_element(body, "principles")
+                        .map(|text| split_principles(&text))
+                        .unwrap_or_default(),
+                }
+            })
+            .unwrap_or_default();
+
+        let menu_regex = Regex::new(r"(?s)<menu>(.*?)</menu>").unwrap();
+        let item_regex = Regex::new(r"(?s)<item\s([^>]*)>(.*?)</item>").unwrap();
+        let menu = menu_regex
+            .captures(content)
+            .map(|caps| {
+                item_regex
+                    .captures_iter(&caps[1])
+                    .filter_map(|item| {
+                        let attributes = xml_attributes(&item[1]);
+                        let (trigger, fuzzy_match) = split_command(attributes.get("cmd")?);
+                        Some(AgentMenuItem {
+                            trigger,
+                            fuzzy_match,
+                            description: menu_label(&decode_entities(&item[2])),
+                            workflow: attributes
+                                .get("workflow")
+                                .map(|path| Self::resolve_menu_path(project_path, path)),
+                            exec: attributes
+                                .get("exec")
+                                .map(|path| Self::resolve_menu_path(project_path, path)),
+                            action: attributes.get("action").cloned(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Agent {
+            id: id.to_string(),
+            module: module.to_string(),
+            name: attributes
+                .get("name")
+                .cloned()
+                .or(front_matter.name)
+                .unwrap_or_else(|| id.to_string()),
+            title: attributes.get("title").cloned().unwrap_or_default(),
+            icon: attributes.get("icon").cloned().filter(|icon| !icon.is_empty()),
+            description: front_matter.description,
+            persona,
+            menu,
+            file_path: String::new(),
+            customized: false,
+        })
+    }
+
+    /// Apply `_bmad/_config/agents/<module>-<id>.customize.yaml`, if any:
+    /// a new name, a replacement persona and extra menu items
+    fn customize_agent(project_path: &Path, agent: &mut Agent) {
+        let file = project_path
+            .join("_bmad")
+            .join(CONFIG_DIR)
+            .join("agents")
+            .join(format!("{}-{}.customize.yaml", agent.module, agent.id));
+        let custom = match fs::read_to_string(&file) {
+            Ok(content) => match serde_yaml::from_str::<AgentCustomization>(&content) {
+                Ok(custom) => custom,
+                Err(e) => {
+                    eprintln!("Ignoring invalid {}: {}", file.display(), e);
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+
+        if !custom.agent.metadata.name.trim().is_empty() {
+            agent.name = custom.agent.metadata.name.trim().to_string();
+            agent.customized = true;
+        }
+
+        let persona = custom.persona;
+        if !persona.role.is_empty()
+            || !persona.identity.is_empty()
+            || !persona.communication_style.is_empty()
+            || !persona.principles.is_empty()
+        {
+            agent.persona = AgentPersona {
+                role: persona.role,
+                identity: persona.identity,
+                communication_style: persona.communication_style,
+                principles: persona.principles,
+            };
+            agent.customized = true;
+        }
+
+        for item in custom.menu.into_iter().filter(|item| !item.trigger.is_empty()) {
+            agent.menu.push(AgentMenuItem {
+                trigger: item.trigger.trim_start_matches('*').to_string(),
+                fuzzy_match: None,
+                description: item.description,
+                workflow: item.workflow.map(|path| Self::resolve_menu_path(project_path, &path)),
+                exec: item.exec.map(|path| Self::resolve_menu_path(project_path, &path)),
+                action: item.action,
+            });
+            agent.customized = true;
+        }
+    }
+
+    /// Resolve `{project-root}` in a menu item path; placeholders such as
+    /// `todo` are kept as written
+    fn resolve_menu_path(project_path: &Path, value: &str) -> String {
+        if value.contains("{project-root}") {
+            Self::resolve_project_path(project_path, value)
+                .to_string_lossy()
+                .to_string()
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+/// `name="value"` pairs of an XML start tag, with entities decoded
+fn xml_attributes(tag: &str) -> HashMap<String, String> {
+    let attribute_regex = Regex::new(r#"([\w-]+)="([^"]*)""#).unwrap();
+    attribute_regex
+        .captures_iter(tag)
+        .map(|caps| (caps[1].to_string(), decode_entities(&caps[2])))
+        .collect()
+}
+
+/// Trimmed text of the first `<name>` element
+fn xml_element(body: &str, name: &str) -> Option<String> {
+    let regex = Regex::new(&format!(r"(?s)<{0}>(.*?)</{0}>", regex::escape(name))).ok()?;
+    regex
+        .captures(body)
+        .map(|caps| decode_entities(caps[1].trim()))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Principles are written as a `- ` list, on one line or several
+fn split_principles(text: &str) -> Vec<String> {
+    if !text.trim_start().starts_with("- ") {
+        return Some(text.trim().to_string()).filter(|p| !p.is_empty()).into_iter().collect();
+    }
+    let item_regex = Regex::new(r"(?:^|\s)-\s+").unwrap();
+    item_regex
+        .split(text)
+        .map(|item| item.trim().trim_matches('"').trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// `DS or fuzzy match on dev-story` → (`DS`, `dev-story`); older agents
+/// use a bare `*command`
+fn split_command(cmd: &str) -> (String, Option<String>) {
+    match cmd.split_once(" or fuzzy match on ") {
+        Some((trigger, fuzzy)) => (trigger.trim().to_string(), Some(fuzzy.trim().to_string())),
+        None => (cmd.trim().trim_start_matches('*').to_string(), None),
+    }
+}
+
+/// Menu text without its `[DS]` shortcut prefix
+fn menu_label(text: &str) -> String {
+    let text = text.trim();
+    match text.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        Some((_, label)) => label.trim().to_string(),
+        None => text.to_string(),
+    }
+}
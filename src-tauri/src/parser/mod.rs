@@ -1,4 +1,5 @@
 use crate::models::*;
+use crate::settings;
 use chrono::{DateTime, Utc};
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
@@ -7,39 +8,80 @@ use std::path::{Path, PathBuf};
 use thiserror::Error;
 use walkdir::WalkDir;
 
+/// Status value marking a story as blocked, in sprint-status.yaml or a
+/// story's `Status:` line. The story's status reads as backlog.
+const BLOCKED_STATUS: &str = "blocked";
+
+mod agents;
+mod overrides;
+mod teams;
+mod workflows;
+
+pub use overrides::{ProjectOverrides, OVERRIDES_FILE};
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("YAML parse error: {0}")]
     Yaml(#[from] serde_yaml::Error),
+    #[error("CSV parse error: {0}")]
+    Csv(#[from] csv::Error),
     #[error("Invalid BMAD structure: {0}")]
     InvalidStructure(String),
+    #[error("Invalid .bmad-dash.yaml: {0}")]
+    InvalidOverrides(String),
+}
+
+/// A checkbox line in a story's Tasks / Subtasks section
+#[derive(Debug, Clone)]
+pub struct TaskLine {
+    pub id: String,
+    pub title: String,
+    pub completed: bool,
+    /// Nesting level (0 for tasks, 1 for subtasks, ...)
+    pub depth: usize,
+    /// Byte offset of the checkbox mark (the character between `[` and `]`)
+    pub mark_offset: usize,
 }
 
 pub struct BmadParser;
 
+/// YAML between the `---` lines opening a file, if any
+fn front_matter(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix("---")?;
+    let end = rest.find("\n---")?;
+    Some(&rest[..end])
+}
+
+/// Compare paths as given, then resolved (watchers may report either form)
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
+
 impl BmadParser {
     /// Detect if a directory contains a BMAD project
     pub fn is_bmad_project(path: &Path) -> bool {
-        let bmad_output = path.join("_bmad-output");
-        if bmad_output.exists() && bmad_output.is_dir() {
+        // Check for the configured BMAD docs folders or a layout file
+        if Self::find_named_docs_dir(path).is_some() || path.join(OVERRIDES_FILE).is_file() {
             return true;
         }
 
-        if Self::looks_like_bmad_docs(path) {
+        // A freshly installed BMAD method, before any workflow produced output
+        if path.join("_bmad/bmm/config.yaml").is_file() {
             return true;
         }
 
-        // Check for standard BMAD directories
-        let bmad_docs = path.join("bmad-docs");
-        let dot_bmad = path.join(".bmad");
-        let docs = path.join("docs");
-
-        if bmad_docs.exists() || dot_bmad.exists() {
+        if Self::looks_like_bmad_docs(path) {
             return true;
         }
 
+        let docs = path.join("docs");
+
         // Check for docs/ with BMAD artifacts
         if docs.exists() {
             // Check for sprint-status.yaml anywhere in docs/
@@ -64,19 +106,16 @@ impl BmadParser {
 
     /// Find the BMAD docs directory in a project
     pub fn find_bmad_docs_dir(project_path: &Path) -> Option<PathBuf> {
-        let bmad_output = project_path.join("_bmad-output");
-        if bmad_output.exists() {
-            return Some(bmad_output);
-        }
-
-        let bmad_docs = project_path.join("bmad-docs");
-        if bmad_docs.exists() {
-            return Some(bmad_docs);
+        let declared = ProjectOverrides::load(project_path)
+            .ok()
+            .and_then(|overrides| overrides.docs_dir)
+            .filter(|dir| dir.is_dir());
+        if let Some(dir) = declared {
+            return Some(dir);
         }
 
-        let dot_bmad = project_path.join(".bmad");
-        if dot_bmad.exists() {
-            return Some(dot_bmad);
+        if let Some(dir) = Self::find_named_docs_dir(project_path) {
+            return Some(dir);
         }
 
         if Self::looks_like_bmad_docs(project_path) {
@@ -92,6 +131,34 @@ impl BmadParser {
         None
     }
 
+    /// Docs folder `parse_project` uses when none is given: the detected
+    /// one, else the one a fresh install will write to
+    pub fn project_docs_dir(project_path: &Path) -> Option<PathBuf> {
+        Self::find_bmad_docs_dir(project_path).or_else(|| Self::planned_docs_dir(project_path))
+    }
+
+    /// Docs folder a freshly installed BMAD method will write to before any
+    /// workflow has created it: the BMM config's `output_folder`, else
+    /// `_bmad-output`. None without `_bmad/bmm/config.yaml`.
+    pub fn planned_docs_dir(project_path: &Path) -> Option<PathBuf> {
+        if !project_path.join("_bmad/bmm/config.yaml").is_file() {
+            return None;
+        }
+        Some(
+            Self::bmm_config_path(project_path, "output_folder")
+                .unwrap_or_else(|| project_path.join("_bmad-output")),
+        )
+    }
+
+    /// First folder named in the `docsFolderNames` setting that exists in `path`
+    pub fn find_named_docs_dir(path: &Path) -> Option<PathBuf> {
+        settings::current()
+            .docs_folder_names
+            .iter()
+            .map(|name| path.join(name))
+            .find(|dir| dir.is_dir())
+    }
+
     fn looks_like_bmad_docs(path: &Path) -> bool {
         if !path.exists() || !path.is_dir() {
             return false;
@@ -127,8 +194,12 @@ impl BmadParser {
         false
     }
 
-    /// Parse a BMAD project from a directory
+    /// Parse a BMAD project from a directory.
+    ///
+    /// A `.bmad-dash.yaml` at the project root takes precedence over the
+    /// built-in layout heuristics (see `ProjectOverrides`).
     pub fn parse_project(project_path: &Path, custom_bmad_dir: Option<&Path>) -> Result<BmadProject, ParseError> {
+        let overrides = ProjectOverrides::load(project_path)?;
         let bmad_dir = if let Some(custom_dir) = custom_bmad_dir {
             if !custom_dir.exists() {
                 return Err(ParseError::InvalidStructure(format!(
@@ -147,17 +218,19 @@ impl BmadParser {
                         ))
                     })?
             } else {
-                let bmad_output = project_path.join("_bmad-output");
-                if custom_dir == project_path && bmad_output.exists() {
-                    bmad_output
-                } else if !Self::looks_like_bmad_docs(custom_dir) && bmad_output.exists() {
-                    bmad_output
-                } else {
-                    custom_dir.to_path_buf()
+                match Self::find_named_docs_dir(project_path) {
+                    Some(named_dir)
+                        if custom_dir == project_path || !Self::looks_like_bmad_docs(custom_dir) =>
+                    {
+                        named_dir
+                    }
+                    _ => custom_dir.to_path_buf(),
                 }
             }
+        } else if let Some(docs_dir) = overrides.docs_dir.clone().filter(|dir| dir.is_dir()) {
+            docs_dir
         } else {
-            Self::find_bmad_docs_dir(project_path)
+            Self::project_docs_dir(project_path)
                 .ok_or_else(|| ParseError::InvalidStructure("No bmad-docs directory found".into()))?
         };
 
@@ -171,22 +244,26 @@ impl BmadParser {
         let id = uuid::Uuid::new_v4().to_string();
 
         // Parse sprint status if exists (check multiple locations)
-        let (sprint_status, sprint_status_time) = Self::parse_sprint_status(&bmad_dir, &now)?;
+        let (sprint_status, sprint_status_time) =
+            Self::parse_sprint_status(&bmad_dir, &overrides, &now)?;
 
         // Parse documents
-        let documents = Self::parse_documents(&bmad_dir, &now)?;
+        let documents = Self::parse_documents(&bmad_dir, &overrides, &now)?;
 
         // Parse epics
-        let mut epics = Self::parse_epics(&bmad_dir, &sprint_status, &now)?;
+        let mut epics = Self::parse_epics(&bmad_dir, &overrides, &sprint_status, &now)?;
 
         // Parse user story files and attach them to epics
-        let stories_by_epic = Self::parse_story_files(&bmad_dir, &sprint_status, &now)?;
+        let stories_by_epic = Self::parse_story_files(&bmad_dir, &overrides, &sprint_status, &now)?;
         Self::attach_story_files(&mut epics, stories_by_epic, &sprint_status, &now);
 
         // Determine current phase based on sprint status and documents
         let current_phase = Self::determine_phase(&documents, &epics, &sprint_status);
-        let last_activity =
-            Self::latest_activity(&documents, &epics, sprint_status_time.as_deref(), &now);
+        let last_activity = Self::latest_activity(
+            &documents,
+            &epics,
+            Self::sprint_status_file(&bmad_dir, &overrides).as_deref(),
+        );
         let created_at =
             Self::earliest_activity(&documents, &epics, sprint_status_time.as_deref(), &now);
 
@@ -205,21 +282,146 @@ impl BmadParser {
         })
     }
 
-    /// Parse sprint-status.yaml - handles multiple formats
-    fn parse_sprint_status(
-        bmad_dir: &Path,
-        now: &str,
-    ) -> Result<(Option<SprintStatus>, Option<String>), ParseError> {
-        // Try multiple locations for sprint-status.yaml
+    /// Locate sprint-status.yaml (checks multiple locations), or the file
+    /// `.bmad-dash.yaml` declares
+    pub fn find_sprint_status_file(bmad_dir: &Path) -> Option<PathBuf> {
+        let overrides = ProjectOverrides::for_docs_dir(bmad_dir).unwrap_or_default();
+        Self::sprint_status_file(bmad_dir, &overrides)
+    }
+
+    fn sprint_status_file(bmad_dir: &Path, overrides: &ProjectOverrides) -> Option<PathBuf> {
+        if let Some(path) = overrides.sprint_status.as_ref().filter(|p| p.is_file()) {
+            return Some(path.clone());
+        }
+
         let possible_paths = vec![
             bmad_dir.join("sprint-status.yaml"),
             bmad_dir.join("implementation-artifacts/sprint-status.yaml"),
             bmad_dir.join("stories/sprint-status.yaml"),
         ];
 
-        let status_path = possible_paths.into_iter().find(|p| p.exists());
+        possible_paths.into_iter().find(|p| p.exists())
+    }
+
+    /// Resolve a BMAD config path value (`{project-root}/docs/...` or relative) against the project
+    pub fn resolve_project_path(project_path: &Path, value: &str) -> PathBuf {
+        let resolved = value
+            .trim()
+            .replace("{project-root}", &project_path.to_string_lossy());
+        let path = PathBuf::from(resolved);
+        if path.is_absolute() {
+            path
+        } else {
+            project_path.join(path)
+        }
+    }
+
+    /// Read a string key (e.g. `project_name`) from `_bmad/bmm/config.yaml`
+    pub fn bmm_config_value(project_path: &Path, key: &str) -> Option<String> {
+        let content = fs::read_to_string(project_path.join("_bmad/bmm/config.yaml")).ok()?;
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&content).ok()?;
+        yaml.get(key)?.as_str().map(|s| s.to_string())
+    }
+
+    /// Read a path-valued key (e.g. `implementation_artifacts`) from `_bmad/bmm/config.yaml`
+    pub fn bmm_config_path(project_path: &Path, key: &str) -> Option<PathBuf> {
+        let value = Self::bmm_config_value(project_path, key)?;
+        Some(Self::resolve_project_path(project_path, &value))
+    }
+
+    /// Project root a docs folder belongs to: the closest folder above it
+    /// with `.bmad-dash.yaml`, `_bmad` or `.git`
+    pub fn project_root(bmad_dir: &Path) -> Option<PathBuf> {
+        bmad_dir
+            .ancestors()
+            .find(|dir| {
+                dir.join(OVERRIDES_FILE).is_file() || dir.join("_bmad").is_dir() || dir.join(".git").exists()
+            })
+            .map(Path::to_path_buf)
+    }
+
+    /// Folder where story files are written (create-story's `story_dir`).
+    ///
+    /// Prefers the `stories` folder from `.bmad-dash.yaml`, then
+    /// sprint-status.yaml's `story_location`, then the BMM config's
+    /// `implementation_artifacts`, then the folder holding sprint-status.yaml.
+    pub fn find_story_dir(project_path: &Path, bmad_dir: &Path) -> PathBuf {
+        let overrides = ProjectOverrides::load(project_path).unwrap_or_default();
+        if let Some(dir) = overrides.stories_dir.as_ref().filter(|d| d.is_dir()) {
+            return dir.clone();
+        }
+
+        let sprint_file = Self::sprint_status_file(bmad_dir, &overrides);
+
+        let story_location = sprint_file
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+            .and_then(|yaml| {
+                yaml.get("story_location")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            })
+            .filter(|s| !s.trim().is_empty() && !s.contains("{story_location}"))
+            .map(|s| Self::resolve_project_path(project_path, &s));
+
+        if let Some(dir) = story_location.filter(|d| d.is_dir()) {
+            return dir;
+        }
+
+        if let Some(dir) = Self::bmm_config_path(project_path, "implementation_artifacts")
+            .filter(|d| d.is_dir())
+        {
+            return dir;
+        }
+
+        if let Some(dir) = sprint_file.as_ref().and_then(|p| p.parent()) {
+            return dir.to_path_buf();
+        }
+
+        let implementation_dir = bmad_dir.join("implementation-artifacts");
+        if implementation_dir.is_dir() {
+            implementation_dir
+        } else {
+            bmad_dir.to_path_buf()
+        }
+    }
+
+    /// Locate the epics.md files the parser reads stories from
+    pub fn find_epics_files(bmad_dir: &Path) -> Vec<PathBuf> {
+        vec![
+            bmad_dir.join("epics.md"),
+            bmad_dir.join("planning-artifacts/epics.md"),
+            bmad_dir.join("epics/epics.md"),
+        ]
+        .into_iter()
+        .filter(|p| p.exists())
+        .collect()
+    }
+
+    /// Locate bmm-workflow-status.yaml, which workflow-init may keep outside the docs folder
+    pub fn find_workflow_status_file(project_path: &Path, bmad_dir: &Path) -> Option<PathBuf> {
+        let mut possible_paths = vec![
+            bmad_dir.join("bmm-workflow-status.yaml"),
+            bmad_dir.join("planning-artifacts/bmm-workflow-status.yaml"),
+        ];
+        for key in ["planning_artifacts", "output_folder"] {
+            if let Some(dir) = Self::bmm_config_path(project_path, key) {
+                possible_paths.push(dir.join("bmm-workflow-status.yaml"));
+            }
+        }
+        possible_paths.push(project_path.join("docs/bmm-workflow-status.yaml"));
+
+        possible_paths.into_iter().find(|p| p.exists())
+    }
 
-        let status_path = match status_path {
+    /// Parse sprint-status.yaml - handles multiple formats
+    fn parse_sprint_status(
+        bmad_dir: &Path,
+        overrides: &ProjectOverrides,
+        now: &str,
+    ) -> Result<(Option<SprintStatus>, Option<String>), ParseError> {
+        let status_path = match Self::sprint_status_file(bmad_dir, overrides) {
             Some(p) => p,
             None => return Ok((None, None)),
         };
@@ -261,6 +463,7 @@ impl BmadParser {
             let mut epic_stories: HashMap<String, HashMap<String, StoryStatus>> = HashMap::new();
             let mut epic_statuses: HashMap<String, EpicStatus> = HashMap::new();
             let mut epic_retrospectives: HashMap<String, RetrospectiveStatus> = HashMap::new();
+            let mut blocked_keys: HashSet<String> = HashSet::new();
 
             for (key, val) in dev_status {
                 if let Some(key_str) = key.as_str() {
@@ -269,7 +472,7 @@ impl BmadParser {
                             // This is an epic status entry like "epic-1: in-progress"
                             epic_statuses.insert(
                                 key_str.to_string(),
-                                Self::parse_epic_status(status_str),
+                                overrides.epic_status(status_str),
                             );
                         } else if key_str.ends_with("-retrospective") {
                             // This is a retrospective entry like "epic-1-retrospective: optional"
@@ -287,7 +490,10 @@ impl BmadParser {
                             if let Some(epic_num_str) = parts.first() {
                                 if let Ok(epic_num) = epic_num_str.parse::<u32>() {
                                     let epic_key = format!("epic-{}", epic_num);
-                                    let story_status = Self::parse_story_status(status_str);
+                                    let story_status = overrides.story_status(status_str);
+                                    if Self::is_blocked(status_str) {
+                                        blocked_keys.insert(key_str.to_string());
+                                    }
 
                                     epic_stories
                                         .entry(epic_key)
@@ -301,7 +507,7 @@ impl BmadParser {
                         let status = val
                             .get("status")
                             .and_then(|v| v.as_str())
-                            .map(|s| Self::parse_epic_status(s))
+                            .map(|s| overrides.epic_status(s))
                             .unwrap_or(EpicStatus::Backlog);
 
                         epic_statuses.insert(key_str.to_string(), status);
@@ -311,7 +517,10 @@ impl BmadParser {
                             for (story_key, story_val) in mapping {
                                 if let (Some(sk), Some(sv)) = (story_key.as_str(), story_val.as_str()) {
                                     if sk != "status" && sk != "retrospective" {
-                                        stories.insert(sk.to_string(), Self::parse_story_status(sv));
+                                        stories.insert(sk.to_string(), overrides.story_status(sv));
+                                        if Self::is_blocked(sv) {
+                                            blocked_keys.insert(sk.to_string());
+                                        }
                                     }
                                 }
                             }
@@ -331,6 +540,16 @@ impl BmadParser {
                 }
             }
 
+            let blocked_in = |stories: &HashMap<String, StoryStatus>| {
+                let mut blocked: Vec<String> = stories
+                    .keys()
+                    .filter(|key| blocked_keys.contains(*key))
+                    .cloned()
+                    .collect();
+                blocked.sort();
+                blocked
+            };
+
             // Build final development_status structure
             for (epic_key, status) in &epic_statuses {
                 let stories = epic_stories.remove(epic_key).unwrap_or_default();
@@ -340,6 +559,7 @@ impl BmadParser {
                     epic_key.clone(),
                     EpicSprintStatus {
                         status: status.clone(),
+                        blocked: blocked_in(&stories),
                         stories,
                         retrospective,
                     },
@@ -353,6 +573,7 @@ impl BmadParser {
                         epic_key.clone(),
                         EpicSprintStatus {
                             status: EpicStatus::Backlog,
+                            blocked: blocked_in(&stories),
                             stories,
                             retrospective: None,
                         },
@@ -374,7 +595,7 @@ impl BmadParser {
         ))
     }
 
-    fn parse_epic_status(s: &str) -> EpicStatus {
+    pub(crate) fn parse_epic_status(s: &str) -> EpicStatus {
         match s {
             "in-progress" => EpicStatus::InProgress,
             "done" => EpicStatus::Done,
@@ -382,7 +603,7 @@ impl BmadParser {
         }
     }
 
-    fn parse_story_status(s: &str) -> StoryStatus {
+    pub(crate) fn parse_story_status(s: &str) -> StoryStatus {
         match s {
             "ready-for-dev" => StoryStatus::ReadyForDev,
             "in-progress" => StoryStatus::InProgress,
@@ -393,7 +614,11 @@ impl BmadParser {
     }
 
     /// Parse all documents from bmad-docs
-    fn parse_documents(bmad_dir: &Path, now: &str) -> Result<Vec<BmadDocument>, ParseError> {
+    fn parse_documents(
+        bmad_dir: &Path,
+        overrides: &ProjectOverrides,
+        now: &str,
+    ) -> Result<Vec<BmadDocument>, ParseError> {
         let mut documents = Vec::new();
 
         // Look for common BMAD documents in multiple locations
@@ -409,8 +634,8 @@ impl BmadParser {
         // Check root level
         for (filename, doc_type) in &doc_patterns {
             let file_path = bmad_dir.join(filename);
-            if file_path.exists() {
-                if let Ok(doc) = Self::create_document(&file_path, doc_type, now) {
+            if file_path.exists() && !overrides.is_ignored(&file_path) {
+                if let Ok(doc) = Self::create_document(&file_path, overrides.document_type(&file_path).unwrap_or(doc_type), now) {
                     documents.push(doc);
                 }
             }
@@ -421,8 +646,8 @@ impl BmadParser {
         if planning_dir.exists() {
             for (filename, doc_type) in &doc_patterns {
                 let file_path = planning_dir.join(filename);
-                if file_path.exists() {
-                    if let Ok(doc) = Self::create_document(&file_path, doc_type, now) {
+                if file_path.exists() && !overrides.is_ignored(&file_path) {
+                    if let Ok(doc) = Self::create_document(&file_path, overrides.document_type(&file_path).unwrap_or(doc_type), now) {
                         documents.push(doc);
                     }
                 }
@@ -434,8 +659,8 @@ impl BmadParser {
         if solutioning_dir.exists() {
             for (filename, doc_type) in &doc_patterns {
                 let file_path = solutioning_dir.join(filename);
-                if file_path.exists() {
-                    if let Ok(doc) = Self::create_document(&file_path, doc_type, now) {
+                if file_path.exists() && !overrides.is_ignored(&file_path) {
+                    if let Ok(doc) = Self::create_document(&file_path, overrides.document_type(&file_path).unwrap_or(doc_type), now) {
                         documents.push(doc);
                     }
                 }
@@ -446,25 +671,32 @@ impl BmadParser {
         for entry in WalkDir::new(bmad_dir).max_depth(3) {
             if let Ok(entry) = entry {
                 let path = entry.path();
-                if path.extension().map(|e| e == "md").unwrap_or(false) {
+                if path.extension().map(|e| e == "md").unwrap_or(false) && !overrides.is_ignored(path) {
                     let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
-                    // Skip already processed and epic files
-                    if doc_patterns.iter().any(|(f, _)| *f == filename) {
+                    // Skip duplicates by path
+                    let path_str = path.to_string_lossy().to_string();
+                    if documents.iter().any(|d| d.file_path == path_str) {
                         continue;
                     }
-                    if filename == "epics.md" || filename.starts_with("epic-") {
+
+                    // Types declared in .bmad-dash.yaml win over the skips below
+                    if let Some(doc_type) = overrides.document_type(path) {
+                        if let Ok(doc) = Self::create_document(path, doc_type, now) {
+                            documents.push(doc);
+                        }
                         continue;
                     }
-                    // Skip story files (pattern: N-N-*.md)
-                    let story_pattern = Regex::new(r"^\d+-\d+-.+\.md$").unwrap();
-                    if story_pattern.is_match(filename) {
+
+                    // Skip already processed and epic files
+                    if doc_patterns.iter().any(|(f, _)| *f == filename) {
                         continue;
                     }
-
-                    // Skip duplicates by path
-                    let path_str = path.to_string_lossy().to_string();
-                    if documents.iter().any(|d| d.file_path == path_str) {
+                    if filename == "epics.md" || filename.starts_with("epic-") || overrides.is_epic_file(filename) {
+                        continue;
+                    }
+                    // Skip story files (pattern: N-N-*.md unless overridden)
+                    if overrides.story_numbers(filename).is_some() {
                         continue;
                     }
 
@@ -537,40 +769,29 @@ impl BmadParser {
         }
     }
 
+    /// Newest modification time of the project's files: documents, epic
+    /// and story files and sprint-status.yaml. None when no file time can
+    /// be read; items without a file carry the parse time and do not count.
     fn latest_activity(
         documents: &[BmadDocument],
         epics: &[Epic],
-        sprint_status_time: Option<&str>,
-        fallback: &str,
-    ) -> String {
-        let mut latest = Self::parse_rfc3339(fallback);
-
-        for doc in documents {
-            if let Some(dt) = Self::parse_rfc3339(&doc.updated_at) {
-                Self::merge_latest(&mut latest, dt);
-            }
-        }
-
-        for epic in epics {
-            if let Some(dt) = Self::parse_rfc3339(&epic.updated_at) {
-                Self::merge_latest(&mut latest, dt);
-            }
-            for story in &epic.stories {
-                if let Some(dt) = Self::parse_rfc3339(&story.updated_at) {
-                    Self::merge_latest(&mut latest, dt);
-                }
-            }
-        }
-
-        if let Some(time) = sprint_status_time {
-            if let Some(dt) = Self::parse_rfc3339(time) {
-                Self::merge_latest(&mut latest, dt);
-            }
-        }
-
-        latest
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_else(|| fallback.to_string())
+        sprint_status_file: Option<&Path>,
+    ) -> Option<String> {
+        let files = documents
+            .iter()
+            .map(|doc| Some(doc.file_path.as_str()))
+            .chain(epics.iter().flat_map(|epic| {
+                std::iter::once(epic.file_path.as_deref())
+                    .chain(epic.stories.iter().map(|story| story.file_path.as_deref()))
+            }))
+            .flatten()
+            .map(Path::new)
+            .chain(sprint_status_file);
+
+        files
+            .filter_map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+            .max()
+            .map(|time| DateTime::<Utc>::from(time).to_rfc3339())
     }
 
     fn earliest_activity(
@@ -741,41 +962,40 @@ impl BmadParser {
     /// Parse epics from bmad-docs
     fn parse_epics(
         bmad_dir: &Path,
+        overrides: &ProjectOverrides,
         sprint_status: &Option<SprintStatus>,
         now: &str,
     ) -> Result<Vec<Epic>, ParseError> {
         let mut epics = Vec::new();
 
         // Prefer epic-*.md files over epics.md
-        let epics_dir = bmad_dir.join("epics");
+        let epics_dir = overrides
+            .epics_dir
+            .clone()
+            .unwrap_or_else(|| bmad_dir.join("epics"));
         if epics_dir.exists() {
-            epics.extend(Self::parse_epics_from_dir(&epics_dir, sprint_status, now)?);
+            epics.extend(Self::parse_epics_from_dir(&epics_dir, overrides, sprint_status, now)?);
         }
 
-        epics.extend(Self::parse_epic_files_from_dir(bmad_dir, sprint_status, now)?);
+        // A fresh install's docs folder may not exist yet
+        if bmad_dir.is_dir() {
+            epics.extend(Self::parse_epic_files_from_dir(bmad_dir, overrides, sprint_status, now)?);
+        }
 
         let epics = Self::merge_epics_by_number(epics);
         let mut by_number: HashMap<u32, Epic> =
             epics.into_iter().map(|epic| (epic.number, epic)).collect();
 
         // Supplement stories from epics.md if needed
-        let epics_file_paths = vec![
-            bmad_dir.join("epics.md"),
-            bmad_dir.join("planning-artifacts/epics.md"),
-            bmad_dir.join("epics/epics.md"),
-        ];
-
         let mut epics_from_docs = Vec::new();
-        for epics_file in epics_file_paths {
-            if epics_file.exists() {
-                let content = fs::read_to_string(&epics_file)?;
-                epics_from_docs.extend(Self::parse_epics_from_single_file(
-                    &content,
-                    &epics_file,
-                    sprint_status,
-                    now,
-                )?);
-            }
+        for epics_file in Self::find_epics_files(bmad_dir) {
+            let content = fs::read_to_string(&epics_file)?;
+            epics_from_docs.extend(Self::parse_epics_from_single_file(
+                &content,
+                &epics_file,
+                sprint_status,
+                now,
+            )?);
         }
 
         for epic in Self::merge_epics_by_number(epics_from_docs) {
@@ -789,6 +1009,13 @@ impl BmadParser {
         Ok(merged)
     }
 
+    /// Epics and stories as planned in the epic documents, without sprint-status applied
+    pub fn parse_planned_epics(bmad_dir: &Path) -> Result<Vec<Epic>, ParseError> {
+        let now = Utc::now().to_rfc3339();
+        let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+        Self::parse_epics(bmad_dir, &overrides, &None, &now)
+    }
+
     /// Parse all epics from a single epics.md file
     fn parse_epics_from_single_file(
         content: &str,
@@ -865,6 +1092,7 @@ impl BmadParser {
 
     fn parse_epics_from_dir(
         epics_dir: &Path,
+        overrides: &ProjectOverrides,
         sprint_status: &Option<SprintStatus>,
         now: &str,
     ) -> Result<Vec<Epic>, ParseError> {
@@ -874,9 +1102,11 @@ impl BmadParser {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().map(|e| e == "md").unwrap_or(false) {
+            if path.extension().map(|e| e == "md").unwrap_or(false) && !overrides.is_ignored(&path) {
                 let content = fs::read_to_string(&path)?;
-                if let Some(epic) = Self::parse_epic_file(path.as_path(), &content, sprint_status, now)? {
+                if let Some(epic) =
+                    Self::parse_epic_file(path.as_path(), &content, overrides, sprint_status, now)?
+                {
                     epics.push(epic);
                 }
             }
@@ -888,6 +1118,7 @@ impl BmadParser {
 
     fn parse_epic_files_from_dir(
         bmad_dir: &Path,
+        overrides: &ProjectOverrides,
         sprint_status: &Option<SprintStatus>,
         now: &str,
     ) -> Result<Vec<Epic>, ParseError> {
@@ -898,9 +1129,11 @@ impl BmadParser {
             let path = entry.path();
 
             if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.starts_with("epic") && path.extension().map(|e| e == "md").unwrap_or(false) {
+                if path.is_file() && overrides.is_epic_file(filename) && !overrides.is_ignored(&path) {
                     let content = fs::read_to_string(&path)?;
-                    if let Some(epic) = Self::parse_epic_file(&path, &content, sprint_status, now)? {
+                    if let Some(epic) =
+                        Self::parse_epic_file(&path, &content, overrides, sprint_status, now)?
+                    {
                         epics.push(epic);
                     }
                 }
@@ -913,18 +1146,14 @@ impl BmadParser {
     fn parse_epic_file(
         path: &Path,
         content: &str,
+        overrides: &ProjectOverrides,
         sprint_status: &Option<SprintStatus>,
         now: &str,
     ) -> Result<Option<Epic>, ParseError> {
         let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
         // Extract epic number from filename (e.g., "epic-1.md" or "epic-1-title.md")
-        let epic_num_regex = Regex::new(r"epic-?(\d+)").unwrap();
-        let epic_number = epic_num_regex
-            .captures(filename)
-            .and_then(|c| c.get(1))
-            .and_then(|m| m.as_str().parse::<u32>().ok())
-            .unwrap_or(0);
+        let epic_number = overrides.epic_number(filename).unwrap_or(0);
 
         if epic_number == 0 {
             return Ok(None);
@@ -963,34 +1192,33 @@ impl BmadParser {
 
     fn parse_story_files(
         bmad_dir: &Path,
+        overrides: &ProjectOverrides,
         sprint_status: &Option<SprintStatus>,
         now: &str,
     ) -> Result<HashMap<u32, Vec<Story>>, ParseError> {
         let mut stories_by_epic: HashMap<u32, Vec<Story>> = HashMap::new();
-        let story_file_regex = Regex::new(r"^(\d+)-(\d+)-.+\.md$").unwrap();
 
-        for entry in WalkDir::new(bmad_dir).max_depth(4) {
+        // A stories folder from .bmad-dash.yaml may live outside the docs folder
+        let mut roots = vec![bmad_dir.to_path_buf()];
+        if let Some(stories_dir) = &overrides.stories_dir {
+            if stories_dir.is_dir() && !stories_dir.starts_with(bmad_dir) {
+                roots.push(stories_dir.clone());
+            }
+        }
+
+        for entry in roots.iter().flat_map(|root| WalkDir::new(root).max_depth(4)) {
             if let Ok(entry) = entry {
                 let path = entry.path();
-                if !path.is_file() {
+                if !path.is_file() || overrides.is_ignored(path) {
                     continue;
                 }
 
                 let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                let caps = match story_file_regex.captures(filename) {
-                    Some(caps) => caps,
+                let (epic_number, story_number) = match overrides.story_numbers(filename) {
+                    Some(numbers) => numbers,
                     None => continue,
                 };
 
-                let epic_number = caps
-                    .get(1)
-                    .and_then(|m| m.as_str().parse::<u32>().ok())
-                    .unwrap_or(0);
-                let story_number = caps
-                    .get(2)
-                    .and_then(|m| m.as_str().parse::<u32>().ok())
-                    .unwrap_or(0);
-
                 if epic_number == 0 || story_number == 0 {
                     continue;
                 }
@@ -1035,6 +1263,9 @@ impl BmadParser {
         let (user_type, capability, value_benefit) = Self::extract_user_story_details(content);
         let status =
             Self::story_status_from_sprint(sprint_status, epic_number, story_number, Some(story_key));
+        let blocked = Self::story_blocked_in_sprint(sprint_status, epic_number, story_number, Some(story_key))
+            || Self::has_blocked_status_line(content);
+        let tasks = Self::extract_tasks(content);
 
         Some(Story {
             id: uuid::Uuid::new_v4().to_string(),
@@ -1046,13 +1277,214 @@ impl BmadParser {
             value_benefit,
             acceptance_criteria: Vec::new(),
             status,
-            tasks: Vec::new(),
+            blocked,
+            tasks,
             file_path: Some(path.to_string_lossy().to_string()),
             created_at: file_time.clone(),
             updated_at: file_time,
         })
     }
 
+    /// Parse a single story file (e.g. after editing it) with its sprint status applied
+    pub fn parse_story_at(bmad_dir: &Path, path: &Path) -> Result<Story, ParseError> {
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+        let (epic_number, story_number) = overrides.story_numbers(filename).ok_or_else(|| {
+            ParseError::InvalidStructure(format!("Not a story file: {}", path.display()))
+        })?;
+
+        let now = Utc::now().to_rfc3339();
+        let (sprint_status, _) = Self::parse_sprint_status(bmad_dir, &overrides, &now)?;
+        let content = fs::read_to_string(path)?;
+
+        Self::parse_story_file(path, &content, epic_number, story_number, &sprint_status, &now)
+            .ok_or_else(|| {
+                ParseError::InvalidStructure(format!("Could not parse story: {}", path.display()))
+            })
+    }
+
+    /// Refresh a parsed project after `changed` files were modified.
+    ///
+    /// Story files and documents the project already knows are re-read on
+    /// their own; anything else (sprint-status.yaml, epics, files that were
+    /// added or removed) falls back to a full parse.
+    pub fn reparse_changed(project: &BmadProject, changed: &[PathBuf]) -> Result<BmadProject, ParseError> {
+        let now = Utc::now().to_rfc3339();
+        let mut updated = project.clone();
+
+        for path in changed {
+            if !path.is_file() || !Self::reparse_file(&mut updated, path, &now)? {
+                let mut reparsed = Self::parse_project(
+                    Path::new(&project.path),
+                    Some(Path::new(&project.bmad_docs_path)),
+                )?;
+                reparsed.id = project.id.clone();
+                return Ok(reparsed);
+            }
+        }
+
+        updated.current_phase =
+            Self::determine_phase(&updated.documents, &updated.epics, &updated.sprint_status);
+        let sprint_status_file = Self::find_sprint_status_file(Path::new(&updated.bmad_docs_path));
+        updated.last_activity =
+            Self::latest_activity(&updated.documents, &updated.epics, sprint_status_file.as_deref());
+        Ok(updated)
+    }
+
+    /// Re-read one known story file or document in place; false if `path` is neither
+    fn reparse_file(project: &mut BmadProject, path: &Path, now: &str) -> Result<bool, ParseError> {
+        let sprint_status = project.sprint_status.clone();
+
+        let story = project
+            .epics
+            .iter_mut()
+            .flat_map(|epic| epic.stories.iter_mut())
+            .find(|story| story.file_path.as_deref().map(|p| same_file(Path::new(p), path)).unwrap_or(false));
+        if let Some(story) = story {
+            let numbers = story
+                .number
+                .split_once('.')
+                .and_then(|(e, s)| Some((e.parse::<u32>().ok()?, s.parse::<u32>().ok()?)));
+            let (epic_number, story_number) = match numbers {
+                Some(numbers) => numbers,
+                None => return Ok(false),
+            };
+
+            let content = fs::read_to_string(path)?;
+            let mut reparsed =
+                match Self::parse_story_file(path, &content, epic_number, story_number, &sprint_status, now) {
+                    Some(reparsed) => reparsed,
+                    None => return Ok(false),
+                };
+            reparsed.id = story.id.clone();
+            reparsed.epic_id = story.epic_id.clone();
+            reparsed.file_path = story.file_path.clone();
+            *story = reparsed;
+            return Ok(true);
+        }
+
+        let document = project
+            .documents
+            .iter_mut()
+            .find(|doc| same_file(Path::new(&doc.file_path), path));
+        if let Some(document) = document {
+            let mut reparsed = Self::create_document(path, &document.doc_type, now)?;
+            reparsed.id = document.id.clone();
+            reparsed.file_path = document.file_path.clone();
+            *document = reparsed;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Locate checkbox lines in the story's Tasks / Subtasks section.
+    ///
+    /// Task ids come from the numbering authors already use ("Task 2", "2.1")
+    /// and fall back to the position in the list ("3", "3.2"), so they survive
+    /// edits elsewhere in the file.
+    pub fn task_lines(content: &str) -> Vec<TaskLine> {
+        let section_regex = Regex::new(r"(?m)^##[ \t]+[^\r\n]*Task[^\r\n]*").unwrap();
+        let checkbox_regex = Regex::new(r"^([ \t]*)[-*+][ \t]+\[([ xX])\][ \t]*(.*)$").unwrap();
+        let task_number_regex = Regex::new(r"^(?:Task[ \t]+)?(\d+(?:\.\d+)*)\b").unwrap();
+        let subtask_number_regex = Regex::new(r"^(?:Subtask[ \t]+)?(\d+\.\d+(?:\.\d+)*)\b").unwrap();
+
+        let section_start = match section_regex.find(content) {
+            Some(m) => m.end(),
+            None => return Vec::new(),
+        };
+
+        let mut lines = Vec::new();
+        let mut used_ids: HashSet<String> = HashSet::new();
+        // (indent, id, child count) for each open nesting level
+        let mut stack: Vec<(usize, String, usize)> = Vec::new();
+        let mut top_level_count = 0;
+        let mut offset = section_start;
+
+        for raw_line in content[section_start..].split_inclusive('\n') {
+            let line_start = offset;
+            offset += raw_line.len();
+            let line = raw_line.trim_end_matches(['\n', '\r']);
+
+            if line.starts_with("## ") || line.starts_with("# ") {
+                break;
+            }
+
+            let caps = match checkbox_regex.captures(line) {
+                Some(caps) => caps,
+                None => continue,
+            };
+
+            let indent = caps.get(1).map(|m| m.as_str().replace('\t', "    ").len()).unwrap_or(0);
+            let mark = caps.get(2).unwrap();
+            let title = caps.get(3).map(|m| m.as_str().trim()).unwrap_or("").to_string();
+
+            while stack.last().map(|(i, _, _)| *i >= indent).unwrap_or(false) {
+                stack.pop();
+            }
+
+            let (positional, explicit) = match stack.last_mut() {
+                Some((_, parent_id, children)) => {
+                    *children += 1;
+                    (
+                        format!("{}.{}", parent_id, children),
+                        subtask_number_regex
+                            .captures(&title)
+                            .map(|c| c[1].to_string()),
+                    )
+                }
+                None => {
+                    top_level_count += 1;
+                    (
+                        top_level_count.to_string(),
+                        task_number_regex.captures(&title).map(|c| c[1].to_string()),
+                    )
+                }
+            };
+
+            let id = match explicit {
+                Some(id) if !used_ids.contains(&id) => id,
+                _ => positional,
+            };
+            used_ids.insert(id.clone());
+
+            lines.push(TaskLine {
+                id: id.clone(),
+                title,
+                completed: mark.as_str() != " ",
+                depth: stack.len(),
+                mark_offset: line_start + mark.start(),
+            });
+            stack.push((indent, id, 0));
+        }
+
+        lines
+    }
+
+    fn extract_tasks(content: &str) -> Vec<Task> {
+        fn build(lines: &[TaskLine], index: &mut usize, depth: usize) -> Vec<Task> {
+            let mut tasks = Vec::new();
+            while let Some(line) = lines.get(*index) {
+                if line.depth < depth {
+                    break;
+                }
+                *index += 1;
+                let subtasks = build(lines, index, line.depth + 1);
+                tasks.push(Task {
+                    id: line.id.clone(),
+                    title: line.title.clone(),
+                    completed: line.completed,
+                    subtasks,
+                });
+            }
+            tasks
+        }
+
+        let lines = Self::task_lines(content);
+        let mut index = 0;
+        build(&lines, &mut index, 0)
+    }
+
     fn story_title_from_filename(filename: &str, epic_number: u32, story_number: u32) -> String {
         let prefix = format!("{}-{}-", epic_number, story_number);
         let cleaned = filename
@@ -1121,6 +1553,39 @@ impl BmadParser {
             .unwrap_or(StoryStatus::Backlog)
     }
 
+    /// Whether the sprint-status.yaml entry for a story says `blocked`
+    /// (its `status` reads as backlog), matched like `story_status_from_sprint`
+    fn story_blocked_in_sprint(
+        sprint_status: &Option<SprintStatus>,
+        epic_number: u32,
+        story_number: u32,
+        story_key: Option<&str>,
+    ) -> bool {
+        let epic_key = format!("epic-{}", epic_number);
+        let prefix = format!("{}-{}-", epic_number, story_number);
+        let number_key = format!("{}.{}", epic_number, story_number);
+
+        sprint_status
+            .as_ref()
+            .and_then(|ss| ss.development_status.get(&epic_key))
+            .is_some_and(|es| {
+                es.blocked.iter().any(|key| {
+                    Some(key.as_str()) == story_key || key.starts_with(&prefix) || *key == number_key
+                })
+            })
+    }
+
+    fn is_blocked(value: &str) -> bool {
+        value.trim().eq_ignore_ascii_case(BLOCKED_STATUS)
+    }
+
+    /// `Status: blocked` (optionally in bold) in a story file
+    fn has_blocked_status_line(content: &str) -> bool {
+        let status_line =
+            Regex::new(r"(?im)^\s*(?:\*\*)?status(?:\*\*)?\s*:\s*(?:\*\*)?\s*blocked\b").unwrap();
+        status_line.is_match(content)
+    }
+
     fn parse_stories_from_content(
         content: &str,
         epic_number: u32,
@@ -1161,6 +1626,7 @@ impl BmadParser {
                 value_benefit,
                 acceptance_criteria: Vec::new(),
                 status,
+                blocked: Self::story_blocked_in_sprint(sprint_status, epic_number, story_num, None),
                 tasks: Vec::new(),
                 file_path: None,
                 created_at: source_time.to_string(),
@@ -1227,7 +1693,8 @@ impl BmadParser {
         1
     }
 
-    /// Scan a directory for BMAD projects
+    /// Scan a directory for BMAD projects `parse_project` can open without
+    /// being given a docs folder
     pub fn scan_for_projects(root_path: &Path, max_depth: usize) -> Vec<PathBuf> {
         let mut projects = Vec::new();
 
@@ -1237,7 +1704,7 @@ impl BmadParser {
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
-            if path.is_dir() && Self::is_bmad_project(path) {
+            if path.is_dir() && Self::is_bmad_project(path) && Self::project_docs_dir(path).is_some() {
                 projects.push(path.to_path_buf());
             }
         }
@@ -1245,3 +1712,62 @@ impl BmadParser {
         projects
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked_stories_and_last_activity_come_from_the_files() {
+        let project = std::env::temp_dir().join(format!("bmad-parse-{}", uuid::Uuid::new_v4()));
+        let docs = project.join("bmad-docs");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(
+            docs.join("sprint-status.yaml"),
+            "development_status:\n  epic-1: in-progress\n  1-1-setup: blocked\n  1-2-login: review\n  1-3-logout: backlog\n",
+        )
+        .unwrap();
+        fs::write(docs.join("1-2-login.md"), "# Story 1.2: Login\n\n**Status:** Blocked\n").unwrap();
+        fs::write(docs.join("1-3-logout.md"), "# Story 1.3: Logout\n\nStatus: backlog\n").unwrap();
+
+        let parsed = BmadParser::parse_project(&project, Some(&docs)).unwrap();
+        let stories: Vec<(&str, bool)> = parsed
+            .epics
+            .iter()
+            .flat_map(|epic| &epic.stories)
+            .map(|story| (story.number.as_str(), story.blocked))
+            .collect();
+        assert!(stories.contains(&("1.2", true)));
+        assert!(stories.contains(&("1.3", false)));
+        assert_eq!(
+            parsed.sprint_status.unwrap().development_status["epic-1"].blocked,
+            ["1-1-setup"]
+        );
+
+        let newest = ["sprint-status.yaml", "1-2-login.md", "1-3-logout.md"]
+            .iter()
+            .map(|file| fs::metadata(docs.join(file)).unwrap().modified().unwrap())
+            .max()
+            .unwrap();
+        assert_eq!(parsed.last_activity, Some(DateTime::<Utc>::from(newest).to_rfc3339()));
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn fresh_install_parses_as_an_empty_project() {
+        let project = std::env::temp_dir().join(format!("bmad-fresh-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(project.join("_bmad/bmm")).unwrap();
+        fs::write(
+            project.join("_bmad/bmm/config.yaml"),
+            "project_name: fresh\noutput_folder: \"{project-root}/_bmad-output\"\n",
+        )
+        .unwrap();
+
+        assert!(BmadParser::is_bmad_project(&project));
+        let parsed = BmadParser::parse_project(&project, None).unwrap();
+        assert_eq!(parsed.bmad_docs_path, project.join("_bmad-output").to_string_lossy());
+        assert!(parsed.epics.is_empty() && parsed.documents.is_empty());
+        assert_eq!(parsed.last_activity, None);
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
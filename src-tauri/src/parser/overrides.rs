@@ -0,0 +1,346 @@
+use super::{BmadParser, ParseError};
+use crate::models::{EpicStatus, StoryStatus};
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Optional project-local file describing a non-standard layout
+pub const OVERRIDES_FILE: &str = ".bmad-dash.yaml";
+
+/// Built-in story filename pattern: `<epic>-<story>-<slug>.md`
+const STORY_PATTERN: &str = r"^(\d+)-(\d+)-.+\.md$";
+
+/// Built-in epic filename pattern, e.g. `epic-3.md` or `epic3-title.md`
+const EPIC_PATTERN: &str = r"epic-?(\d+)";
+
+/// `.bmad-dash.yaml` as written. Paths are relative to the project root
+/// (or use `{project-root}`) and must stay inside it, since stories are
+/// written and moved there; globs are matched against paths relative to it.
+///
+/// ```yaml
+/// docs: work
+/// stories: work/stories
+/// epics: work/epics
+/// sprint_status: tracking/status.yaml
+/// patterns:
+///   story: '^S(?P<epic>\d+)\.(?P<story>\d+)-.+\.md$'
+///   epic: '^EPIC-(?P<epic>\d+)\.md$'
+/// statuses:
+///   story: { todo: backlog, doing: in-progress, qa: review }
+///   epic: { active: in-progress }
+/// documents:
+///   - pattern: "work/specs/*.md"
+///     type: prd
+/// ignore:
+///   - "work/archive/**"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OverridesFile {
+    docs: Option<String>,
+    stories: Option<String>,
+    epics: Option<String>,
+    sprint_status: Option<String>,
+    patterns: PatternsFile,
+    statuses: StatusesFile,
+    documents: Vec<DocumentMapping>,
+    ignore: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PatternsFile {
+    story: Option<String>,
+    epic: Option<String>,
+}
+
+/// Project status words mapped to the built-in ones
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatusesFile {
+    story: HashMap<String, StoryStatus>,
+    epic: HashMap<String, EpicStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DocumentMapping {
+    pattern: String,
+    #[serde(rename = "type")]
+    doc_type: String,
+}
+
+/// A project's `.bmad-dash.yaml`, checked and compiled. Every field falls
+/// back to the parser's built-in heuristics when the file leaves it out
+/// (or when there is no file at all).
+#[derive(Debug, Clone)]
+pub struct ProjectOverrides {
+    root: PathBuf,
+    pub docs_dir: Option<PathBuf>,
+    pub stories_dir: Option<PathBuf>,
+    pub epics_dir: Option<PathBuf>,
+    pub sprint_status: Option<PathBuf>,
+    story_pattern: Regex,
+    epic_pattern: Regex,
+    custom_epic_pattern: bool,
+    story_statuses: HashMap<String, StoryStatus>,
+    epic_statuses: HashMap<String, EpicStatus>,
+    documents: Vec<(GlobMatcher, String)>,
+    ignore: GlobSet,
+}
+
+impl Default for ProjectOverrides {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::new(),
+            docs_dir: None,
+            stories_dir: None,
+            epics_dir: None,
+            sprint_status: None,
+            story_pattern: Regex::new(STORY_PATTERN).unwrap(),
+            epic_pattern: Regex::new(EPIC_PATTERN).unwrap(),
+            custom_epic_pattern: false,
+            story_statuses: HashMap::new(),
+            epic_statuses: HashMap::new(),
+            documents: Vec::new(),
+            ignore: GlobSet::empty(),
+        }
+    }
+}
+
+impl ProjectOverrides {
+    /// Read `.bmad-dash.yaml` from the project root; the built-in layout if there is none
+    pub fn load(project_path: &Path) -> Result<Self, ParseError> {
+        let file = project_path.join(OVERRIDES_FILE);
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse(project_path, &content)
+    }
+
+    /// Check and compile the content of a `.bmad-dash.yaml` in `project_path`
+    pub fn parse(project_path: &Path, content: &str) -> Result<Self, ParseError> {
+        let parsed: OverridesFile = serde_yaml::from_str(content)
+            .map_err(|e| ParseError::InvalidOverrides(e.to_string()))?;
+        Self::compile(project_path, parsed)
+    }
+
+    /// Overrides for a docs folder, read from the closest project root above
+    /// it (a folder with `.bmad-dash.yaml`, `_bmad` or `.git`)
+    pub fn for_docs_dir(bmad_dir: &Path) -> Result<Self, ParseError> {
+        match BmadParser::project_root(bmad_dir) {
+            Some(root) => Self::load(&root),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn compile(project_path: &Path, file: OverridesFile) -> Result<Self, ParseError> {
+        let path = |value: Option<String>| {
+            value
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| inside_project(project_path, &v))
+                .transpose()
+        };
+        let regex = |pattern: &str, groups: usize| {
+            let regex = Regex::new(pattern)
+                .map_err(|e| ParseError::InvalidOverrides(format!("{}: {}", pattern, e)))?;
+            if regex.captures_len() <= groups {
+                return Err(ParseError::InvalidOverrides(format!(
+                    "{} needs {} capture group(s)",
+                    pattern, groups
+                )));
+            }
+            Ok(regex)
+        };
+        let glob = |pattern: &str| {
+            Glob::new(pattern)
+                .map_err(|e| ParseError::InvalidOverrides(format!("{}: {}", pattern, e)))
+        };
+
+        let mut ignore = GlobSetBuilder::new();
+        for pattern in &file.ignore {
+            ignore.add(glob(pattern)?);
+        }
+        let documents = file
+            .documents
+            .iter()
+            .map(|mapping| Ok((glob(&mapping.pattern)?.compile_matcher(), mapping.doc_type.clone())))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        Ok(Self {
+            root: project_path.to_path_buf(),
+            docs_dir: path(file.docs)?,
+            stories_dir: path(file.stories)?,
+            epics_dir: path(file.epics)?,
+            sprint_status: path(file.sprint_status)?,
+            story_pattern: regex(file.patterns.story.as_deref().unwrap_or(STORY_PATTERN), 2)?,
+            custom_epic_pattern: file.patterns.epic.is_some(),
+            epic_pattern: regex(file.patterns.epic.as_deref().unwrap_or(EPIC_PATTERN), 1)?,
+            story_statuses: file.statuses.story,
+            epic_statuses: file.statuses.epic,
+            documents,
+            ignore: ignore
+                .build()
+                .map_err(|e| ParseError::InvalidOverrides(e.to_string()))?,
+        })
+    }
+
+    /// Epic and story number from a story filename. Patterns may name their
+    /// groups `epic` and `story`; otherwise the first two groups are used.
+    pub fn story_numbers(&self, filename: &str) -> Option<(u32, u32)> {
+        let caps = self.story_pattern.captures(filename)?;
+        let epic = caps.name("epic").or_else(|| caps.get(1))?;
+        let story = caps.name("story").or_else(|| caps.get(2))?;
+        Some((epic.as_str().parse().ok()?, story.as_str().parse().ok()?))
+    }
+
+    /// Story filename moved to another epic and/or number, keeping the
+    /// rest of the name as the story pattern matched it
+    pub fn renamed_story_file(&self, filename: &str, to: (u32, u32)) -> Option<String> {
+        let caps = self.story_pattern.captures(filename)?;
+        let epic = caps.name("epic").or_else(|| caps.get(1))?;
+        let story = caps.name("story").or_else(|| caps.get(2))?;
+        let mut spans = [(epic.range(), to.0), (story.range(), to.1)];
+        spans.sort_by_key(|(range, _)| range.start);
+
+        let mut renamed = String::new();
+        let mut rest = 0;
+        for (range, number) in spans {
+            renamed.push_str(&filename[rest..range.start]);
+            renamed.push_str(&number.to_string());
+            rest = range.end;
+        }
+        renamed.push_str(&filename[rest..]);
+        Some(renamed)
+    }
+
+    /// Epic number from an epic filename (group `epic`, else the first group)
+    pub fn epic_number(&self, filename: &str) -> Option<u32> {
+        let caps = self.epic_pattern.captures(filename)?;
+        let epic = caps.name("epic").or_else(|| caps.get(1))?;
+        epic.as_str().parse().ok()
+    }
+
+    /// Whether a file next to the other docs is an epic file. Without a
+    /// custom pattern these are the `.md` files starting with `epic`.
+    pub fn is_epic_file(&self, filename: &str) -> bool {
+        if self.custom_epic_pattern {
+            self.epic_pattern.is_match(filename)
+        } else {
+            filename.starts_with("epic") && filename.ends_with(".md")
+        }
+    }
+
+    pub fn story_status(&self, value: &str) -> StoryStatus {
+        match self.story_statuses.get(value) {
+            Some(status) => status.clone(),
+            None => BmadParser::parse_story_status(value),
+        }
+    }
+
+    pub fn epic_status(&self, value: &str) -> EpicStatus {
+        match self.epic_statuses.get(value) {
+            Some(status) => status.clone(),
+            None => BmadParser::parse_epic_status(value),
+        }
+    }
+
+    /// Word to write for a story status: the project's own word when
+    /// `statuses.story` maps one to it, else the built-in value
+    pub fn story_status_value<'a>(&'a self, status: &StoryStatus) -> &'a str {
+        custom_word(&self.story_statuses, status).unwrap_or(status.as_str())
+    }
+
+    /// Word to write for an epic status, like `story_status_value`
+    pub fn epic_status_value<'a>(&'a self, status: &EpicStatus) -> &'a str {
+        custom_word(&self.epic_statuses, status).unwrap_or(status.as_str())
+    }
+
+    /// Document type from the first `documents` pattern matching `path`
+    pub fn document_type(&self, path: &Path) -> Option<&str> {
+        let relative = self.relative(path);
+        self.documents
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(relative))
+            .map(|(_, doc_type)| doc_type.as_str())
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        !self.ignore.is_empty() && self.ignore.is_match(self.relative(path))
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+}
+
+/// Project word mapped to a built-in status; the first in sort order when
+/// several map to it, so writes do not depend on the map's order
+fn custom_word<'a, S: PartialEq>(words: &'a HashMap<String, S>, status: &S) -> Option<&'a str> {
+    words
+        .iter()
+        .filter(|(_, mapped)| *mapped == status)
+        .map(|(word, _)| word.as_str())
+        .min()
+}
+
+/// An override path resolved against the project root, with `.` and `..`
+/// folded away. Absolute paths (other than `{project-root}/...`) and paths
+/// climbing out of the root are refused: a cloned repository's
+/// `.bmad-dash.yaml` must not send story writes elsewhere.
+fn inside_project(project_path: &Path, value: &str) -> Result<PathBuf, ParseError> {
+    let outside = || ParseError::InvalidOverrides(format!("path must stay inside the project: {}", value));
+    let value = value.trim();
+    if Path::new(value).is_absolute() {
+        return Err(outside());
+    }
+
+    let mut path = PathBuf::new();
+    for component in BmadParser::resolve_project_path(project_path, value).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !path.pop() {
+                    return Err(outside());
+                }
+            }
+            other => path.push(other),
+        }
+    }
+    if path.starts_with(project_path) {
+        Ok(path)
+    } else {
+        Err(outside())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_must_stay_inside_the_project() {
+        let project = Path::new("/work/app");
+        let parsed = ProjectOverrides::parse(
+            project,
+            "docs: ./work/../docs\nstories: '{project-root}/docs/stories'\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.docs_dir, Some(project.join("docs")));
+        assert_eq!(parsed.stories_dir, Some(project.join("docs/stories")));
+
+        for value in ["/etc", "../other", "docs/../../other", "'{project-root}/../other'"] {
+            let content = format!("stories: {}\n", value);
+            assert!(
+                matches!(ProjectOverrides::parse(project, &content), Err(ParseError::InvalidOverrides(_))),
+                "{} was accepted",
+                value
+            );
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use super::{BmadParser, ParseError};
+use crate::models::{Agent, PartyMember, PartyRoster, Team, TeamMember};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Team entry standing for every installed agent
+const ALL_AGENTS: &str = "*";
+
+/// A team file as written
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TeamFile {
+    bundle: TeamBundle,
+    agents: Vec<String>,
+    /// Party roster CSV, relative to the team file
+    party: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TeamBundle {
+    name: Option<String>,
+    icon: Option<String>,
+    description: Option<String>,
+}
+
+/// A party roster row; same columns as `_config/agent-manifest.csv`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartyRow {
+    name: String,
+    #[serde(default)]
+    display_name: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    module: String,
+}
+
+impl BmadParser {
+    /// Teams defined in `_bmad/*/teams/*.yaml`, by module then file name,
+    /// with each member and party roster row linked to the agents
+    /// `parse_agents` finds. Team files that cannot be read are skipped; a
+    /// party roster that cannot be read leaves the team without a party.
+    pub fn parse_teams(project_path: &Path) -> Result<Vec<Team>, ParseError> {
+        let agents = Self::parse_agents(project_path)?;
+
+        let mut modules: Vec<_> = fs::read_dir(project_path.join("_bmad"))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("teams").is_dir())
+            .collect();
+        modules.sort();
+
+        let mut teams = Vec::new();
+        for module_dir in modules {
+            let module = module_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut files: Vec<_> = fs::read_dir(module_dir.join("teams"))?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|ext| ext == "yaml" || ext == "yml")
+                })
+                .collect();
+            files.sort();
+
+            for file in files {
+                match Self::parse_team(&file, &module, &agents) {
+                    Ok(team) => teams.push(team),
+                    Err(e) => eprintln!("Skipping team {}: {}", file.display(), e),
+                }
+            }
+        }
+
+        Ok(teams)
+    }
+
+    fn parse_team(file: &Path, module: &str, agents: &[Agent]) -> Result<Team, ParseError> {
+        let content = fs::read_to_string(file)?;
+        let team: TeamFile = serde_yaml::from_str(&content)?;
+        let id = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut members: Vec<TeamMember> = Vec::new();
+        for agent_id in &team.agents {
+            let linked: Vec<TeamMember> = if agent_id == ALL_AGENTS {
+                agents
+                    .iter()
+                    .map(|agent| TeamMember {
+                        agent_id: agent.id.clone(),
+                        module: Some(agent.module.clone()),
+                        installed: true,
+                    })
+                    .collect()
+            } else {
+                let agent = find_agent(agents, agent_id, None);
+                vec![TeamMember {
+                    agent_id: agent_id.clone(),
+                    module: agent.map(|a| a.module.clone()),
+                    installed: agent.is_some(),
+                }]
+            };
+            for member in linked {
+                if !members.iter().any(|m| m.agent_id == member.agent_id) {
+                    members.push(member);
+                }
+            }
+        }
+
+        // A missing or broken roster only loses the party, not the team
+        let party = team
+            .party
+            .as_deref()
+            .filter(|party| !party.trim().is_empty())
+            .and_then(|party| {
+                let roster = file.parent().unwrap_or(file).join(party.trim());
+                match Self::parse_party_roster(&roster, agents) {
+                    Ok(party) => Some(party),
+                    Err(e) => {
+                        eprintln!("Skipping party roster {}: {}", roster.display(), e);
+                        None
+                    }
+                }
+            });
+
+        Ok(Team {
+            name: team.bundle.name.filter(|n| !n.is_empty()).unwrap_or_else(|| id.clone()),
+            id,
+            module: module.to_string(),
+            icon: team.bundle.icon.filter(|i| !i.is_empty()),
+            description: team.bundle.description,
+            members,
+            party,
+            file_path: file.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Read a party roster CSV, in file order
+    fn parse_party_roster(file: &Path, agents: &[Agent]) -> Result<PartyRoster, ParseError> {
+        let mut reader = csv::Reader::from_path(file)?;
+        let mut members = Vec::new();
+        for row in reader.deserialize::<PartyRow>() {
+            let row = row?;
+            let installed = find_agent(agents, &row.name, Some(&row.module)).is_some();
+            members.push(PartyMember {
+                agent_id: row.name,
+                display_name: row.display_name,
+                title: row.title,
+                icon: Some(row.icon).filter(|i| !i.is_empty()),
+                role: row.role,
+                module: row.module,
+                installed,
+            });
+        }
+
+        Ok(PartyRoster {
+            file_path: file
+                .canonicalize()
+                .unwrap_or_else(|_| file.to_path_buf())
+                .to_string_lossy()
+                .to_string(),
+            members,
+        })
+    }
+}
+
+/// Installed agent with this id, from `module` when one is given
+fn find_agent<'a>(agents: &'a [Agent], id: &str, module: Option<&str>) -> Option<&'a Agent> {
+    agents.iter().find(|agent| {
+        agent.id == id && module.is_none_or(|module| module.is_empty() || agent.module == module)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_party_roster_keeps_the_team() {
+        let project = std::env::temp_dir().join(format!("bmad-teams-{}", uuid::Uuid::new_v4()));
+        let teams = project.join("_bmad/bmm/teams");
+        std::fs::create_dir_all(&teams).unwrap();
+        std::fs::write(
+            teams.join("team-fullstack.yaml"),
+            "bundle:\n  name: Team Fullstack\nagents:\n  - dev\nparty: ./missing-party.csv\n",
+        )
+        .unwrap();
+
+        let parsed = BmadParser::parse_teams(&project).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "Team Fullstack");
+        assert!(parsed[0].party.is_none());
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+}
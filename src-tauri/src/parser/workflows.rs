@@ -0,0 +1,400 @@
+use super::{front_matter, BmadParser, ParseError};
+use crate::models::{Workflow, WorkflowGroup, WorkflowInput, WorkflowOutput};
+use globset::GlobBuilder;
+use regex::{Captures, Regex};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// How often a variable may refer to another one while being resolved
+const MAX_RESOLVE_DEPTH: usize = 8;
+
+/// Deepest folder searched when checking whether a `**` glob matches anything
+const MAX_GLOB_DEPTH: usize = 8;
+
+/// Value of variables the workflow sets itself when it runs
+const RUN_TIME_VALUE: &str = "system-generated";
+
+/// Keys of an input pattern entry that hold paths, in order of preference
+const INPUT_PATTERN_KEYS: &[&str] = &["whole", "pattern", "sharded_index", "sharded_single"];
+
+/// Top-level or `variables` keys that hold a file the workflow writes:
+/// its main document, the sprint status it updates and the story it works on
+const OUTPUT_PATH_KEYS: &[&str] = &[
+    "default_output_file",
+    "status_file",
+    "sprint_status",
+    "sprint_status_file",
+    "story_file",
+];
+
+impl BmadParser {
+    /// Workflows installed in `_bmad/*/workflows`, grouped by phase folder
+    /// (numbered phases first, then named ones such as `testarch`, then
+    /// workflows without a phase).
+    ///
+    /// `workflow.yaml` files are read in full: variables are resolved
+    /// against the module config and the project root, and inputs and
+    /// outputs are checked against the files on disk. Step-file workflows
+    /// (`workflow.md`) only declare a name and description in their front
+    /// matter.
+    pub fn parse_workflows(project_path: &Path) -> Result<Vec<WorkflowGroup>, ParseError> {
+        let bmad_dir = project_path.join("_bmad");
+        if !bmad_dir.is_dir() {
+            return Err(ParseError::InvalidStructure(format!(
+                "No _bmad folder in {}",
+                project_path.display()
+            )));
+        }
+
+        let mut modules: Vec<_> = fs::read_dir(&bmad_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join("workflows").is_dir())
+            .collect();
+        modules.sort();
+
+        let mut groups: BTreeMap<Option<String>, Vec<Workflow>> = BTreeMap::new();
+        for module_dir in modules {
+            let workflows_dir = module_dir.join("workflows");
+            let mut files: Vec<PathBuf> = WalkDir::new(&workflows_dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .filter(|path| {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    name == "workflow.yaml"
+                        || (name == "workflow.md" && !path.with_file_name("workflow.yaml").exists())
+                })
+                .collect();
+            files.sort();
+
+            for file in files {
+                match Self::parse_workflow(project_path, &module_dir, &workflows_dir, &file) {
+                    Ok(workflow) => groups.entry(workflow.phase.clone()).or_default().push(workflow),
+                    Err(e) => eprintln!("Skipping workflow {}: {}", file.display(), e),
+                }
+            }
+        }
+
+        // `None` sorts first in the map; ungrouped workflows go last
+        let mut groups: Vec<WorkflowGroup> = groups
+            .into_iter()
+            .map(|(phase, workflows)| WorkflowGroup { phase, workflows })
+            .collect();
+        let ungrouped = groups.iter().take_while(|g| g.phase.is_none()).count();
+        groups.rotate_left(ungrouped);
+        Ok(groups)
+    }
+
+    fn parse_workflow(
+        project_path: &Path,
+        module_dir: &Path,
+        workflows_dir: &Path,
+        file: &Path,
+    ) -> Result<Workflow, ParseError> {
+        let content = fs::read_to_string(file)?;
+        let is_markdown = file.extension().is_some_and(|ext| ext == "md");
+        let yaml: Value = if is_markdown {
+            match front_matter(&content) {
+                Some(yaml) => serde_yaml::from_str(yaml)?,
+                None => Value::Null,
+            }
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+        let empty = Mapping::new();
+        let fields = yaml.as_mapping().unwrap_or(&empty);
+
+        let module = module_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let folder = file.parent().unwrap_or(workflows_dir);
+        let id = folder
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Only a folder between `workflows` and the workflow's own folder is a phase
+        let phase = folder
+            .parent()
+            .filter(|parent| *parent != workflows_dir)
+            .and_then(|parent| parent.strip_prefix(workflows_dir).ok())
+            .and_then(|relative| relative.components().next())
+            .map(|phase| phase.as_os_str().to_string_lossy().to_string());
+
+        let resolver = Resolver::new(project_path, module_dir, fields);
+        let text = |key: &str| fields.get(key).and_then(Value::as_str).map(str::to_string);
+        let path = |key: &str| {
+            text(key)
+                .filter(|value| !value.is_empty())
+                .map(|value| resolver.resolve(&value))
+        };
+
+        let config = fields
+            .iter()
+            .filter_map(|(key, value)| Some((key.as_str()?, value.as_str()?)))
+            .filter(|(_, value)| value.starts_with("{config_source}:"))
+            .map(|(key, value)| (key.to_string(), resolver.resolve(value)))
+            .collect();
+        let variables = fields
+            .get("variables")
+            .and_then(Value::as_mapping)
+            .map(|variables| {
+                variables
+                    .iter()
+                    .filter_map(|(key, value)| Some((key.as_str()?, value.as_str()?)))
+                    .map(|(key, value)| (key.to_string(), resolver.resolve(value)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut inputs = Vec::new();
+        if let Some(patterns) = fields.get("input_file_patterns").and_then(Value::as_mapping) {
+            for (key, entry) in patterns {
+                let Some(key) = key.as_str() else { continue };
+                let entry_text = |k: &str| entry.get(k).and_then(Value::as_str).map(str::to_string);
+                let pattern = INPUT_PATTERN_KEYS
+                    .iter()
+                    .find_map(|k| entry_text(k))
+                    .map(|p| resolver.resolve(&p));
+                let sharded = entry_text("sharded").map(|p| resolver.resolve(&p));
+                let exists = pattern
+                    .iter()
+                    .chain(sharded.iter())
+                    .any(|p| pattern_exists(project_path, p));
+                inputs.push(WorkflowInput {
+                    key: key.to_string(),
+                    description: entry_text("description"),
+                    pattern,
+                    sharded,
+                    load_strategy: entry_text("load_strategy"),
+                    exists,
+                });
+            }
+        }
+        // `required_inputs` is a list of single-key maps
+        for required in fields
+            .get("required_inputs")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_mapping)
+        {
+            for (key, value) in required {
+                let (Some(key), Some(value)) = (key.as_str(), value.as_str()) else { continue };
+                let pattern = resolver.resolve(value);
+                inputs.push(WorkflowInput {
+                    key: key.to_string(),
+                    description: None,
+                    exists: pattern_exists(project_path, &pattern),
+                    pattern: Some(pattern),
+                    sharded: None,
+                    load_strategy: None,
+                });
+            }
+        }
+
+        // Several keys often name the same file (`default_output_file: "{status_file}"`)
+        let mut outputs: Vec<WorkflowOutput> = Vec::new();
+        for key in OUTPUT_PATH_KEYS {
+            let value = fields
+                .get(*key)
+                .or_else(|| fields.get("variables").and_then(|variables| variables.get(*key)))
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty());
+            let Some(output) = value.map(|value| resolver.resolve(value)) else { continue };
+            if outputs.iter().any(|o| o.path == output) {
+                continue;
+            }
+            outputs.push(WorkflowOutput {
+                key: key.to_string(),
+                exists: pattern_exists(project_path, &output),
+                path: output,
+            });
+        }
+
+        Ok(Workflow {
+            name: text("name").filter(|n| !n.is_empty()).unwrap_or_else(|| id.clone()),
+            id,
+            description: text("description").unwrap_or_default(),
+            author: text("author"),
+            module,
+            phase,
+            file_path: file.to_string_lossy().to_string(),
+            standalone: fields.get("standalone").and_then(Value::as_bool).unwrap_or(false),
+            instructions: path("instructions"),
+            template: path("template"),
+            validation: path("validation"),
+            config,
+            variables,
+            inputs,
+            outputs,
+        })
+    }
+}
+
+/// Fills in a workflow's `{name}` references: `{project-root}`, the
+/// workflow's own top-level keys and `variables`, and `{config_source}:key`
+/// values from the module config. `{{name}}` placeholders and references
+/// to values only known at run time (`{date}`, `{story_id}`) are kept.
+struct Resolver<'a> {
+    project_path: &'a Path,
+    values: HashMap<String, String>,
+    config: Mapping,
+    config_regex: Regex,
+    reference_regex: Regex,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(project_path: &'a Path, module_dir: &Path, fields: &Mapping) -> Self {
+        let mut values: HashMap<String, String> = HashMap::new();
+        let variables = fields.get("variables").and_then(Value::as_mapping);
+        for (key, value) in fields.iter().chain(variables.into_iter().flatten()) {
+            if let (Some(key), Some(value)) = (key.as_str(), value.as_str()) {
+                values.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        let mut resolver = Self {
+            project_path,
+            values,
+            config: Mapping::new(),
+            // Some installed workflows have a stray `}` after the config key
+            config_regex: Regex::new(r"^\{config_source\}:([\w-]+)\}?$").unwrap(),
+            reference_regex: Regex::new(r"\{\{[^{}]*\}\}|\{([\w-]+)\}").unwrap(),
+        };
+        let config_file = resolver
+            .values
+            .get("config_source")
+            .or_else(|| resolver.values.get("main_config"))
+            .map(|source| PathBuf::from(resolver.resolve(source)))
+            .unwrap_or_else(|| module_dir.join("config.yaml"));
+        resolver.config = fs::read_to_string(config_file)
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+            .unwrap_or_default();
+        resolver
+    }
+
+    fn resolve(&self, value: &str) -> String {
+        self.resolve_at(value, 0)
+    }
+
+    fn resolve_at(&self, value: &str, depth: usize) -> String {
+        if depth > MAX_RESOLVE_DEPTH {
+            return value.to_string();
+        }
+
+        if let Some(caps) = self.config_regex.captures(value.trim()) {
+            return match self.config.get(&caps[1]).and_then(Value::as_str) {
+                Some(configured) => self.resolve_at(configured, depth + 1),
+                None => value.to_string(),
+            };
+        }
+
+        self.reference_regex
+            .replace_all(value, |caps: &Captures| {
+                let Some(name) = caps.get(1).map(|m| m.as_str()) else {
+                    return caps[0].to_string();
+                };
+                if name == "project-root" {
+                    return self.project_path.to_string_lossy().to_string();
+                }
+                match self.values.get(name) {
+                    // e.g. `date`, filled in when the workflow runs
+                    Some(referenced) if referenced == RUN_TIME_VALUE => caps[0].to_string(),
+                    Some(referenced) => self.resolve_at(referenced, depth + 1),
+                    None => caps[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+}
+
+/// Whether a resolved path or glob (relative to the project) matches a file.
+/// References left after resolving are filled at run time and match any name.
+fn pattern_exists(project_path: &Path, pattern: &str) -> bool {
+    let placeholder_regex = Regex::new(r"\{\{[^{}]*\}\}|\{[\w-]+\}").unwrap();
+    let pattern = placeholder_regex.replace_all(pattern, "*");
+    if pattern.contains(['{', '}']) || pattern.trim().is_empty() {
+        return false;
+    }
+
+    let path = project_path.join(pattern.as_ref());
+    let is_glob = |part: &str| part.contains(['*', '?', '[']);
+    if !is_glob(&pattern) {
+        return path.exists();
+    }
+
+    // Walk from the last folder before the first wildcard
+    let mut base = PathBuf::new();
+    let mut rest = Vec::new();
+    for component in path.components() {
+        let part = component.as_os_str().to_string_lossy().to_string();
+        if rest.is_empty() && !is_glob(&part) {
+            base.push(component);
+        } else if !matches!(component, Component::CurDir) {
+            rest.push(part);
+        }
+    }
+    let depth = if rest.iter().any(|part| part == "**") {
+        MAX_GLOB_DEPTH
+    } else {
+        rest.len()
+    };
+    let matcher = match GlobBuilder::new(&rest.join("/")).literal_separator(true).build() {
+        Ok(glob) => glob.compile_matcher(),
+        Err(_) => return false,
+    };
+
+    WalkDir::new(&base)
+        .min_depth(1)
+        .max_depth(depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .any(|entry| {
+            entry
+                .path()
+                .strip_prefix(&base)
+                .is_ok_and(|relative| matcher.is_match(relative))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_include_status_and_story_files() {
+        let project = std::env::temp_dir().join(format!("bmad-workflows-{}", uuid::Uuid::new_v4()));
+        let module = project.join("_bmad/bmm");
+        let workflow = module.join("workflows/4-implementation/sprint-planning");
+        std::fs::create_dir_all(&workflow).unwrap();
+        std::fs::create_dir_all(project.join("out")).unwrap();
+        std::fs::write(module.join("config.yaml"), "implementation_artifacts: out\n").unwrap();
+        std::fs::write(project.join("out/sprint-status.yaml"), "").unwrap();
+        std::fs::write(
+            workflow.join("workflow.yaml"),
+            "name: sprint-planning\n\
+             config_source: \"{project-root}/_bmad/bmm/config.yaml\"\n\
+             implementation_artifacts: \"{config_source}:implementation_artifacts\"\n\
+             story_file: \"\"\n\
+             variables:\n  status_file: \"{implementation_artifacts}/sprint-status.yaml\"\n\
+             default_output_file: \"{status_file}\"\n\
+             sprint_status_file: \"{implementation_artifacts}/summary.md\"\n",
+        )
+        .unwrap();
+
+        let groups = BmadParser::parse_workflows(&project).unwrap();
+        let outputs: Vec<(&str, bool)> = groups[0].workflows[0]
+            .outputs
+            .iter()
+            .map(|o| (o.key.as_str(), o.exists))
+            .collect();
+        assert_eq!(outputs, [("default_output_file", true), ("sprint_status_file", false)]);
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+}
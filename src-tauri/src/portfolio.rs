@@ -0,0 +1,333 @@
+use crate::models::{
+    BmadProject, PortfolioOverview, PortfolioProject, PortfolioQuery, PortfolioSort, PortfolioTotals,
+    ProjectEntry, ProjectStats, StoryRef, StoryStatus,
+};
+use crate::parser::BmadParser;
+use crate::{settings, snapshots};
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Summarize the registered projects for the global dashboard.
+///
+/// Projects come from the snapshot cache when they have been parsed before
+/// and are parsed (and cached) otherwise, so only counts and story
+/// references cross IPC, never document content.
+pub fn overview(entries: &[ProjectEntry], query: &PortfolioQuery) -> PortfolioOverview {
+    let now = Utc::now();
+    let stale_after_days = settings::current().stale_after_days as i64;
+    let mut cached: HashMap<String, BmadProject> = snapshots::store()
+        .and_then(|store| store.cached_projects())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|project| (project.id.clone(), project))
+        .collect();
+
+    let search = query
+        .search
+        .as_deref()
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty());
+    let mut projects: Vec<PortfolioProject> = entries
+        .iter()
+        .filter(|entry| query.include_archived || !entry.archived)
+        .filter(|entry| {
+            search.as_ref().is_none_or(|search| {
+                entry.name.to_lowercase().contains(search) || entry.path.to_lowercase().contains(search)
+            })
+        })
+        .map(|entry| match load_project(entry, cached.remove(&entry.id)) {
+            Ok(project) => summarize(entry, &project, now, stale_after_days),
+            Err(e) => unreadable(entry, e),
+        })
+        .filter(|project| query.phase.is_none_or(|phase| project.phase == phase))
+        .filter(|project| !query.stale_only || project.stale)
+        .collect();
+
+    projects.sort_by(|a, b| compare(a, b, query.sort_by));
+    if query.descending {
+        projects.reverse();
+    }
+
+    PortfolioOverview {
+        generated_at: now.to_rfc3339(),
+        totals: totals(&projects),
+        projects,
+    }
+}
+
+fn load_project(entry: &ProjectEntry, cached: Option<BmadProject>) -> Result<BmadProject, String> {
+    if let Some(project) = cached {
+        return Ok(project);
+    }
+
+    let project = BmadParser::parse_project(
+        Path::new(&entry.path),
+        entry.bmad_docs_path.as_deref().map(Path::new),
+    )
+    .map_err(|e| e.to_string())?;
+    if let Err(e) = snapshots::record(&entry.id, &project) {
+        eprintln!("Failed to snapshot project {}: {}", entry.id, e);
+    }
+    Ok(project)
+}
+
+fn summarize(
+    entry: &ProjectEntry,
+    project: &BmadProject,
+    now: DateTime<Utc>,
+    stale_after_days: i64,
+) -> PortfolioProject {
+    let stats = ProjectStats::from_project(project);
+    let count = |status: &str| stats.stories_by_status.get(status).copied().unwrap_or(0);
+    let review_stories = project
+        .epics
+        .iter()
+        .flat_map(|epic| &epic.stories)
+        .filter(|story| story.status == StoryStatus::Review)
+        .map(|story| StoryRef {
+            number: story.number.clone(),
+            title: story.title.clone(),
+        })
+        .collect();
+
+    let last_activity = project
+        .last_activity
+        .as_deref()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|time| time.with_timezone(&Utc));
+    let idle_days = last_activity.map(|time| (now - time).num_days().max(0));
+    let finished = stats.total_stories > 0 && stats.progress_percentage == 100;
+
+    PortfolioProject {
+        id: entry.id.clone(),
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+        pinned: entry.pinned,
+        archived: entry.archived,
+        phase: project.current_phase,
+        progress_percentage: stats.progress_percentage,
+        total_epics: stats.total_epics,
+        total_stories: stats.total_stories,
+        done_stories: count("done"),
+        in_progress_stories: count("in-progress"),
+        review_stories,
+        blocked_stories: blocked_stories(project),
+        last_activity: last_activity.map(|time| time.to_rfc3339()),
+        idle_days,
+        stale: !finished && idle_days.is_some_and(|days| days >= stale_after_days),
+        error: None,
+    }
+}
+
+fn unreadable(entry: &ProjectEntry, error: String) -> PortfolioProject {
+    PortfolioProject {
+        id: entry.id.clone(),
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+        pinned: entry.pinned,
+        archived: entry.archived,
+        phase: 0,
+        progress_percentage: 0,
+        total_epics: 0,
+        total_stories: 0,
+        done_stories: 0,
+        in_progress_stories: 0,
+        review_stories: Vec::new(),
+        blocked_stories: Vec::new(),
+        last_activity: None,
+        idle_days: None,
+        stale: false,
+        error: Some(error),
+    }
+}
+
+/// Stories the parser found marked blocked in sprint-status.yaml or their story file
+fn blocked_stories(project: &BmadProject) -> Vec<StoryRef> {
+    project
+        .epics
+        .iter()
+        .flat_map(|epic| &epic.stories)
+        .filter(|story| story.blocked)
+        .map(|story| StoryRef {
+            number: story.number.clone(),
+            title: story.title.clone(),
+        })
+        .collect()
+}
+
+fn compare(a: &PortfolioProject, b: &PortfolioProject, sort_by: PortfolioSort) -> Ordering {
+    let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+    match sort_by {
+        PortfolioSort::Pinned => b.pinned.cmp(&a.pinned).then_with(by_name),
+        PortfolioSort::Name => by_name(),
+        PortfolioSort::Progress => a.progress_percentage.cmp(&b.progress_percentage).then_with(by_name),
+        PortfolioSort::Phase => a.phase.cmp(&b.phase).then_with(by_name),
+        // Most recent first; projects without any activity last
+        PortfolioSort::LastActivity => b.idle_days.map(|d| -d).cmp(&a.idle_days.map(|d| -d)).then_with(by_name),
+        // Longest idle first
+        PortfolioSort::Staleness => b.idle_days.cmp(&a.idle_days).then_with(by_name),
+    }
+}
+
+fn totals(projects: &[PortfolioProject]) -> PortfolioTotals {
+    let mut totals = PortfolioTotals {
+        projects: projects.len(),
+        ..Default::default()
+    };
+    for project in projects {
+        totals.stale_projects += project.stale as usize;
+        totals.total_stories += project.total_stories;
+        totals.done_stories += project.done_stories;
+        totals.in_progress_stories += project.in_progress_stories;
+        totals.review_stories += project.review_stories.len();
+        totals.blocked_stories += project.blocked_stories.len();
+    }
+    if totals.total_stories > 0 {
+        totals.progress_percentage =
+            ((totals.done_stories as f64 / totals.total_stories as f64) * 100.0) as u8;
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicStatus, Story};
+    use chrono::Duration;
+
+    fn story(number: &str, status: StoryStatus, blocked: bool) -> Story {
+        Story {
+            id: number.replace('.', "-"),
+            epic_id: "epic-1".to_string(),
+            number: number.to_string(),
+            title: format!("Story {}", number),
+            user_type: String::new(),
+            capability: String::new(),
+            value_benefit: String::new(),
+            acceptance_criteria: Vec::new(),
+            status,
+            blocked,
+            tasks: Vec::new(),
+            file_path: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn project(stories: Vec<Story>, last_activity: Option<DateTime<Utc>>) -> BmadProject {
+        BmadProject {
+            id: String::new(),
+            name: "Demo".to_string(),
+            path: "/work/demo".to_string(),
+            bmad_docs_path: "/work/demo/docs".to_string(),
+            description: None,
+            current_phase: 4,
+            epics: vec![Epic {
+                id: "epic-1".to_string(),
+                number: 1,
+                title: "Accounts".to_string(),
+                goal: String::new(),
+                stories,
+                status: EpicStatus::InProgress,
+                retrospective: None,
+                file_path: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            }],
+            documents: Vec::new(),
+            sprint_status: None,
+            last_activity: last_activity.map(|time| time.to_rfc3339()),
+            created_at: String::new(),
+        }
+    }
+
+    fn entry(name: &str) -> ProjectEntry {
+        ProjectEntry {
+            id: name.to_lowercase(),
+            path: format!("/work/{}", name.to_lowercase()),
+            bmad_docs_path: None,
+            name: name.to_string(),
+            pinned: false,
+            archived: false,
+            added_at: String::new(),
+            watch_options: Default::default(),
+        }
+    }
+
+    #[test]
+    fn idle_unfinished_projects_are_stale() {
+        let now = Utc::now();
+        let stories = vec![
+            story("1.1", StoryStatus::Done, false),
+            story("1.2", StoryStatus::InProgress, true),
+            story("1.3", StoryStatus::Review, false),
+        ];
+
+        let summary = summarize(&entry("Idle"), &project(stories.clone(), Some(now - Duration::days(20))), now, 14);
+        assert_eq!(summary.idle_days, Some(20));
+        assert!(summary.stale);
+        assert_eq!(summary.done_stories, 1);
+        assert_eq!(summary.in_progress_stories, 1);
+        assert_eq!(summary.review_stories.len(), 1);
+        let blocked: Vec<&str> = summary.blocked_stories.iter().map(|s| s.number.as_str()).collect();
+        assert_eq!(blocked, ["1.2"]);
+
+        let recent = summarize(&entry("Recent"), &project(stories, Some(now - Duration::days(3))), now, 14);
+        assert!(!recent.stale);
+
+        // Finished projects and projects without any activity never go stale
+        let done = vec![story("1.1", StoryStatus::Done, false)];
+        let finished = summarize(&entry("Done"), &project(done, Some(now - Duration::days(90))), now, 14);
+        assert!(!finished.stale);
+        let unknown = summarize(&entry("New"), &project(Vec::new(), None), now, 14);
+        assert_eq!(unknown.idle_days, None);
+        assert!(!unknown.stale);
+    }
+
+    #[test]
+    fn totals_add_up_the_projects() {
+        let now = Utc::now();
+        let a = summarize(
+            &entry("A"),
+            &project(
+                vec![story("1.1", StoryStatus::Done, false), story("1.2", StoryStatus::Backlog, true)],
+                Some(now - Duration::days(30)),
+            ),
+            now,
+            14,
+        );
+        let b = summarize(
+            &entry("B"),
+            &project(vec![story("1.1", StoryStatus::Done, false)], Some(now)),
+            now,
+            14,
+        );
+
+        let totals = totals(&[a, b, unreadable(&entry("Broken"), "no docs".to_string())]);
+        assert_eq!(totals.projects, 3);
+        assert_eq!(totals.stale_projects, 1);
+        assert_eq!(totals.total_stories, 3);
+        assert_eq!(totals.done_stories, 2);
+        assert_eq!(totals.blocked_stories, 1);
+        assert_eq!(totals.progress_percentage, 66);
+    }
+
+    #[test]
+    fn last_activity_sorts_recent_first_and_unknown_last() {
+        let now = Utc::now();
+        let with_idle = |name: &str, days: Option<i64>| {
+            summarize(&entry(name), &project(Vec::new(), days.map(|d| now - Duration::days(d))), now, 14)
+        };
+        let mut projects = [with_idle("Old", Some(40)), with_idle("Never", None), with_idle("Fresh", Some(1))];
+
+        projects.sort_by(|a, b| compare(a, b, PortfolioSort::LastActivity));
+        let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Fresh", "Old", "Never"]);
+
+        projects.sort_by(|a, b| compare(a, b, PortfolioSort::Staleness));
+        let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Old", "Fresh", "Never"]);
+    }
+}
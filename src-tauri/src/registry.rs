@@ -0,0 +1,275 @@
+use crate::models::{ProjectEntry, ProjectEntryUpdate};
+use crate::parser::BmadParser;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the registry file layout
+const REGISTRY_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid project registry: {0}")]
+    InvalidRegistry(#[from] serde_json::Error),
+    #[error("Project registry version {0} is newer than this app supports")]
+    UnsupportedVersion(u32),
+    #[error("Project is already registered: {0}")]
+    AlreadyRegistered(String),
+    #[error("Project not found: {0}")]
+    NotFound(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryFile {
+    version: u32,
+    projects: Vec<ProjectEntry>,
+}
+
+/// The projects the user added, kept as `projects.json` in the app data
+/// directory. This, not the webview's local storage, is the list of
+/// projects the app opens, watches and aggregates.
+pub struct ProjectRegistry {
+    file: PathBuf,
+    projects: Vec<ProjectEntry>,
+}
+
+impl ProjectRegistry {
+    /// Read the registry, starting empty if the file does not exist yet or
+    /// cannot be parsed (the broken file is moved aside)
+    pub fn load(file: PathBuf) -> Result<Self, RegistryError> {
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    file,
+                    projects: Vec::new(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let projects = match serde_json::from_str::<RegistryFile>(&content) {
+            Ok(registry) if registry.version > REGISTRY_VERSION => {
+                return Err(RegistryError::UnsupportedVersion(registry.version));
+            }
+            Ok(registry) => registry.projects,
+            Err(e) => {
+                let backup = move_aside(&file)?;
+                eprintln!(
+                    "Invalid project registry moved to {}: {}",
+                    backup.display(),
+                    RegistryError::from(e)
+                );
+                Vec::new()
+            }
+        };
+
+        Ok(Self { file, projects })
+    }
+
+    /// [`load`](Self::load) for startup: a registry that cannot be read or
+    /// comes from a newer version is logged and moved aside, and the app
+    /// starts with no projects instead of failing to launch
+    pub fn load_or_empty(file: PathBuf) -> Self {
+        match Self::load(file.clone()) {
+            Ok(registry) => registry,
+            Err(e) => {
+                match move_aside(&file) {
+                    Ok(backup) => eprintln!(
+                        "Project registry {} could not be loaded ({}); moved to {}",
+                        file.display(),
+                        e,
+                        backup.display()
+                    ),
+                    Err(move_error) => eprintln!(
+                        "Project registry {} could not be loaded ({}) or moved aside ({})",
+                        file.display(),
+                        e,
+                        move_error
+                    ),
+                }
+                Self {
+                    file,
+                    projects: Vec::new(),
+                }
+            }
+        }
+    }
+
+    pub fn list(&self) -> &[ProjectEntry] {
+        &self.projects
+    }
+
+    pub fn get(&self, id: &str) -> Result<&ProjectEntry, RegistryError> {
+        self.projects
+            .iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| RegistryError::NotFound(id.to_string()))
+    }
+
+    /// Register a project folder. `id` keeps the id a project already has
+    /// elsewhere (e.g. in the webview's store); otherwise a new one is made.
+    pub fn add(
+        &mut self,
+        path: &Path,
+        bmad_docs_path: Option<&Path>,
+        id: Option<String>,
+    ) -> Result<ProjectEntry, RegistryError> {
+        let path_string = path.to_string_lossy().to_string();
+        if self.projects.iter().any(|p| p.path == path_string) {
+            return Err(RegistryError::AlreadyRegistered(path_string));
+        }
+
+        let entry = ProjectEntry {
+            id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            path: path_string,
+            bmad_docs_path: bmad_docs_path.map(|p| p.to_string_lossy().to_string()),
+            name: project_name(path),
+            pinned: false,
+            archived: false,
+            added_at: Utc::now().to_rfc3339(),
+            watch_options: Default::default(),
+        };
+        self.projects.push(entry.clone());
+        self.save()?;
+        Ok(entry)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<ProjectEntry, RegistryError> {
+        let index = self
+            .projects
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| RegistryError::NotFound(id.to_string()))?;
+        let entry = self.projects.remove(index);
+        self.save()?;
+        Ok(entry)
+    }
+
+    /// Apply an update; a new `bmad_docs_path` must already be checked
+    /// against the scope (see `update_project`)
+    pub fn update(
+        &mut self,
+        id: &str,
+        update: ProjectEntryUpdate,
+    ) -> Result<ProjectEntry, RegistryError> {
+        let entry = self
+            .projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| RegistryError::NotFound(id.to_string()))?;
+
+        if let Some(name) = update.name.filter(|n| !n.trim().is_empty()) {
+            entry.name = name;
+        }
+        if let Some(docs_path) = update.bmad_docs_path {
+            entry.bmad_docs_path = Some(docs_path).filter(|p| !p.is_empty());
+        }
+        if let Some(pinned) = update.pinned {
+            entry.pinned = pinned;
+        }
+        if let Some(archived) = update.archived {
+            entry.archived = archived;
+        }
+        if let Some(watch_options) = update.watch_options {
+            entry.watch_options = watch_options;
+        }
+
+        let entry = entry.clone();
+        self.save()?;
+        Ok(entry)
+    }
+
+    /// Whether a project folder is registered
+    pub fn contains_path(&self, path: &Path) -> bool {
+        self.projects.iter().any(|p| Path::new(&p.path) == path)
+    }
+
+    /// Write the registry through a temp file so a crash never leaves it half-written
+    fn save(&self) -> Result<(), RegistryError> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&RegistryFile {
+            version: REGISTRY_VERSION,
+            projects: self.projects.clone(),
+        })?;
+
+        let temp_path = self.file.with_extension("json.tmp");
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &self.file)?;
+        Ok(())
+    }
+}
+
+/// Name for a new project: the BMM config's `project_name`, else the folder name
+pub fn project_name(path: &Path) -> String {
+    BmadParser::bmm_config_value(path, "project_name")
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Unknown Project".to_string())
+}
+
+/// Keep a registry file the app cannot use for the user, instead of
+/// overwriting it on the next save
+fn move_aside(file: &Path) -> std::io::Result<PathBuf> {
+    let backup = file.with_extension(format!(
+        "json.invalid-{}",
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::rename(file, &backup)?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry_file(content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bmad-registry-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("projects.json");
+        fs::write(&file, content).unwrap();
+        file
+    }
+
+    fn backups(file: &Path) -> usize {
+        fs::read_dir(file.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".invalid-"))
+            .count()
+    }
+
+    #[test]
+    fn newer_registry_is_moved_aside_at_startup() {
+        let file = registry_file(r#"{"version": 99, "projects": []}"#);
+        assert!(matches!(
+            ProjectRegistry::load(file.clone()),
+            Err(RegistryError::UnsupportedVersion(99))
+        ));
+
+        let registry = ProjectRegistry::load_or_empty(file.clone());
+        assert!(registry.list().is_empty());
+        assert!(!file.exists());
+        assert_eq!(backups(&file), 1);
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn unparseable_registry_starts_empty() {
+        let file = registry_file("{ not json");
+        let registry = ProjectRegistry::load_or_empty(file.clone());
+        assert!(registry.list().is_empty());
+        assert_eq!(backups(&file), 1);
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Extensions the app may always write; the `writableExtensions` setting adds more
+const DEFAULT_WRITE_EXTENSIONS: &[&str] = &["md", "markdown", "yaml", "yml"];
+
+/// The only extensions `writableExtensions` may add. The setting is written
+/// by the webview, so it can never open scripts or binaries to writes.
+pub const OPTIONAL_WRITE_EXTENSIONS: &[&str] = &["txt", "csv", "json", "toml", "xml", "excalidraw"];
+
+#[derive(Error, Debug)]
+pub enum ScopeError {
+    #[error("Path must be absolute: {0}")]
+    RelativePath(String),
+    #[error("Path may not contain '..': {0}")]
+    ParentTraversal(String),
+    #[error("Path is outside the opened projects: {0}")]
+    OutsideProjects(String),
+    #[error("Writing .{0} files is not allowed")]
+    ExtensionNotAllowed(String),
+    #[error("Refusing to open {0} as a project root")]
+    UnsafeRoot(String),
+    #[error("Folder was not picked in the folder dialog or found by a scan: {0}")]
+    NotGranted(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Registry of project roots the file commands may touch.
+///
+/// Roots are the folders of the projects in the `ProjectRegistry`, added
+/// when a project is registered or reopened at launch. Every path coming
+/// from the webview is canonicalized and must land inside one of them, so
+/// `..` segments and symlinks cannot reach the rest of the file system.
+///
+/// A folder path sent by the webview never becomes a root by itself:
+/// `add_project` only takes folders granted here, i.e. picked by the user
+/// in a native dialog or found by the backend's own scans.
+pub struct ProjectScope {
+    roots: Vec<PathBuf>,
+    granted: Vec<PathBuf>,
+    write_extensions: Vec<String>,
+}
+
+impl Default for ProjectScope {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            granted: Vec::new(),
+            write_extensions: DEFAULT_WRITE_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+impl ProjectScope {
+    /// Let a folder (and everything below it) be added as a project later
+    pub fn grant(&mut self, path: &Path) -> Result<PathBuf, ScopeError> {
+        check_components(path)?;
+        let folder = fs::canonicalize(path)?;
+        if !self.granted.contains(&folder) {
+            self.granted.push(folder.clone());
+        }
+        Ok(folder)
+    }
+
+    /// Canonical path of a folder inside a granted one (or a registered root)
+    pub fn resolve_granted(&self, path: &Path) -> Result<PathBuf, ScopeError> {
+        check_components(path)?;
+        let folder = fs::canonicalize(path)?;
+        if self.granted.iter().chain(&self.roots).any(|granted| folder.starts_with(granted)) {
+            Ok(folder)
+        } else {
+            Err(ScopeError::NotGranted(path.to_string_lossy().to_string()))
+        }
+    }
+
+    /// Extensions writable on top of Markdown and YAML, from the settings;
+    /// any not in `OPTIONAL_WRITE_EXTENSIONS` are ignored
+    pub fn allow_write_extensions(&mut self, extensions: &[String]) {
+        self.write_extensions = DEFAULT_WRITE_EXTENSIONS
+            .iter()
+            .map(|e| e.to_string())
+            .chain(
+                extensions
+                    .iter()
+                    .map(|e| e.to_lowercase())
+                    .filter(|e| OPTIONAL_WRITE_EXTENSIONS.contains(&e.as_str())),
+            )
+            .collect();
+    }
+
+    /// Add a project (or bmad-docs) folder. The home directory and its
+    /// ancestors are refused since they would open up everything below.
+    pub fn register(&mut self, path: &Path) -> Result<PathBuf, ScopeError> {
+        check_components(path)?;
+        let root = fs::canonicalize(path)?;
+
+        let home = dirs::home_dir().and_then(|h| fs::canonicalize(h).ok());
+        let too_broad = root.parent().is_none()
+            || home.as_ref().map(|h| h.starts_with(&root)).unwrap_or(false);
+        if too_broad {
+            return Err(ScopeError::UnsafeRoot(path.to_string_lossy().to_string()));
+        }
+
+        if !self.roots.contains(&root) {
+            self.roots.push(root.clone());
+        }
+        Ok(root)
+    }
+
+    pub fn unregister(&mut self, path: &Path) {
+        let root = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.roots.retain(|r| *r != root);
+    }
+
+    /// Canonical path for a file or folder inside a registered root.
+    ///
+    /// The path need not exist yet, but its parent folder must.
+    pub fn resolve(&self, path: &Path) -> Result<PathBuf, ScopeError> {
+        check_components(path)?;
+
+        let resolved = match fs::canonicalize(path) {
+            Ok(resolved) => resolved,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let parent = path.parent().ok_or(e)?;
+                let name = path
+                    .file_name()
+                    .ok_or_else(|| ScopeError::OutsideProjects(path.to_string_lossy().to_string()))?;
+                fs::canonicalize(parent)?.join(name)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if self.roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(ScopeError::OutsideProjects(path.to_string_lossy().to_string()))
+        }
+    }
+
+    /// Like `resolve` for a folder that may not exist yet: its closest
+    /// existing ancestor must be inside a registered root
+    pub fn resolve_dir(&self, path: &Path) -> Result<PathBuf, ScopeError> {
+        check_components(path)?;
+        let existing = path.ancestors().find(|dir| dir.exists()).unwrap_or(path);
+        let missing = path.strip_prefix(existing).unwrap_or(Path::new(""));
+        Ok(self.resolve(existing)?.join(missing))
+    }
+
+    /// Like `resolve`, but also requires a writable file extension
+    pub fn resolve_writable(&self, path: &Path) -> Result<PathBuf, ScopeError> {
+        let resolved = self.resolve(path)?;
+        let extension = resolved
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if self.write_extensions.contains(&extension) {
+            Ok(resolved)
+        } else {
+            Err(ScopeError::ExtensionNotAllowed(extension))
+        }
+    }
+}
+
+fn check_components(path: &Path) -> Result<(), ScopeError> {
+    if !path.is_absolute() {
+        return Err(ScopeError::RelativePath(path.to_string_lossy().to_string()));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(ScopeError::ParentTraversal(path.to_string_lossy().to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_granted_folders_and_registered_roots_are_reachable() {
+        let base = std::env::temp_dir().join(format!("bmad-dash-scope-{}", uuid::Uuid::new_v4()));
+        let project = base.join("project");
+        let secrets = base.join("secrets");
+        fs::create_dir_all(project.join("docs")).unwrap();
+        fs::create_dir_all(&secrets).unwrap();
+        fs::write(secrets.join("key"), "secret").unwrap();
+
+        let mut scope = ProjectScope::default();
+        assert!(matches!(scope.resolve_granted(&project), Err(ScopeError::NotGranted(_))));
+        scope.grant(&project).unwrap();
+        assert!(scope.resolve_granted(&project.join("docs")).is_ok());
+        assert!(scope.resolve_granted(&secrets).is_err());
+
+        // Granting alone opens nothing to the file commands
+        assert!(scope.resolve(&project.join("docs/prd.md")).is_err());
+        scope.register(&project).unwrap();
+        assert!(scope.resolve(&project.join("docs/prd.md")).is_ok());
+
+        assert!(matches!(
+            scope.resolve(&project.join("../secrets/key")),
+            Err(ScopeError::ParentTraversal(_))
+        ));
+        assert!(scope.resolve(&secrets.join("key")).is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&secrets, project.join("link")).unwrap();
+            assert!(scope.resolve(&project.join("link/key")).is_err());
+        }
+
+        assert_eq!(
+            scope.resolve_dir(&project.join("docs/stories/new")).unwrap(),
+            fs::canonicalize(&project).unwrap().join("docs/stories/new")
+        );
+        assert!(scope.resolve_dir(&secrets.join("stories")).is_err());
+        assert!(scope.resolve_dir(&project.join("new/../../secrets")).is_err());
+
+        assert!(scope.resolve_writable(&project.join("docs/prd.md")).is_ok());
+        assert!(scope.resolve_writable(&project.join("docs/notes.txt")).is_err());
+        scope.allow_write_extensions(&["TXT".to_string(), "sh".to_string()]);
+        assert!(scope.resolve_writable(&project.join("docs/notes.txt")).is_ok());
+        assert!(matches!(
+            scope.resolve_writable(&project.join("docs/run.sh")),
+            Err(ScopeError::ExtensionNotAllowed(_))
+        ));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::models::AppSettings;
+use crate::scope::OPTIONAL_WRITE_EXTENSIONS;
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Version of the settings file layout
+const SETTINGS_VERSION: u64 = 2;
+
+/// Upgrades from older settings layouts: `MIGRATIONS[n - 1]` turns a
+/// version `n` file into version `n + 1`. Add a step here whenever a field
+/// is renamed or changes meaning; new fields only need a default.
+const MIGRATIONS: &[fn(&mut Value)] = &[nest_watch_settings];
+
+/// Deepest folder searched for projects, also for `scan_projects`
+pub const MAX_SCAN_DEPTH: usize = 10;
+const MAX_STALE_AFTER_DAYS: u32 = 365;
+const DEBOUNCE_RANGE_MS: (u64, u64) = (50, 10_000);
+const DISCOVER_INTERVAL_RANGE_SECS: (u64, u64) = (30, 86_400);
+
+static STORE: OnceLock<SettingsStore> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid settings file: {0}")]
+    InvalidFile(#[from] serde_json::Error),
+    #[error("Settings version {0} is newer than this app supports")]
+    UnsupportedVersion(u64),
+    #[error("Invalid setting {0}: {1}")]
+    InvalidValue(&'static str, String),
+    #[error("Settings are not available")]
+    Unavailable,
+}
+
+/// Settings loaded from `settings.json` in the app data directory
+struct SettingsStore {
+    file: PathBuf,
+    settings: RwLock<AppSettings>,
+}
+
+/// Load the settings file; called once at startup. A file that cannot be
+/// read is moved aside and the defaults are used instead.
+pub fn init(file: PathBuf) {
+    let settings = match load(&file) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Failed to load settings from {}: {}", file.display(), e);
+            if file.exists() {
+                let backup = file.with_extension(format!(
+                    "json.invalid-{}",
+                    Utc::now().format("%Y%m%d%H%M%S")
+                ));
+                let _ = fs::rename(&file, backup);
+            }
+            AppSettings::default()
+        }
+    };
+    let _ = STORE.set(SettingsStore {
+        file,
+        settings: RwLock::new(settings),
+    });
+}
+
+/// Current settings; the defaults if `init` has not run
+pub fn current() -> AppSettings {
+    STORE
+        .get()
+        .map(|store| store.settings.read().clone())
+        .unwrap_or_default()
+}
+
+/// Folders to search for projects: the scan roots that exist right now,
+/// or the home directory if none are set
+pub fn scan_roots(settings: &AppSettings) -> Vec<PathBuf> {
+    if settings.scan_roots.is_empty() {
+        dirs::home_dir().into_iter().collect()
+    } else {
+        settings
+            .scan_roots
+            .iter()
+            .map(PathBuf::from)
+            .filter(|root| root.is_dir())
+            .collect()
+    }
+}
+
+/// Validate, save and apply new settings. Returns them as stored, e.g.
+/// with extensions lower-cased and duplicates dropped.
+pub fn update(settings: AppSettings) -> Result<AppSettings, SettingsError> {
+    let store = STORE.get().ok_or(SettingsError::Unavailable)?;
+    let settings = validate(settings)?;
+    if let Some(root) = missing_scan_roots(&settings).next() {
+        return Err(SettingsError::InvalidValue(
+            "scanRoots",
+            format!("not a folder: {}", root),
+        ));
+    }
+    if let Some(ext) = unknown_writable_extensions(&settings).next() {
+        return Err(SettingsError::InvalidValue(
+            "writableExtensions",
+            format!(".{} files cannot be made writable", ext),
+        ));
+    }
+    save(&store.file, &settings)?;
+    *store.settings.write() = settings.clone();
+    Ok(settings)
+}
+
+fn load(file: &Path) -> Result<AppSettings, SettingsError> {
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AppSettings::default()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut value: Value = serde_json::from_str(&content)?;
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if version > SETTINGS_VERSION {
+        return Err(SettingsError::UnsupportedVersion(version));
+    }
+    for migrate in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migrate(&mut value);
+    }
+
+    let mut settings = validate(serde_json::from_value(value)?)?;
+    // A drive that is not mounted yet; scans skip the root until it is back
+    for root in missing_scan_roots(&settings) {
+        eprintln!("Scan root {} is not a folder, skipping it for now", root);
+    }
+    // Saved before the allowlist existed; the scope would ignore them anyway
+    let unknown: Vec<String> = unknown_writable_extensions(&settings).cloned().collect();
+    if !unknown.is_empty() {
+        eprintln!("Dropping writable extensions {}", unknown.join(", "));
+        settings.writable_extensions.retain(|ext| !unknown.contains(ext));
+    }
+    if version < SETTINGS_VERSION {
+        save(file, &settings)?;
+    }
+    Ok(settings)
+}
+
+/// Version 1 to 2: `debounceMs` and `watchedExtensions` move into `watch`
+fn nest_watch_settings(value: &mut Value) {
+    let Some(settings) = value.as_object_mut() else {
+        return;
+    };
+    let mut watch = serde_json::Map::new();
+    if let Some(debounce) = settings.remove("debounceMs") {
+        watch.insert("debounceMs".into(), debounce);
+    }
+    if let Some(extensions) = settings.remove("watchedExtensions") {
+        watch.insert("extensions".into(), extensions);
+    }
+    settings.insert("watch".into(), Value::Object(watch));
+}
+
+/// Write the settings through a temp file so a crash never leaves them half-written
+fn save(file: &Path, settings: &AppSettings) -> Result<(), SettingsError> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut value = serde_json::to_value(settings)?;
+    value["version"] = SETTINGS_VERSION.into();
+
+    let temp_path = file.with_extension("json.tmp");
+    let mut temp = fs::File::create(&temp_path)?;
+    temp.write_all(serde_json::to_string_pretty(&value)?.as_bytes())?;
+    temp.sync_all()?;
+    fs::rename(&temp_path, file)?;
+    Ok(())
+}
+
+fn missing_scan_roots(settings: &AppSettings) -> impl Iterator<Item = &String> {
+    settings.scan_roots.iter().filter(|root| !Path::new(root).is_dir())
+}
+
+fn unknown_writable_extensions(settings: &AppSettings) -> impl Iterator<Item = &String> {
+    settings
+        .writable_extensions
+        .iter()
+        .filter(|ext| !OPTIONAL_WRITE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Check every setting and normalize the lists. Scan roots only have to
+/// exist when they are changed (see `update`), not when the file is loaded.
+fn validate(mut settings: AppSettings) -> Result<AppSettings, SettingsError> {
+    if !(1..=MAX_SCAN_DEPTH).contains(&settings.scan_depth) {
+        return Err(SettingsError::InvalidValue(
+            "scanDepth",
+            format!("must be between 1 and {}", MAX_SCAN_DEPTH),
+        ));
+    }
+
+    let (min_debounce, max_debounce) = DEBOUNCE_RANGE_MS;
+    if !(min_debounce..=max_debounce).contains(&settings.watch.debounce_ms) {
+        return Err(SettingsError::InvalidValue(
+            "watch.debounceMs",
+            format!("must be between {} and {} ms", min_debounce, max_debounce),
+        ));
+    }
+
+    if !(1..=MAX_STALE_AFTER_DAYS).contains(&settings.stale_after_days) {
+        return Err(SettingsError::InvalidValue(
+            "staleAfterDays",
+            format!("must be between 1 and {}", MAX_STALE_AFTER_DAYS),
+        ));
+    }
+
+    let (min_interval, max_interval) = DISCOVER_INTERVAL_RANGE_SECS;
+    if !(min_interval..=max_interval).contains(&settings.discover_interval_secs) {
+        return Err(SettingsError::InvalidValue(
+            "discoverIntervalSecs",
+            format!("must be between {} and {} seconds", min_interval, max_interval),
+        ));
+    }
+
+    settings.scan_roots = dedup(settings.scan_roots.iter().map(|root| root.trim().to_string()));
+
+    settings.watch.extensions = dedup(
+        settings
+            .watch
+            .extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase()),
+    );
+    if settings.watch.extensions.is_empty() {
+        return Err(SettingsError::InvalidValue(
+            "watch.extensions",
+            "at least one extension is required".into(),
+        ));
+    }
+    if let Some(ext) = settings
+        .watch
+        .extensions
+        .iter()
+        .find(|ext| !ext.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err(SettingsError::InvalidValue(
+            "watch.extensions",
+            format!("not a file extension: {}", ext),
+        ));
+    }
+
+    settings.writable_extensions = dedup(
+        settings
+            .writable_extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase()),
+    );
+    if let Some(ext) = settings
+        .writable_extensions
+        .iter()
+        .find(|ext| !ext.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err(SettingsError::InvalidValue(
+            "writableExtensions",
+            format!("not a file extension: {}", ext),
+        ));
+    }
+
+    settings.docs_folder_names = dedup(settings.docs_folder_names.iter().map(|name| name.trim().to_string()));
+    if settings.docs_folder_names.is_empty() {
+        return Err(SettingsError::InvalidValue(
+            "docsFolderNames",
+            "at least one folder name is required".into(),
+        ));
+    }
+    if let Some(name) = settings
+        .docs_folder_names
+        .iter()
+        .find(|name| name.contains(['/', '\\']) || name.as_str() == "." || name.as_str() == "..")
+    {
+        return Err(SettingsError::InvalidValue(
+            "docsFolderNames",
+            format!("not a folder name: {}", name),
+        ));
+    }
+
+    Ok(settings)
+}
+
+/// Non-empty values in their first-seen order
+fn dedup(values: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    values
+        .filter(|value| !value.is_empty() && seen.insert(value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings_file(content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bmad-settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("settings.json");
+        fs::write(&file, content).unwrap();
+        file
+    }
+
+    #[test]
+    fn version_1_file_is_migrated_and_saved() {
+        let file = settings_file(
+            r#"{"version": 1, "scanDepth": 4, "debounceMs": 900, "watchedExtensions": ["md", "txt"]}"#,
+        );
+
+        let settings = load(&file).unwrap();
+        assert_eq!(settings.scan_depth, 4);
+        assert_eq!(settings.watch.debounce_ms, 900);
+        assert_eq!(settings.watch.extensions, ["md", "txt"]);
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(saved["version"], SETTINGS_VERSION);
+        assert_eq!(saved["watch"]["debounceMs"], 900);
+        assert!(saved.get("debounceMs").is_none());
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn version_1_file_without_watch_fields_gets_the_defaults() {
+        let file = settings_file(r#"{"version": 1, "staleAfterDays": 30}"#);
+        let settings = load(&file).unwrap();
+        assert_eq!(settings.stale_after_days, 30);
+        assert_eq!(settings.watch, AppSettings::default().watch);
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn missing_scan_root_keeps_the_settings() {
+        let missing = std::env::temp_dir().join(format!("bmad-unmounted-{}", uuid::Uuid::new_v4()));
+        let file = settings_file(&format!(
+            r#"{{"version": 2, "staleAfterDays": 30, "scanRoots": [{}]}}"#,
+            serde_json::to_string(&missing).unwrap()
+        ));
+
+        let settings = load(&file).unwrap();
+        assert_eq!(settings.stale_after_days, 30);
+        assert_eq!(settings.scan_roots, [missing.to_string_lossy()]);
+        assert!(scan_roots(&settings).is_empty());
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn unknown_writable_extensions_are_dropped_on_load() {
+        let file = settings_file(r#"{"version": 2, "writableExtensions": ["txt", "sh", "exe"]}"#);
+        assert_eq!(load(&file).unwrap().writable_extensions, ["txt"]);
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn newer_file_is_refused() {
+        let file = settings_file(r#"{"version": 99}"#);
+        assert!(matches!(load(&file), Err(SettingsError::UnsupportedVersion(99))));
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+}
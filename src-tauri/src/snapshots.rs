@@ -0,0 +1,432 @@
+use crate::models::{BmadProject, ProjectChangesSince, ProjectSnapshot, ProjectStats, StoryStatusChange};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Schema steps; `PRAGMA user_version` records how many have been applied
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE project_cache (
+        project_id TEXT PRIMARY KEY,
+        project_json TEXT NOT NULL,
+        parsed_at TEXT NOT NULL
+    );
+    CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_id TEXT NOT NULL,
+        taken_at TEXT NOT NULL,
+        phase INTEGER NOT NULL,
+        stats_json TEXT NOT NULL,
+        statuses_json TEXT NOT NULL
+    );
+    CREATE INDEX snapshots_by_project ON snapshots (project_id, taken_at);
+"];
+
+static STORE: OnceLock<SnapshotStore> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Invalid snapshot data: {0}")]
+    InvalidData(#[from] serde_json::Error),
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+    #[error("Snapshot store is not available")]
+    Unavailable,
+}
+
+/// SQLite database (`snapshots.db` in the app data directory) holding the
+/// last parsed state of each project, for rendering before the first parse
+/// finishes, and a time series of snapshots taken whenever a project's
+/// story statuses, stats or phase change.
+pub struct SnapshotStore {
+    conn: Mutex<Connection>,
+}
+
+/// Open the database; called once at startup. Without it projects are
+/// simply not cached.
+pub fn init(file: PathBuf) {
+    match SnapshotStore::open(&file) {
+        Ok(store) => {
+            let _ = STORE.set(store);
+        }
+        Err(e) => eprintln!("Failed to open snapshot store {}: {}", file.display(), e),
+    }
+}
+
+pub fn store() -> Result<&'static SnapshotStore, SnapshotError> {
+    STORE.get().ok_or(SnapshotError::Unavailable)
+}
+
+/// Cache a freshly parsed project and snapshot it if it changed.
+///
+/// Does nothing if the store is not set up.
+pub fn record(project_id: &str, project: &BmadProject) -> Result<(), SnapshotError> {
+    match STORE.get() {
+        Some(store) => store.record(project_id, project),
+        None => Ok(()),
+    }
+}
+
+impl SnapshotStore {
+    fn open(file: &PathBuf) -> Result<Self, SnapshotError> {
+        if let Some(dir) = file.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let mut conn = Connection::open(file)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version < MIGRATIONS.len() {
+            let tx = conn.transaction()?;
+            for migration in &MIGRATIONS[version..] {
+                tx.execute_batch(migration)?;
+            }
+            tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+            tx.commit()?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn record(&self, project_id: &str, project: &BmadProject) -> Result<(), SnapshotError> {
+        let now = timestamp(Utc::now());
+        let stats = ProjectStats::from_project(project);
+        let statuses = story_statuses(project);
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO project_cache (project_id, project_json, parsed_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (project_id) DO UPDATE SET project_json = ?2, parsed_at = ?3",
+            params![project_id, serde_json::to_string(project)?, now],
+        )?;
+
+        // Only keep a new point in the series when something changed
+        let unchanged = latest(&tx, project_id)?.is_some_and(|last| {
+            last.phase == project.current_phase
+                && last.story_statuses == statuses
+                && last.stats.total_epics == stats.total_epics
+                && last.stats.completed_epics == stats.completed_epics
+        });
+        if !unchanged {
+            tx.execute(
+                "INSERT INTO snapshots (project_id, taken_at, phase, stats_json, statuses_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    project_id,
+                    now,
+                    project.current_phase,
+                    serde_json::to_string(&stats)?,
+                    serde_json::to_string(&statuses)?,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Last parsed state of every cached project, with the project id applied
+    pub fn cached_projects(&self) -> Result<Vec<BmadProject>, SnapshotError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT project_id, project_json FROM project_cache")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            let (project_id, json) = row?;
+            match serde_json::from_str::<BmadProject>(&json) {
+                Ok(mut project) => {
+                    project.id = project_id;
+                    projects.push(project);
+                }
+                // Written by an older app version with a different model; the next parse replaces it
+                Err(e) => eprintln!("Skipping cached project {}: {}", project_id, e),
+            }
+        }
+        Ok(projects)
+    }
+
+    /// Snapshots of a project, oldest first, optionally only those taken at or after `since`
+    pub fn list(
+        &self,
+        project_id: &str,
+        since: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ProjectSnapshot>, SnapshotError> {
+        let since = since.map(normalize_time).transpose()?.unwrap_or_default();
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM (
+                 SELECT id, project_id, taken_at, phase, stats_json, statuses_json FROM snapshots
+                 WHERE project_id = ?1 AND taken_at >= ?2
+                 ORDER BY taken_at DESC, id DESC LIMIT ?3
+             ) ORDER BY taken_at, id",
+        )?;
+        let rows = stmt.query_map(params![project_id, since, limit], read_row)?;
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            snapshots.push(into_snapshot(row?)?);
+        }
+        Ok(snapshots)
+    }
+
+    /// Story status changes between the last snapshot at or before `since`
+    /// (the first one if the project was added later) and the latest one
+    pub fn changes_since(&self, project_id: &str, since: &str) -> Result<ProjectChangesSince, SnapshotError> {
+        let since = normalize_time(since)?;
+        let conn = self.conn.lock();
+
+        let before = conn
+            .query_row(
+                "SELECT id, project_id, taken_at, phase, stats_json, statuses_json FROM snapshots
+                 WHERE project_id = ?1 AND taken_at <= ?2 ORDER BY taken_at DESC, id DESC LIMIT 1",
+                params![project_id, since],
+                read_row,
+            )
+            .optional()?;
+        let baseline = match before {
+            Some(row) => Some(row),
+            None => conn
+                .query_row(
+                    "SELECT id, project_id, taken_at, phase, stats_json, statuses_json FROM snapshots
+                     WHERE project_id = ?1 ORDER BY taken_at, id LIMIT 1",
+                    params![project_id],
+                    read_row,
+                )
+                .optional()?,
+        }
+        .map(into_snapshot)
+        .transpose()?;
+        let latest = latest(&conn, project_id)?;
+
+        let story_changes = match (&baseline, &latest) {
+            (Some(baseline), Some(latest)) => status_changes(&baseline.story_statuses, &latest.story_statuses),
+            _ => Vec::new(),
+        };
+
+        Ok(ProjectChangesSince {
+            project_id: project_id.to_string(),
+            since,
+            baseline,
+            latest,
+            story_changes,
+        })
+    }
+
+    /// Drop a project's cache and snapshots, e.g. after it was removed
+    pub fn forget(&self, project_id: &str) -> Result<(), SnapshotError> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM project_cache WHERE project_id = ?1", params![project_id])?;
+        conn.execute("DELETE FROM snapshots WHERE project_id = ?1", params![project_id])?;
+        Ok(())
+    }
+}
+
+type SnapshotRow = (i64, String, String, u8, String, String);
+
+fn read_row(row: &rusqlite::Row) -> rusqlite::Result<SnapshotRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+}
+
+fn into_snapshot(row: SnapshotRow) -> Result<ProjectSnapshot, SnapshotError> {
+    let (id, project_id, taken_at, phase, stats_json, statuses_json) = row;
+    Ok(ProjectSnapshot {
+        id,
+        project_id,
+        taken_at,
+        phase,
+        stats: serde_json::from_str(&stats_json)?,
+        story_statuses: serde_json::from_str(&statuses_json)?,
+    })
+}
+
+fn latest(conn: &Connection, project_id: &str) -> Result<Option<ProjectSnapshot>, SnapshotError> {
+    conn.query_row(
+        "SELECT id, project_id, taken_at, phase, stats_json, statuses_json FROM snapshots
+         WHERE project_id = ?1 ORDER BY taken_at DESC, id DESC LIMIT 1",
+        params![project_id],
+        read_row,
+    )
+    .optional()?
+    .map(into_snapshot)
+    .transpose()
+}
+
+fn story_statuses(project: &BmadProject) -> BTreeMap<String, crate::models::StoryStatus> {
+    project
+        .epics
+        .iter()
+        .flat_map(|epic| &epic.stories)
+        .map(|story| (story.number.clone(), story.status.clone()))
+        .collect()
+}
+
+fn status_changes(
+    from: &BTreeMap<String, crate::models::StoryStatus>,
+    to: &BTreeMap<String, crate::models::StoryStatus>,
+) -> Vec<StoryStatusChange> {
+    let numbers: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+    numbers
+        .into_iter()
+        .filter(|number| from.get(*number) != to.get(*number))
+        .map(|number| StoryStatusChange {
+            story_number: number.clone(),
+            from: from.get(number).cloned(),
+            to: to.get(number).cloned(),
+        })
+        .collect()
+}
+
+/// Stored times are UTC RFC 3339 with whole seconds, so they sort as text
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn normalize_time(value: &str) -> Result<String, SnapshotError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| timestamp(time.with_timezone(&Utc)))
+        .map_err(|e| SnapshotError::InvalidTime(format!("{}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicStatus, Story, StoryStatus};
+
+    fn story(number: &str, status: StoryStatus) -> Story {
+        Story {
+            id: number.replace('.', "-"),
+            epic_id: "epic-1".to_string(),
+            number: number.to_string(),
+            title: format!("Story {}", number),
+            user_type: String::new(),
+            capability: String::new(),
+            value_benefit: String::new(),
+            acceptance_criteria: Vec::new(),
+            status,
+            blocked: false,
+            tasks: Vec::new(),
+            file_path: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn project(stories: Vec<Story>) -> BmadProject {
+        BmadProject {
+            id: String::new(),
+            name: "Demo".to_string(),
+            path: "/work/demo".to_string(),
+            bmad_docs_path: "/work/demo/docs".to_string(),
+            description: None,
+            current_phase: 4,
+            epics: vec![Epic {
+                id: "epic-1".to_string(),
+                number: 1,
+                title: "Accounts".to_string(),
+                goal: String::new(),
+                stories,
+                status: EpicStatus::InProgress,
+                retrospective: None,
+                file_path: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            }],
+            documents: Vec::new(),
+            sprint_status: None,
+            last_activity: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn open_store() -> (SnapshotStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("bmad-snapshots-{}", uuid::Uuid::new_v4()));
+        (SnapshotStore::open(&dir.join("snapshots.db")).unwrap(), dir)
+    }
+
+    #[test]
+    fn status_changes_list_added_removed_and_changed_stories() {
+        let from = BTreeMap::from([
+            ("1.1".to_string(), StoryStatus::Done),
+            ("1.2".to_string(), StoryStatus::InProgress),
+            ("1.3".to_string(), StoryStatus::Backlog),
+        ]);
+        let to = BTreeMap::from([
+            ("1.1".to_string(), StoryStatus::Done),
+            ("1.2".to_string(), StoryStatus::Review),
+            ("1.4".to_string(), StoryStatus::Backlog),
+        ]);
+
+        let changes: Vec<(String, Option<StoryStatus>, Option<StoryStatus>)> = status_changes(&from, &to)
+            .into_iter()
+            .map(|c| (c.story_number, c.from, c.to))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("1.2".to_string(), Some(StoryStatus::InProgress), Some(StoryStatus::Review)),
+                ("1.3".to_string(), Some(StoryStatus::Backlog), None),
+                ("1.4".to_string(), None, Some(StoryStatus::Backlog)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_projects_add_no_snapshot() {
+        let (store, dir) = open_store();
+        let first = project(vec![story("1.1", StoryStatus::InProgress)]);
+        store.record("p1", &first).unwrap();
+        store.record("p1", &first).unwrap();
+        assert_eq!(store.list("p1", None, None).unwrap().len(), 1);
+
+        let second = project(vec![story("1.1", StoryStatus::Review)]);
+        store.record("p1", &second).unwrap();
+        let snapshots = store.list("p1", None, None).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].story_statuses["1.1"], StoryStatus::Review);
+        // The newest ones when limited, still oldest first
+        let last = store.list("p1", None, Some(1)).unwrap();
+        assert_eq!(last[0].id, snapshots[1].id);
+
+        let cached = store.cached_projects().unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].id, "p1");
+        assert_eq!(cached[0].epics[0].stories[0].status, StoryStatus::Review);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn changes_since_compare_with_the_snapshot_before() {
+        let (store, dir) = open_store();
+        store.record("p1", &project(vec![story("1.1", StoryStatus::InProgress)])).unwrap();
+        store
+            .record("p1", &project(vec![story("1.1", StoryStatus::Done), story("1.2", StoryStatus::Backlog)]))
+            .unwrap();
+
+        // Before the first snapshot: compared with the first one
+        let changes = store.changes_since("p1", "2000-01-01T00:00:00+02:00").unwrap();
+        assert_eq!(changes.since, "1999-12-31T22:00:00Z");
+        let numbers: Vec<&str> = changes.story_changes.iter().map(|c| c.story_number.as_str()).collect();
+        assert_eq!(numbers, ["1.1", "1.2"]);
+
+        // After the latest one: nothing changed since
+        let changes = store.changes_since("p1", "2999-01-01T00:00:00Z").unwrap();
+        assert!(changes.story_changes.is_empty());
+        assert!(matches!(store.changes_since("p1", "yesterday"), Err(SnapshotError::InvalidTime(_))));
+
+        store.forget("p1").unwrap();
+        assert!(store.list("p1", None, None).unwrap().is_empty());
+        assert!(store.cached_projects().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
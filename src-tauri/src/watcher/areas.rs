@@ -0,0 +1,176 @@
+use crate::parser::{BmadParser, ProjectOverrides, OVERRIDES_FILE};
+use crate::settings;
+use notify::RecursiveMode;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// BMAD core folder holding module configs, agents and workflows
+const BMAD_CORE_DIR: &str = "_bmad";
+
+const WORKFLOW_STATUS_FILE: &str = "bmm-workflow-status.yaml";
+
+/// Part of the project model a file change invalidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModelArea {
+    /// Folder moves and anything else that needs a full reparse
+    #[default]
+    Project,
+    /// Module config.yaml files under `_bmad`, and `.bmad-dash.yaml`
+    Config,
+    WorkflowStatus,
+    SprintStatus,
+    Epics,
+    Stories,
+    Documents,
+    Agents,
+    Workflows,
+}
+
+impl ModelArea {
+    /// Whether the parsed project has to be rebuilt from scratch
+    pub fn needs_full_parse(self) -> bool {
+        matches!(self, ModelArea::Project | ModelArea::Config)
+    }
+
+    /// Whether the parsed project reads this area at all
+    pub fn in_project_model(self) -> bool {
+        !matches!(
+            self,
+            ModelArea::WorkflowStatus | ModelArea::Agents | ModelArea::Workflows
+        )
+    }
+}
+
+/// Every path the parser resolved for a project, used to decide what to
+/// watch and which model area a change belongs to
+#[derive(Debug, Clone)]
+pub struct WatchSet {
+    bmad_core: PathBuf,
+    /// The project's `.bmad-dash.yaml`, whether or not it exists
+    overrides_file: PathBuf,
+    docs_root: Option<PathBuf>,
+    story_dir: Option<PathBuf>,
+    /// Folders, files and story/epic filename patterns from `.bmad-dash.yaml`
+    overrides: ProjectOverrides,
+    workflow_status: Option<PathBuf>,
+    /// The `watch.extensions` setting when the set was resolved
+    extensions: Vec<String>,
+}
+
+impl WatchSet {
+    pub fn resolve(project_path: &Path, docs_root: Option<&Path>) -> Self {
+        let overrides = ProjectOverrides::load(project_path).unwrap_or_default();
+        Self {
+            bmad_core: project_path.join(BMAD_CORE_DIR),
+            overrides_file: project_path.join(OVERRIDES_FILE),
+            docs_root: docs_root.map(Path::to_path_buf),
+            story_dir: docs_root.map(|root| BmadParser::find_story_dir(project_path, root)),
+            overrides,
+            workflow_status: docs_root
+                .and_then(|root| BmadParser::find_workflow_status_file(project_path, root)),
+            extensions: settings::current().watch.extensions,
+        }
+    }
+
+    /// Folders to watch besides the docs root. Single files are watched
+    /// through their folder so editors that replace them are still seen.
+    pub fn extra_watches(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut watches = Vec::new();
+        let mut add = |path: &Path, mode: RecursiveMode| {
+            let covered = self
+                .docs_root
+                .as_ref()
+                .is_some_and(|root| path.starts_with(root))
+                || watches.iter().any(|(watched, watched_mode): &(PathBuf, RecursiveMode)| {
+                    path == watched || (*watched_mode == RecursiveMode::Recursive && path.starts_with(watched))
+                });
+            if path.is_dir() && !covered {
+                watches.push((path.to_path_buf(), mode));
+            }
+        };
+
+        add(&self.bmad_core, RecursiveMode::Recursive);
+        if let Some(story_dir) = &self.story_dir {
+            add(story_dir, RecursiveMode::Recursive);
+        }
+        if let Some(epics_dir) = &self.overrides.epics_dir {
+            add(epics_dir, RecursiveMode::Recursive);
+        }
+        for file in [&self.workflow_status, &self.overrides.sprint_status, &Some(self.overrides_file.clone())] {
+            if let Some(parent) = file.as_deref().and_then(Path::parent) {
+                add(parent, RecursiveMode::NonRecursive);
+            }
+        }
+
+        watches
+    }
+
+    /// Model area for a changed path, or None if the parser never reads it
+    /// or its extension is not a watched one
+    pub fn classify(&self, path: &Path) -> Option<ModelArea> {
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !extension.is_empty() && !self.extensions.contains(&extension) {
+            return None;
+        }
+        if path == self.overrides_file {
+            return Some(ModelArea::Config);
+        }
+        if self.overrides.sprint_status.as_deref().is_some_and(|p| p == path) {
+            return Some(ModelArea::SprintStatus);
+        }
+
+        if let Ok(relative) = path.strip_prefix(&self.bmad_core) {
+            if extension.is_empty() {
+                return None;
+            }
+            let in_folder = |name: &str| relative.components().any(|c| c.as_os_str() == name);
+            return Some(if in_folder("agents") {
+                ModelArea::Agents
+            } else if in_folder("workflows") {
+                ModelArea::Workflows
+            } else if filename == "config.yaml" {
+                ModelArea::Config
+            } else {
+                return None;
+            });
+        }
+
+        if filename == WORKFLOW_STATUS_FILE
+            || self.workflow_status.as_deref().is_some_and(|p| p == path)
+        {
+            return Some(ModelArea::WorkflowStatus);
+        }
+
+        let in_docs = [&self.docs_root, &self.story_dir, &self.overrides.epics_dir]
+            .into_iter()
+            .flatten()
+            .any(|root| path.starts_with(root));
+        if !in_docs {
+            return None;
+        }
+
+        let in_epics_dir = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|name| name == "epics");
+        match extension.as_str() {
+            // Folders have no extension; moving one moves everything inside
+            "" => Some(ModelArea::Project),
+            "yaml" | "yml" if filename.starts_with("sprint-status") => Some(ModelArea::SprintStatus),
+            "md" if self.overrides.story_numbers(filename).is_some() => Some(ModelArea::Stories),
+            "md" if filename == "epics.md"
+                || filename.starts_with("epic-")
+                || self.overrides.is_epic_file(filename)
+                || in_epics_dir =>
+            {
+                Some(ModelArea::Epics)
+            }
+            _ => Some(ModelArea::Documents),
+        }
+    }
+}
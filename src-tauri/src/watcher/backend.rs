@@ -0,0 +1,180 @@
+use super::debounce::Signal;
+use crate::models::WatchOptions;
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Config, ErrorKind, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// Poll interval unless a project sets its own
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Shortest poll interval a project may ask for
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchMode {
+    /// inotify / FSEvents / ReadDirectoryChanges
+    Native,
+    /// Periodic scans with `PollWatcher`
+    Poll,
+}
+
+/// How a project's folders are being watched, as reported to the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatus {
+    pub mode: WatchMode,
+    pub poll_interval_ms: Option<u64>,
+    /// Why native watching was given up, if it was
+    pub fallback_reason: Option<String>,
+}
+
+/// Poll interval for a project's settings, no shorter than `MIN_POLL_INTERVAL`
+fn poll_interval(options: &WatchOptions) -> Duration {
+    options
+        .poll_interval_ms
+        .map(|ms| Duration::from_millis(ms).max(MIN_POLL_INTERVAL))
+        .unwrap_or(DEFAULT_POLL_INTERVAL)
+}
+
+/// A project's notify watcher, shared with its debounce thread.
+///
+/// Keeps its own sender so a failing native watcher can be swapped for a
+/// `PollWatcher` feeding the same thread. Dropping it drops every sender,
+/// which ends the thread.
+pub struct ProjectWatcher {
+    watcher: Box<dyn Watcher + Send>,
+    tx: Sender<Signal>,
+    options: WatchOptions,
+    status: WatchStatus,
+}
+
+impl ProjectWatcher {
+    /// Native watcher unless polling is forced or the native one cannot be created
+    pub fn start(tx: Sender<Signal>, options: WatchOptions) -> Result<Self, String> {
+        if options.force_poll {
+            return Self::polling(tx, options, None);
+        }
+
+        match RecommendedWatcher::new(event_handler(tx.clone()), Config::default()) {
+            Ok(watcher) => Ok(Self {
+                watcher: Box::new(watcher),
+                tx,
+                options,
+                status: WatchStatus {
+                    mode: WatchMode::Native,
+                    poll_interval_ms: None,
+                    fallback_reason: None,
+                },
+            }),
+            Err(e) => Self::polling(tx, options, Some(e.to_string())),
+        }
+    }
+
+    fn polling(
+        tx: Sender<Signal>,
+        options: WatchOptions,
+        fallback_reason: Option<String>,
+    ) -> Result<Self, String> {
+        let config = Config::default().with_poll_interval(poll_interval(&options));
+        let watcher = PollWatcher::new(event_handler(tx.clone()), config)
+            .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+        Ok(Self {
+            watcher: Box::new(watcher),
+            tx,
+            options,
+            status: WatchStatus {
+                mode: WatchMode::Poll,
+                poll_interval_ms: Some(poll_interval(&options).as_millis() as u64),
+                fallback_reason,
+            },
+        })
+    }
+
+    pub fn status(&self) -> &WatchStatus {
+        &self.status
+    }
+
+    /// Replace a native watcher that hit `error` with a polling one.
+    ///
+    /// Returns false if the error is not one polling can work around. Every
+    /// path has to be watched again afterwards.
+    pub fn fall_back(&mut self, error: &notify::Error) -> Result<bool, String> {
+        if self.status.mode != WatchMode::Native || !needs_polling(error) {
+            return Ok(false);
+        }
+        *self = Self::polling(self.tx.clone(), self.options, Some(error.to_string()))?;
+        Ok(true)
+    }
+
+    pub fn watch(&mut self, path: &Path, mode: RecursiveMode) -> notify::Result<()> {
+        self.watcher.watch(path, mode)
+    }
+
+    pub fn unwatch(&mut self, path: &Path) {
+        // The watch is already gone if the folder was deleted
+        let _ = self.watcher.unwatch(path);
+    }
+}
+
+/// Native watching failures that polling avoids: exhausted inotify watches
+/// (ENOSPC) and backend-specific breakage
+pub fn needs_polling(error: &notify::Error) -> bool {
+    match &error.kind {
+        ErrorKind::MaxFilesWatch | ErrorKind::Generic(_) => true,
+        ErrorKind::Io(e) => e.kind() == std::io::ErrorKind::Unsupported,
+        _ => false,
+    }
+}
+
+fn event_handler(tx: Sender<Signal>) -> impl FnMut(notify::Result<Event>) + Send + 'static {
+    move |res| match res {
+        Ok(event) => {
+            for (path, kind) in event_changes(event) {
+                let _ = tx.send(Signal::Change(path, kind));
+            }
+        }
+        Err(e) => {
+            let _ = tx.send(Signal::Error(e));
+        }
+    }
+}
+
+/// Paths and change kinds from a notify event; renames are reported as a
+/// `rename-from` / `rename-to` pair
+fn event_changes(event: Event) -> Vec<(PathBuf, &'static str)> {
+    let kind = match event.kind {
+        EventKind::Create(_) => "create",
+        EventKind::Remove(_) => "remove",
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => "rename-from",
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => "rename-to",
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            return paths
+                .next()
+                .map(|from| (from, "rename-from"))
+                .into_iter()
+                .chain(paths.next().map(|to| (to, "rename-to")))
+                .collect();
+        }
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            // Backends that cannot pair renames report each side on its own
+            return event
+                .paths
+                .into_iter()
+                .map(|path| {
+                    let kind = if path.exists() { "rename-to" } else { "rename-from" };
+                    (path, kind)
+                })
+                .collect();
+        }
+        EventKind::Modify(_) => "modify",
+        _ => return Vec::new(),
+    };
+
+    event.paths.into_iter().map(|path| (path, kind)).collect()
+}
@@ -0,0 +1,194 @@
+use super::areas::ModelArea;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Who made a change
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeOrigin {
+    /// The app's own write, seen again by the watcher
+    App,
+    /// An editor, agent or anything else outside the app
+    #[default]
+    External,
+}
+
+/// Every kind of change seen for one path during a batch, in arrival order
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    pub kinds: Vec<String>,
+    /// Filled in by the watcher before the batch is emitted
+    pub area: ModelArea,
+    pub origin: ChangeOrigin,
+}
+
+/// What a watcher's event handler sends to its debounce thread
+#[derive(Debug)]
+pub enum Signal {
+    Change(PathBuf, &'static str),
+    Error(notify::Error),
+}
+
+/// Changes collected until the folder goes quiet
+#[derive(Debug, Default)]
+pub struct Batch {
+    changes: Vec<FileChange>,
+    errors: Vec<notify::Error>,
+}
+
+impl Batch {
+    pub fn add(&mut self, path: &Path, kind: &str) {
+        let path = path.to_string_lossy().to_string();
+        let change = match self.changes.iter_mut().find(|c| c.path == path) {
+            Some(change) => change,
+            None => {
+                self.changes.push(FileChange {
+                    path,
+                    kinds: Vec::new(),
+                    area: ModelArea::default(),
+                    origin: ChangeOrigin::default(),
+                });
+                self.changes.last_mut().unwrap()
+            }
+        };
+        if !change.kinds.iter().any(|k| k == kind) {
+            change.kinds.push(kind.to_string());
+        }
+    }
+
+    pub fn add_signal(&mut self, signal: Signal) {
+        match signal {
+            Signal::Change(path, kind) => self.add(&path, kind),
+            Signal::Error(e) => self.errors.push(e),
+        }
+    }
+
+    pub fn into_parts(self) -> (Vec<FileChange>, Vec<notify::Error>) {
+        (self.changes, self.errors)
+    }
+}
+
+/// Trailing-edge debounce loop, run on its own thread per watcher.
+///
+/// The first change opens a batch; it is flushed once no change has arrived
+/// for `quiet`, or after `max_delay` during a continuous burst, so the last
+/// change is always delivered. Watch errors travel with the batch.
+/// Returns when every sender (the watcher) is dropped.
+pub fn run(
+    rx: Receiver<Signal>,
+    quiet: Duration,
+    max_delay: Duration,
+    mut flush: impl FnMut(Vec<FileChange>, Vec<notify::Error>),
+) {
+    while let Ok(signal) = rx.recv() {
+        let mut batch = Batch::default();
+        batch.add_signal(signal);
+        let opened = Instant::now();
+
+        let disconnected = loop {
+            let remaining = max_delay.saturating_sub(opened.elapsed());
+            if remaining.is_zero() {
+                break false;
+            }
+            match rx.recv_timeout(quiet.min(remaining)) {
+                Ok(signal) => batch.add_signal(signal),
+                Err(RecvTimeoutError::Timeout) => break false,
+                Err(RecvTimeoutError::Disconnected) => break true,
+            }
+        };
+
+        let (changes, errors) = batch.into_parts();
+        flush(changes, errors);
+        if disconnected {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn change(path: &str, kind: &'static str) -> Signal {
+        Signal::Change(PathBuf::from(path), kind)
+    }
+
+    fn paths(changes: &[FileChange]) -> Vec<&str> {
+        changes.iter().map(|c| c.path.as_str()).collect()
+    }
+
+    #[test]
+    fn batch_merges_kinds_per_path_in_order() {
+        let mut batch = Batch::default();
+        batch.add(Path::new("a.md"), "create");
+        batch.add(Path::new("b.md"), "modify");
+        batch.add(Path::new("a.md"), "modify");
+        batch.add(Path::new("a.md"), "create");
+
+        let (changes, errors) = batch.into_parts();
+        assert_eq!(paths(&changes), ["a.md", "b.md"]);
+        assert_eq!(changes[0].kinds, ["create", "modify"]);
+        assert_eq!(changes[1].kinds, ["modify"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn burst_is_flushed_once_after_quiet() {
+        let (tx, rx) = mpsc::channel();
+        for path in ["a.md", "b.md", "a.md"] {
+            tx.send(change(path, "modify")).unwrap();
+        }
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            tx.send(change("c.md", "create")).unwrap();
+        });
+
+        let mut batches = Vec::new();
+        run(rx, Duration::from_millis(50), Duration::from_secs(5), |changes, _| {
+            batches.push(paths(&changes).join(","));
+        });
+        sender.join().unwrap();
+
+        assert_eq!(batches, ["a.md,b.md", "c.md"]);
+    }
+
+    #[test]
+    fn continuous_burst_is_flushed_at_max_delay() {
+        let (tx, rx) = mpsc::channel();
+        let sender = thread::spawn(move || {
+            for i in 0..30 {
+                tx.send(change(&format!("{}.md", i), "modify")).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        let mut batches = Vec::new();
+        run(rx, Duration::from_millis(100), Duration::from_millis(100), |changes, _| {
+            batches.push(changes.len());
+        });
+        sender.join().unwrap();
+
+        // Nothing is lost, and the burst did not wait for a quiet spell
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().sum::<usize>(), 30);
+    }
+
+    #[test]
+    fn pending_changes_are_flushed_when_the_watcher_stops() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(change("a.md", "remove")).unwrap();
+        drop(tx);
+
+        let mut batches = Vec::new();
+        run(rx, Duration::from_secs(5), Duration::from_secs(5), |changes, _| {
+            batches.push(paths(&changes).join(","));
+        });
+        assert_eq!(batches, ["a.md"]);
+    }
+}
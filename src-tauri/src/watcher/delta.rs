@@ -0,0 +1,240 @@
+use crate::models::{BmadProject, Epic, ProjectChange, Story, Task};
+use std::collections::BTreeMap;
+
+/// Semantic differences between two parses of the same project.
+///
+/// Epics are matched by number, stories by "N.M", tasks by id and
+/// documents by path, since parsed ids are regenerated on a full parse.
+pub fn diff_projects(old: &BmadProject, new: &BmadProject) -> Vec<ProjectChange> {
+    let mut changes = Vec::new();
+
+    let old_epics: BTreeMap<u32, &Epic> = old.epics.iter().map(|e| (e.number, e)).collect();
+    let new_epics: BTreeMap<u32, &Epic> = new.epics.iter().map(|e| (e.number, e)).collect();
+
+    for (number, epic) in &new_epics {
+        match old_epics.get(number) {
+            None => changes.push(ProjectChange::EpicAdded {
+                epic_number: *number,
+                title: epic.title.clone(),
+            }),
+            Some(previous) => {
+                if previous.status != epic.status {
+                    changes.push(ProjectChange::EpicStatusChanged {
+                        epic_number: *number,
+                        from: previous.status.clone(),
+                        to: epic.status.clone(),
+                    });
+                }
+                if previous.title != epic.title
+                    || previous.goal != epic.goal
+                    || previous.retrospective != epic.retrospective
+                {
+                    changes.push(ProjectChange::EpicUpdated { epic_number: *number });
+                }
+            }
+        }
+    }
+    for number in old_epics.keys().filter(|n| !new_epics.contains_key(n)) {
+        changes.push(ProjectChange::EpicRemoved { epic_number: *number });
+    }
+
+    diff_stories(old, new, &mut changes);
+    diff_documents(old, new, &mut changes);
+    changes
+}
+
+fn diff_stories(old: &BmadProject, new: &BmadProject, changes: &mut Vec<ProjectChange>) {
+    let stories = |project: &BmadProject| -> BTreeMap<String, Story> {
+        project
+            .epics
+            .iter()
+            .flat_map(|e| e.stories.iter())
+            .map(|s| (s.number.clone(), s.clone()))
+            .collect()
+    };
+    let old_stories = stories(old);
+    let new_stories = stories(new);
+
+    for (number, story) in &new_stories {
+        let previous = match old_stories.get(number) {
+            Some(previous) => previous,
+            None => {
+                changes.push(ProjectChange::StoryAdded {
+                    story_number: number.clone(),
+                    title: story.title.clone(),
+                    status: story.status.clone(),
+                });
+                continue;
+            }
+        };
+
+        if previous.status != story.status {
+            changes.push(ProjectChange::StoryStatusChanged {
+                story_number: number.clone(),
+                from: previous.status.clone(),
+                to: story.status.clone(),
+            });
+        }
+
+        let details_changed = previous.title != story.title
+            || previous.user_type != story.user_type
+            || previous.capability != story.capability
+            || previous.value_benefit != story.value_benefit
+            || previous.acceptance_criteria.len() != story.acceptance_criteria.len()
+            || previous.file_path != story.file_path;
+        if details_changed {
+            changes.push(ProjectChange::StoryUpdated {
+                story_number: number.clone(),
+            });
+        }
+
+        diff_tasks(number, &previous.tasks, &story.tasks, changes);
+    }
+
+    for number in old_stories.keys().filter(|n| !new_stories.contains_key(*n)) {
+        changes.push(ProjectChange::StoryRemoved {
+            story_number: number.clone(),
+        });
+    }
+}
+
+fn diff_tasks(story_number: &str, old: &[Task], new: &[Task], changes: &mut Vec<ProjectChange>) {
+    fn flatten<'a>(tasks: &'a [Task], out: &mut Vec<&'a Task>) {
+        for task in tasks {
+            out.push(task);
+            flatten(&task.subtasks, out);
+        }
+    }
+    let (mut old_tasks, mut new_tasks) = (Vec::new(), Vec::new());
+    flatten(old, &mut old_tasks);
+    flatten(new, &mut new_tasks);
+
+    for task in &new_tasks {
+        match old_tasks.iter().find(|t| t.id == task.id) {
+            None => changes.push(ProjectChange::TaskAdded {
+                story_number: story_number.to_string(),
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+            }),
+            Some(previous) if previous.completed != task.completed => {
+                changes.push(ProjectChange::TaskToggled {
+                    story_number: story_number.to_string(),
+                    task_id: task.id.clone(),
+                    completed: task.completed,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for task in old_tasks.iter().filter(|t| !new_tasks.iter().any(|n| n.id == t.id)) {
+        changes.push(ProjectChange::TaskRemoved {
+            story_number: story_number.to_string(),
+            task_id: task.id.clone(),
+        });
+    }
+}
+
+fn diff_documents(old: &BmadProject, new: &BmadProject, changes: &mut Vec<ProjectChange>) {
+    for document in &new.documents {
+        match old.documents.iter().find(|d| d.file_path == document.file_path) {
+            None => changes.push(ProjectChange::DocumentAdded {
+                file_path: document.file_path.clone(),
+                doc_type: document.doc_type.clone(),
+                title: document.title.clone(),
+            }),
+            Some(previous) if previous.content != document.content || previous.title != document.title => {
+                changes.push(ProjectChange::DocumentUpdated {
+                    file_path: document.file_path.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for document in &old.documents {
+        if !new.documents.iter().any(|d| d.file_path == document.file_path) {
+            changes.push(ProjectChange::DocumentRemoved {
+                file_path: document.file_path.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::BmadParser;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    const STORY: &str = "\
+# Story 1.1: Setup
+
+## Tasks / Subtasks
+
+- [ ] Create the repository
+- [ ] Add CI
+";
+
+    fn write_project(project: &Path) -> PathBuf {
+        let docs = project.join("docs");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(docs.join("epics.md"), "## Epic 1: Accounts\n\n### Story 1.1: Setup\n").unwrap();
+        fs::write(
+            docs.join("sprint-status.yaml"),
+            "development_status:\n  epic-1: in-progress\n  1-1-setup: ready-for-dev\n",
+        )
+        .unwrap();
+        fs::write(docs.join("1-1-setup.md"), STORY).unwrap();
+        docs
+    }
+
+    #[test]
+    fn story_file_edits_are_reparsed_in_place() {
+        let project = std::env::temp_dir().join(format!("bmad-delta-story-{}", uuid::Uuid::new_v4()));
+        let docs = write_project(&project);
+        let before = BmadParser::parse_project(&project, Some(&docs)).unwrap();
+
+        let story_file = docs.join("1-1-setup.md");
+        fs::write(&story_file, STORY.replace("- [ ] Create", "- [x] Create")).unwrap();
+        let after = BmadParser::reparse_changed(&before, &[story_file]).unwrap();
+
+        let changes = diff_projects(&before, &after);
+        assert!(matches!(
+            changes.as_slice(),
+            [ProjectChange::TaskToggled { story_number, completed: true, .. }] if story_number == "1.1"
+        ), "{:?}", changes);
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn sprint_status_edits_change_story_and_epic_status() {
+        let project = std::env::temp_dir().join(format!("bmad-delta-sprint-{}", uuid::Uuid::new_v4()));
+        let docs = write_project(&project);
+        let before = BmadParser::parse_project(&project, Some(&docs)).unwrap();
+
+        let sprint_status = docs.join("sprint-status.yaml");
+        fs::write(&sprint_status, "development_status:\n  epic-1: done\n  1-1-setup: done\n").unwrap();
+        let mut after = BmadParser::reparse_changed(&before, &[sprint_status]).unwrap();
+        after.id = before.id.clone();
+
+        let changes = diff_projects(&before, &after);
+        assert!(changes.iter().any(|c| matches!(
+            c,
+            ProjectChange::StoryStatusChanged { story_number, .. } if story_number == "1.1"
+        )), "{:?}", changes);
+        assert!(changes.iter().any(|c| matches!(c, ProjectChange::EpicStatusChanged { epic_number: 1, .. })));
+        assert!(!changes.iter().any(|c| matches!(c, ProjectChange::TaskToggled { .. })));
+        fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    fn unchanged_projects_have_no_changes() {
+        let project = std::env::temp_dir().join(format!("bmad-delta-same-{}", uuid::Uuid::new_v4()));
+        let docs = write_project(&project);
+        let parsed = BmadParser::parse_project(&project, Some(&docs)).unwrap();
+        let reparsed = BmadParser::parse_project(&project, Some(&docs)).unwrap();
+
+        assert!(diff_projects(&parsed, &reparsed).is_empty());
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
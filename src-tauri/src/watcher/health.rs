@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// Bookkeeping a project's watcher thread keeps for `list_project_watches`
+#[derive(Debug, Clone)]
+pub struct Health {
+    pub started_at: DateTime<Utc>,
+    /// Docs folder being watched; `None` while waiting for it to appear
+    pub docs_path: Option<PathBuf>,
+    pub watched_paths: Vec<PathBuf>,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub event_count: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub error_count: u64,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+            docs_path: None,
+            watched_paths: Vec::new(),
+            last_event_at: None,
+            event_count: 0,
+            last_error: None,
+            last_error_at: None,
+            error_count: 0,
+        }
+    }
+}
+
+impl Health {
+    pub fn set_watched(&mut self, docs_path: Option<&Path>, watched_paths: Vec<PathBuf>) {
+        self.docs_path = docs_path.map(Path::to_path_buf);
+        self.watched_paths = watched_paths;
+    }
+
+    pub fn record_events(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        self.event_count += count as u64;
+        self.last_event_at = Some(Utc::now());
+    }
+
+    pub fn record_error(&mut self, message: &str) {
+        self.error_count += 1;
+        self.last_error = Some(message.to_string());
+        self.last_error_at = Some(Utc::now());
+    }
+
+    /// Watching the docs folder with no error since the last event
+    pub fn is_live(&self) -> bool {
+        self.docs_path.is_some()
+            && match (&self.last_error_at, &self.last_event_at) {
+                (None, _) => true,
+                (Some(error_at), Some(event_at)) => event_at > error_at,
+                (Some(_), None) => false,
+            }
+    }
+}
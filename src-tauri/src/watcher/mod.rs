@@ -0,0 +1,425 @@
+mod areas;
+mod backend;
+mod debounce;
+mod delta;
+mod health;
+mod root;
+
+use crate::models::{BmadProject, ProjectChange, WatchOptions};
+use crate::parser::BmadParser;
+use crate::settings;
+use crate::snapshots;
+use crate::writer::echo;
+use backend::ProjectWatcher;
+use debounce::{ChangeOrigin, Signal};
+use health::Health;
+use parking_lot::Mutex;
+use root::WatchRoot;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+pub use backend::WatchStatus;
+pub use debounce::FileChange;
+
+/// Longest a batch is held back while changes keep arriving
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(3);
+
+/// File watcher state
+#[derive(Default)]
+pub struct FileWatcherState {
+    watchers: HashMap<String, ActiveWatch>,
+}
+
+/// A running project watcher and what it was started with
+struct ActiveWatch {
+    watcher: Arc<Mutex<ProjectWatcher>>,
+    health: Arc<Mutex<Health>>,
+    project_path: PathBuf,
+    bmad_docs_path: Option<PathBuf>,
+    options: WatchOptions,
+}
+
+/// A running watcher as listed by `list_project_watches`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherInfo {
+    pub project_id: String,
+    pub project_path: String,
+    /// Docs folder being watched; `None` while waiting for it to appear
+    pub docs_path: Option<String>,
+    pub watched_paths: Vec<String>,
+    #[serde(flatten)]
+    pub status: WatchStatus,
+    pub started_at: String,
+    pub last_event_at: Option<String>,
+    pub event_count: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+    pub error_count: u64,
+    /// Watching the docs folder with no error since the last event
+    pub live: bool,
+}
+
+/// Payload for watch error events
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchErrorPayload {
+    pub project_id: String,
+    pub message: String,
+    /// The watcher worked around the error, e.g. by falling back to polling
+    pub recovered: bool,
+}
+
+/// Payload for watch mode events, sent when a watcher starts or falls back to polling
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchModePayload {
+    pub project_id: String,
+    #[serde(flatten)]
+    pub status: WatchStatus,
+}
+
+/// Payload for file change events: every path changed since the last event
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangePayload {
+    pub project_id: String,
+    pub changes: Vec<FileChange>,
+}
+
+/// Payload for project update events: what changed, and the project after the change
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectUpdatePayload {
+    pub project_id: String,
+    pub changes: Vec<ProjectChange>,
+    pub project: BmadProject,
+    /// `app` only if every file behind the update was written by the app itself
+    pub origin: ChangeOrigin,
+}
+
+/// Start watching a project's bmad-docs folder, plus the `_bmad` config,
+/// story folder and workflow status the parser resolved from it.
+///
+/// Each changed path is tagged with the model area it invalidates and with
+/// whether it is an echo of the app's own write. Besides
+/// the raw `bmad-file-change` batches, the watcher keeps its own
+/// parse of the project, re-reads the changed files and emits
+/// `bmad-project-updated` with the semantic changes. Without `bmad_docs_path`
+/// the folder is detected like `parse_project` does; if it does not exist
+/// (yet, or any more) the watcher waits for it to appear.
+///
+/// Native watching falls back to polling when it cannot be set up or runs
+/// out of watches; `bmad-watch-mode` reports which one is in use. Other
+/// watch, re-arm and reparse failures are sent as `bmad-watch-error`.
+pub fn start_watching(
+    app: AppHandle,
+    state: Arc<Mutex<FileWatcherState>>,
+    project_id: String,
+    project_path: PathBuf,
+    bmad_docs_path: Option<PathBuf>,
+    options: WatchOptions,
+) -> Result<WatchStatus, String> {
+    let mut state_guard = state.lock();
+
+    // Stop existing watcher for this project if any
+    state_guard.watchers.remove(&project_id);
+
+    // Changes go to a debounce thread, which exits once the watcher is dropped
+    let (tx, rx) = mpsc::channel::<Signal>();
+    let watcher = Arc::new(Mutex::new(ProjectWatcher::start(tx, options)?));
+    let health = Arc::new(Mutex::new(Health::default()));
+
+    let mut root = WatchRoot::new(
+        Arc::downgrade(&watcher),
+        project_path.clone(),
+        bmad_docs_path.clone(),
+    );
+    arm(&watcher, &mut root, Vec::new(), &mut |message, recovered| {
+        report_error(&app, &health, &project_id, message, recovered)
+    })?;
+    health.lock().set_watched(root.root(), root.watched_paths());
+
+    let status = watcher.lock().status().clone();
+    let _ = app.emit(
+        "bmad-watch-mode",
+        WatchModePayload {
+            project_id: project_id.clone(),
+            status: status.clone(),
+        },
+    );
+
+    // Quiet period after the last change before a batch is emitted
+    let quiet = Duration::from_millis(settings::current().watch.debounce_ms);
+    let weak_watcher = Arc::downgrade(&watcher);
+    let thread_health = health.clone();
+    let thread_project_path = project_path.clone();
+    let project_id_clone = project_id.clone();
+    std::thread::spawn(move || {
+        let (health, project_path) = (thread_health, thread_project_path);
+        let report = |message: String, recovered: bool| {
+            report_error(&app, &health, &project_id_clone, message, recovered)
+        };
+        let parse = |root: &WatchRoot| {
+            BmadParser::parse_project(&project_path, root.root())
+        };
+        let snapshot = |project: &BmadProject| {
+            if let Err(e) = snapshots::record(&project_id_clone, project) {
+                eprintln!("Failed to snapshot project {}: {}", project_id_clone, e);
+            }
+        };
+        let mut project = root.root().and_then(|_| parse(&root).ok());
+        if let Some(project) = &project {
+            snapshot(project);
+        }
+
+        debounce::run(rx, quiet, MAX_BATCH_DELAY, |changes, errors| {
+            let watcher = match weak_watcher.upgrade() {
+                Some(watcher) => watcher,
+                None => return,
+            };
+            health.lock().record_events(changes.len());
+
+            // Re-arm when the docs folder was removed, renamed away or has just appeared
+            let root_lost = root.root().is_some_and(|path| {
+                !path.is_dir()
+                    || changes.iter().any(|c| {
+                        Path::new(&c.path) == path
+                            && c.kinds.iter().any(|k| k == "remove" || k == "rename-from")
+                    })
+            });
+            if root_lost {
+                root.disarm();
+            }
+            let (rearmed, fell_back) = match arm(&watcher, &mut root, errors, &mut |message, recovered| {
+                report(message, recovered)
+            }) {
+                Ok(armed) => armed,
+                Err(e) => {
+                    report(
+                        format!("Failed to re-arm watcher for {}: {}", project_path.display(), e),
+                        false,
+                    );
+                    (false, false)
+                }
+            };
+            health.lock().set_watched(root.root(), root.watched_paths());
+            if fell_back {
+                let payload = WatchModePayload {
+                    project_id: project_id_clone.clone(),
+                    status: watcher.lock().status().clone(),
+                };
+                let _ = app.emit("bmad-watch-mode", payload);
+            }
+            // Events may have been lost while switching watchers
+            let rearmed = rearmed || fell_back;
+
+            if root.root().is_none() {
+                return;
+            }
+            let changes: Vec<FileChange> = changes
+                .into_iter()
+                .filter_map(|mut c| {
+                    let path = Path::new(&c.path);
+                    c.area = root.watch_set().classify(path)?;
+                    if echo::is_own_change(path) {
+                        c.origin = ChangeOrigin::App;
+                    }
+                    Some(c)
+                })
+                .collect();
+            if changes.is_empty() && !rearmed {
+                return;
+            }
+
+            let full_parse = rearmed || changes.iter().any(|c| c.area.needs_full_parse());
+            let model_changes: Vec<&FileChange> =
+                changes.iter().filter(|c| c.area.in_project_model()).collect();
+            let origin = if !rearmed
+                && !model_changes.is_empty()
+                && model_changes.iter().all(|c| c.origin == ChangeOrigin::App)
+            {
+                ChangeOrigin::App
+            } else {
+                ChangeOrigin::External
+            };
+            let paths: Vec<PathBuf> = model_changes
+                .iter()
+                .map(|c| PathBuf::from(&c.path))
+                .collect();
+            let payload = FileChangePayload {
+                project_id: project_id_clone.clone(),
+                changes,
+            };
+            let _ = app.emit("bmad-file-change", payload);
+            if paths.is_empty() && !full_parse && project.is_some() {
+                return;
+            }
+
+            let updated = match &project {
+                Some(previous) if !full_parse => BmadParser::reparse_changed(previous, &paths),
+                _ => parse(&root),
+            };
+            let mut updated = match updated {
+                Ok(updated) => updated,
+                Err(e) => {
+                    report(format!("Failed to reparse {}: {}", project_path.display(), e), false);
+                    return;
+                }
+            };
+
+            let changes = match &project {
+                Some(previous) => {
+                    updated.id = previous.id.clone();
+                    delta::diff_projects(previous, &updated)
+                }
+                None => Vec::new(),
+            };
+            if !changes.is_empty() || project.is_none() {
+                let payload = ProjectUpdatePayload {
+                    project_id: project_id_clone.clone(),
+                    changes,
+                    project: updated.clone(),
+                    origin,
+                };
+                let _ = app.emit("bmad-project-updated", payload);
+                snapshot(&updated);
+            }
+            project = Some(updated);
+        });
+    });
+
+    state_guard.watchers.insert(
+        project_id,
+        ActiveWatch {
+            watcher,
+            health,
+            project_path,
+            bmad_docs_path,
+            options,
+        },
+    );
+
+    Ok(status)
+}
+
+/// Stop and start a project's watcher again with the settings it was started with
+pub fn restart_watching(
+    app: AppHandle,
+    state: Arc<Mutex<FileWatcherState>>,
+    project_id: String,
+) -> Result<WatchStatus, String> {
+    let (project_path, bmad_docs_path, options) = {
+        let state_guard = state.lock();
+        let active = state_guard
+            .watchers
+            .get(&project_id)
+            .ok_or_else(|| format!("Project {} is not being watched", project_id))?;
+        (
+            active.project_path.clone(),
+            active.bmad_docs_path.clone(),
+            active.options,
+        )
+    };
+    start_watching(app, state, project_id, project_path, bmad_docs_path, options)
+}
+
+/// Every running watcher with its paths, mode and health
+pub fn list_watchers(state: Arc<Mutex<FileWatcherState>>) -> Vec<WatcherInfo> {
+    let state_guard = state.lock();
+    let mut watchers: Vec<WatcherInfo> = state_guard
+        .watchers
+        .iter()
+        .map(|(project_id, active)| {
+            let health = active.health.lock();
+            let path_string = |path: &Path| path.to_string_lossy().to_string();
+            WatcherInfo {
+                project_id: project_id.clone(),
+                project_path: path_string(&active.project_path),
+                docs_path: health.docs_path.as_deref().map(path_string),
+                watched_paths: health.watched_paths.iter().map(|p| path_string(p)).collect(),
+                status: active.watcher.lock().status().clone(),
+                started_at: health.started_at.to_rfc3339(),
+                last_event_at: health.last_event_at.map(|t| t.to_rfc3339()),
+                event_count: health.event_count,
+                last_error: health.last_error.clone(),
+                last_error_at: health.last_error_at.map(|t| t.to_rfc3339()),
+                error_count: health.error_count,
+                live: health.is_live(),
+            }
+        })
+        .collect();
+    watchers.sort_by(|a, b| a.project_path.cmp(&b.project_path));
+    watchers
+}
+
+/// Log a watcher problem, keep it for `list_watchers` and send `bmad-watch-error`
+fn report_error(
+    app: &AppHandle,
+    health: &Mutex<Health>,
+    project_id: &str,
+    message: String,
+    recovered: bool,
+) {
+    eprintln!("Watch error for {}: {}", project_id, message);
+    if !recovered {
+        health.lock().record_error(&message);
+    }
+    let payload = WatchErrorPayload {
+        project_id: project_id.to_string(),
+        message,
+        recovered,
+    };
+    let _ = app.emit("bmad-watch-error", payload);
+}
+
+/// Arm the watch root, first switching to polling if native watching
+/// reported `errors` it cannot recover from. Every error goes to `report`
+/// along with whether falling back worked around it.
+///
+/// Returns whether the docs folder was (re)armed and whether the watcher
+/// fell back to polling.
+fn arm(
+    watcher: &Mutex<ProjectWatcher>,
+    root: &mut WatchRoot,
+    errors: Vec<notify::Error>,
+    report: &mut dyn FnMut(String, bool),
+) -> Result<(bool, bool), String> {
+    let mut fell_back = false;
+    for error in errors {
+        let recovered = watcher.lock().fall_back(&error)?;
+        fell_back |= recovered;
+        report(error.to_string(), recovered);
+    }
+    if fell_back {
+        root.reset();
+    }
+
+    match root.arm() {
+        Ok(rearmed) => Ok((rearmed, fell_back)),
+        Err(e) if watcher.lock().fall_back(&e)? => {
+            report(e.to_string(), true);
+            root.reset();
+            let rearmed = root
+                .arm()
+                .map_err(|e| format!("Failed to watch path: {}", e))?;
+            Ok((rearmed, true))
+        }
+        Err(e) => Err(format!("Failed to watch path: {}", e)),
+    }
+}
+
+/// Stop watching a project
+pub fn stop_watching(state: Arc<Mutex<FileWatcherState>>, project_id: &str) {
+    let mut state_guard = state.lock();
+    state_guard.watchers.remove(project_id);
+}
+
+/// Stop all watchers
+pub fn stop_all(state: Arc<Mutex<FileWatcherState>>) {
+    let mut state_guard = state.lock();
+    state_guard.watchers.clear();
+}
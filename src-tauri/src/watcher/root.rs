@@ -0,0 +1,170 @@
+use super::areas::WatchSet;
+use super::backend::{needs_polling, ProjectWatcher};
+use crate::parser::BmadParser;
+use notify::RecursiveMode;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
+
+/// Where a project's watcher is currently listening
+#[derive(Debug, Clone, PartialEq)]
+enum Armed {
+    /// Recursive watch on the docs folder
+    Root(PathBuf),
+    /// Non-recursive watch on the closest existing folder, waiting for the docs folder to appear
+    Waiting(PathBuf),
+}
+
+/// Keeps a project's watcher pointed at its docs folder and the other
+/// paths the parser resolved (see `WatchSet`).
+///
+/// If the folder is missing (not created yet, or deleted by a branch switch)
+/// the watcher falls back to the closest existing parent and moves back
+/// once the folder shows up again.
+pub struct WatchRoot {
+    watcher: Weak<Mutex<ProjectWatcher>>,
+    project_path: PathBuf,
+    /// Docs folder chosen by the user; otherwise detected like `parse_project` does
+    docs_path: Option<PathBuf>,
+    armed: Option<Armed>,
+    set: WatchSet,
+    /// Watched folders outside the docs folder
+    extras: Vec<(PathBuf, RecursiveMode)>,
+}
+
+impl WatchRoot {
+    pub fn new(
+        watcher: Weak<Mutex<ProjectWatcher>>,
+        project_path: PathBuf,
+        docs_path: Option<PathBuf>,
+    ) -> Self {
+        let set = WatchSet::resolve(&project_path, None);
+        Self {
+            watcher,
+            project_path,
+            docs_path,
+            armed: None,
+            set,
+            extras: Vec::new(),
+        }
+    }
+
+    /// Paths resolved on the last `arm`
+    pub fn watch_set(&self) -> &WatchSet {
+        &self.set
+    }
+
+    /// Every folder currently watched, the docs folder (or the folder
+    /// waiting for it) first
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        self.armed
+            .iter()
+            .map(|armed| match armed {
+                Armed::Root(path) | Armed::Waiting(path) => path.clone(),
+            })
+            .chain(self.extras.iter().map(|(path, _)| path.clone()))
+            .collect()
+    }
+
+    /// The docs folder being watched, if it exists
+    pub fn root(&self) -> Option<&Path> {
+        match &self.armed {
+            Some(Armed::Root(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Watch the docs folder if it exists, else its closest existing parent,
+    /// and re-resolve the config, story and workflow-status paths.
+    ///
+    /// Returns true when the docs folder was (re)armed by this call.
+    pub fn arm(&mut self) -> notify::Result<bool> {
+        let watcher = match self.watcher.upgrade() {
+            Some(watcher) => watcher,
+            None => return Ok(false),
+        };
+
+        let desired = match self.find_docs_dir() {
+            Some(root) => Armed::Root(root),
+            None => Armed::Waiting(closest_existing(
+                self.docs_path.as_deref().unwrap_or(&self.project_path),
+            )),
+        };
+
+        let rearmed = if self.armed.as_ref() == Some(&desired) {
+            false
+        } else {
+            self.disarm();
+            let (path, mode) = match &desired {
+                Armed::Root(path) => (path, RecursiveMode::Recursive),
+                Armed::Waiting(path) => (path, RecursiveMode::NonRecursive),
+            };
+            watcher.lock().watch(path, mode)?;
+
+            let rearmed = matches!(desired, Armed::Root(_));
+            self.armed = Some(desired);
+            rearmed
+        };
+
+        self.set = WatchSet::resolve(&self.project_path, self.root());
+        self.arm_extras(&watcher)?;
+        Ok(rearmed)
+    }
+
+    /// Forget every watch after the watcher itself was replaced
+    pub fn reset(&mut self) {
+        self.armed = None;
+        self.extras.clear();
+    }
+
+    /// Follow the extra folders to wherever the config currently points.
+    /// They are best effort: a folder that cannot be watched is skipped,
+    /// unless the watcher as a whole is out of capacity.
+    fn arm_extras(&mut self, watcher: &Mutex<ProjectWatcher>) -> notify::Result<()> {
+        let desired = self.set.extra_watches();
+        let mut watcher = watcher.lock();
+
+        self.extras.retain(|extra| {
+            let keep = desired.contains(extra) && extra.0.is_dir();
+            if !keep {
+                watcher.unwatch(&extra.0);
+            }
+            keep
+        });
+        for extra in desired {
+            if self.extras.contains(&extra) {
+                continue;
+            }
+            match watcher.watch(&extra.0, extra.1) {
+                Ok(()) => self.extras.push(extra),
+                Err(e) if needs_polling(&e) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget the current watch, e.g. after the docs folder was removed or renamed
+    pub fn disarm(&mut self) {
+        if let (Some(Armed::Root(path) | Armed::Waiting(path)), Some(watcher)) =
+            (self.armed.take(), self.watcher.upgrade())
+        {
+            watcher.lock().unwatch(&path);
+        }
+    }
+
+    fn find_docs_dir(&self) -> Option<PathBuf> {
+        match &self.docs_path {
+            Some(path) => Some(path.clone()),
+            None => BmadParser::find_bmad_docs_dir(&self.project_path),
+        }
+        .filter(|path| path.is_dir())
+    }
+}
+
+fn closest_existing(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|p| p.is_dir())
+        .unwrap_or(path)
+        .to_path_buf()
+}
@@ -0,0 +1,300 @@
+use crate::parser::BmadParser;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Project-relative location of the create-story template installed by BMAD
+pub const STORY_TEMPLATE_PATH: &str = "_bmad/bmm/workflows/4-implementation/create-story/template.md";
+
+/// Used when the project has no create-story template installed
+const DEFAULT_STORY_TEMPLATE: &str = r#"# Story {{epic_num}}.{{story_num}}: {{story_title}}
+
+Status: ready-for-dev
+
+## Story
+
+As a {{role}},
+I want {{action}},
+so that {{benefit}}.
+
+## Acceptance Criteria
+
+1. [Add acceptance criteria from epics/PRD]
+
+## Tasks / Subtasks
+
+- [ ] Task 1 (AC: #)
+  - [ ] Subtask 1.1
+
+## Dev Notes
+
+### References
+
+## Dev Agent Record
+
+### Agent Model Used
+
+{{agent_model_name_version}}
+
+### Debug Log References
+
+### Completion Notes List
+
+### File List
+"#;
+
+/// Story details pulled from the epic's `### Story N.M` block in epics.md
+#[derive(Debug, Default, Clone)]
+pub struct EpicStoryBlock {
+    pub title: String,
+    pub role: String,
+    pub action: String,
+    pub benefit: String,
+    pub acceptance_criteria: String,
+}
+
+/// Load the story template: an explicit override, the project's installed
+/// create-story template, or the built-in copy.
+pub fn load_template(project_path: &Path, override_path: Option<&Path>) -> std::io::Result<String> {
+    if let Some(path) = override_path {
+        return fs::read_to_string(path);
+    }
+
+    let installed = project_path.join(STORY_TEMPLATE_PATH);
+    if installed.exists() {
+        return fs::read_to_string(installed);
+    }
+
+    Ok(DEFAULT_STORY_TEMPLATE.to_string())
+}
+
+/// Replace `{{name}}` placeholders; unknown placeholders are left for the agents to fill
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> String {
+    let placeholder_regex = Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap();
+    placeholder_regex
+        .replace_all(template, |caps: &regex::Captures| {
+            vars.get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// Replace the body of the `## Acceptance Criteria` section
+pub fn fill_acceptance_criteria(content: &str, criteria: &str) -> String {
+    let section_regex = Regex::new(r"(?m)^##[ \t]+Acceptance Criteria[^\n]*\n").unwrap();
+    let section = match section_regex.find(content) {
+        Some(m) => m,
+        None => return content.to_string(),
+    };
+
+    let next_heading = Regex::new(r"(?m)^##[ \t]").unwrap();
+    let end = next_heading
+        .find(&content[section.end()..])
+        .map(|m| section.end() + m.start())
+        .unwrap_or(content.len());
+
+    format!(
+        "{}\n{}\n\n{}",
+        &content[..section.end()],
+        criteria.trim(),
+        &content[end..]
+    )
+}
+
+/// Turn a story title into the kebab-case slug used in story keys
+pub fn kebab_case(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Find the `### Story N.M` block for a story in the project's epics.md files
+pub fn find_epic_story_block(
+    bmad_dir: &Path,
+    epic_number: u32,
+    story_number: u32,
+) -> Option<(PathBuf, EpicStoryBlock)> {
+    BmadParser::find_epics_files(bmad_dir)
+        .into_iter()
+        .find_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            let block = parse_epic_story_block(&content, epic_number, story_number)?;
+            Some((path, block))
+        })
+}
+
+fn parse_epic_story_block(content: &str, epic_number: u32, story_number: u32) -> Option<EpicStoryBlock> {
+    let header_regex = Regex::new(&format!(
+        r"(?m)^#{{2,4}}\s*Story\s*{}\.{}\b[:\s]*(.*)$",
+        epic_number, story_number
+    ))
+    .ok()?;
+    let header = header_regex.captures(content)?;
+    let start = header.get(0)?.end();
+
+    // The block runs until a heading of the same or a higher level
+    let level = header[0].chars().take_while(|c| *c == '#').count();
+    let next_heading = Regex::new(&format!(r"(?m)^#{{1,{}}}\s", level)).ok()?;
+    let end = next_heading
+        .find(&content[start..])
+        .map(|m| start + m.start())
+        .unwrap_or(content.len());
+    let body = &content[start..end];
+
+    let user_story_regex = Regex::new(
+        r"(?is)\**As\s+(?:a|an)\**\s+(.+?),?\s*\**I\s+want\**\s+(.+?),?\s*\**so\s+that\**\s+(.+?)\.?\s*(?:\n\s*\n|\z)",
+    )
+    .unwrap();
+    let (role, action, benefit) = user_story_regex
+        .captures(body)
+        .map(|c| {
+            (
+                c[1].trim().to_string(),
+                c[2].trim().to_string(),
+                c[3].trim().to_string(),
+            )
+        })
+        .unwrap_or_default();
+
+    let criteria_regex =
+        Regex::new(r"(?im)^(?:\*\*Acceptance Criteria:?\*\*:?|#{3,5}\s*Acceptance Criteria:?)[ \t]*$").unwrap();
+    let acceptance_criteria = criteria_regex
+        .find(body)
+        .map(|m| body[m.end()..].trim().to_string())
+        .unwrap_or_default();
+
+    Some(EpicStoryBlock {
+        title: header
+            .get(1)
+            .map(|m| m.as_str().trim().to_string())
+            .unwrap_or_default(),
+        role,
+        action,
+        benefit,
+        acceptance_criteria,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scope::ProjectScope;
+    use crate::writer::SprintStatusFile;
+
+    const EPICS: &str = "\
+# Epics
+
+## Epic 1: Accounts
+
+#### Story 1.1: Sign up
+
+As a visitor,
+I want to create an account,
+so that I can save my work.
+
+**Acceptance Criteria:**
+
+1. The form asks for an email and a password
+2. A confirmation email is sent
+
+#### Story 1.2: Log in
+
+As a member,
+I want to log in,
+so that I can see my projects.
+
+**Acceptance Criteria:**
+
+1. Wrong passwords are refused
+
+## Epic 2: Billing
+";
+
+    const SPRINT: &str = "\
+development_status:
+  epic-1: backlog
+  1-2-log-in: backlog
+  epic-1-retrospective: optional
+";
+
+    #[test]
+    fn level_four_story_blocks_stop_at_the_next_story() {
+        let block = parse_epic_story_block(EPICS, 1, 1).unwrap();
+        assert_eq!(block.title, "Sign up");
+        assert_eq!(block.role, "visitor");
+        assert_eq!(block.action, "to create an account");
+        assert_eq!(block.benefit, "I can save my work");
+        assert_eq!(
+            block.acceptance_criteria,
+            "1. The form asks for an email and a password\n2. A confirmation email is sent"
+        );
+
+        let last = parse_epic_story_block(EPICS, 1, 2).unwrap();
+        assert_eq!(last.acceptance_criteria, "1. Wrong passwords are refused");
+        assert!(parse_epic_story_block(EPICS, 1, 3).is_none());
+    }
+
+    #[test]
+    fn rendered_story_is_filled_from_the_block() {
+        let block = parse_epic_story_block(EPICS, 1, 1).unwrap();
+        let vars: HashMap<&str, String> = [
+            ("epic_num", "1".to_string()),
+            ("story_num", "1".to_string()),
+            ("story_title", block.title.clone()),
+            ("role", block.role.clone()),
+            ("action", block.action.clone()),
+            ("benefit", block.benefit.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        let content = render_template(DEFAULT_STORY_TEMPLATE, &vars);
+        let content = fill_acceptance_criteria(&content, &block.acceptance_criteria);
+        assert!(content.starts_with("# Story 1.1: Sign up\n"));
+        assert!(content.contains("As a visitor,\nI want to create an account,\nso that I can save my work."));
+        assert!(content.contains(
+            "## Acceptance Criteria\n\n1. The form asks for an email and a password\n2. A confirmation email is sent\n\n## Tasks"
+        ));
+        // Left for the dev agent
+        assert!(content.contains("{{agent_model_name_version}}"));
+        assert!(!content.contains("log in"));
+    }
+
+    #[test]
+    fn create_story_writes_the_file_and_tracks_it() {
+        let project = std::env::temp_dir().join(format!("bmad-create-story-{}", uuid::Uuid::new_v4()));
+        let docs = project.join("docs");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(docs.join("epics.md"), EPICS).unwrap();
+        fs::write(docs.join("sprint-status.yaml"), SPRINT).unwrap();
+        let mut scope = ProjectScope::default();
+        scope.register(&project).unwrap();
+
+        let path = crate::writer::create_story(&scope, &project, &docs, 1, Some(1), None, None).unwrap();
+        assert_eq!(path.file_name().unwrap(), "1-1-sign-up.md");
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("I want to create an account"));
+
+        let sprint = fs::read_to_string(docs.join("sprint-status.yaml")).unwrap();
+        let sprint = SprintStatusFile::parse(&sprint, Default::default()).unwrap();
+        assert_eq!(sprint.story_keys(1), ["1-1-sign-up", "1-2-log-in"]);
+        assert_eq!(sprint.story_status("1-1-sign-up"), Some(crate::models::StoryStatus::ReadyForDev));
+        assert_eq!(sprint.epic_status(1), Some(crate::models::EpicStatus::InProgress));
+        // The epics are only read
+        assert_eq!(fs::read_to_string(docs.join("epics.md")).unwrap(), EPICS);
+
+        assert!(matches!(
+            crate::writer::create_story(&scope, &project, &docs, 1, Some(1), None, None),
+            Err(crate::writer::WriteError::StoryExists(_))
+        ));
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use super::{write_file_checked, WriteError};
+use crate::models::{DocumentVersion, VersionedDocument};
+use crate::scope::ScopeError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Error returned to the frontend by the document commands
+#[derive(Error, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentError {
+    /// The file changed on disk since the caller read it; `None` means it was deleted
+    #[error("Document was modified on disk since it was read")]
+    #[serde(rename_all = "camelCase")]
+    Conflict {
+        current_content: Option<String>,
+        current_version: Option<DocumentVersion>,
+    },
+    /// The path is outside the opened projects or may not be written
+    #[error("{message}")]
+    Forbidden { message: String },
+    #[error("{message}")]
+    Io { message: String },
+}
+
+impl From<ScopeError> for DocumentError {
+    fn from(e: ScopeError) -> Self {
+        match e {
+            ScopeError::Io(e) => e.into(),
+            e => DocumentError::Forbidden {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for DocumentError {
+    fn from(e: std::io::Error) -> Self {
+        DocumentError::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<WriteError> for DocumentError {
+    fn from(e: WriteError) -> Self {
+        DocumentError::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn version_of(path: &Path, content: &str) -> DocumentVersion {
+    DocumentVersion {
+        hash: content_hash(content),
+        modified: fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(|time| DateTime::<Utc>::from(time).to_rfc3339()),
+    }
+}
+
+pub fn read_document(path: &Path) -> Result<VersionedDocument, DocumentError> {
+    let content = fs::read_to_string(path)?;
+    let version = version_of(path, &content);
+    Ok(VersionedDocument { content, version })
+}
+
+/// Write a document if it still matches `expected`; pass a version with an
+/// empty hash to create a new one.
+///
+/// Versions are compared by content hash, so a touched-but-unchanged file
+/// is not treated as a conflict. The check runs again right before the new
+/// content replaces the file, so an edit that lands while writing is not
+/// overwritten either.
+pub fn write_document(
+    path: &Path,
+    content: &str,
+    expected: &DocumentVersion,
+) -> Result<DocumentVersion, DocumentError> {
+    check_version(path, expected)?;
+    write_file_checked(path, content, |target| check_version(target, expected))?;
+    Ok(version_of(path, content))
+}
+
+/// Conflict unless the file on disk still has the `expected` content. An
+/// empty expected hash stands for a new document: the file must not exist.
+fn check_version(path: &Path, expected: &DocumentVersion) -> Result<(), DocumentError> {
+    let current = match fs::read_to_string(path) {
+        Ok(current) => Some(current),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    if current.is_none() && expected.hash.is_empty() {
+        return Ok(());
+    }
+
+    let current_version = current.as_deref().map(|c| version_of(path, c));
+    if current_version.as_ref().map(|v| &v.hash) != Some(&expected.hash) {
+        return Err(DocumentError::Conflict {
+            current_content: current,
+            current_version,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_versions_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("bmad-dash-doc-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("prd.md");
+        fs::write(&path, "# PRD\n").unwrap();
+
+        let read = read_document(&path).unwrap();
+        fs::write(&path, "# PRD\n\nEdited elsewhere\n").unwrap();
+        match write_document(&path, "# Mine\n", &read.version) {
+            Err(DocumentError::Conflict { current_content, .. }) => {
+                assert_eq!(current_content.as_deref(), Some("# PRD\n\nEdited elsewhere\n"));
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# PRD\n\nEdited elsewhere\n");
+
+        let current = read_document(&path).unwrap();
+        let written = write_document(&path, "# Mine\n", &current.version).unwrap();
+        assert_eq!(written.hash, content_hash("# Mine\n"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Mine\n");
+        // No temp files are left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn new_documents_are_created_once() {
+        let dir = std::env::temp_dir().join(format!("bmad-dash-doc-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("architecture.md");
+        let new = DocumentVersion {
+            hash: String::new(),
+            modified: None,
+        };
+
+        let written = write_document(&path, "# Architecture\n", &new).unwrap();
+        assert_eq!(written.hash, content_hash("# Architecture\n"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Architecture\n");
+
+        // Someone else created it first
+        assert!(matches!(
+            write_document(&path, "# Mine\n", &new),
+            Err(DocumentError::Conflict { .. })
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Architecture\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
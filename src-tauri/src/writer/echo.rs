@@ -0,0 +1,130 @@
+use super::document::content_hash;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// How long after a write the watcher may still report it
+const ECHO_WINDOW: Duration = Duration::from_secs(10);
+
+static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, OwnWrite>>> = OnceLock::new();
+
+/// What the app last left at a path
+struct OwnWrite {
+    /// Hash of the written content; `None` if the app removed the file
+    hash: Option<String>,
+    at: Instant,
+}
+
+fn own_writes() -> &'static Mutex<HashMap<PathBuf, OwnWrite>> {
+    OWN_WRITES.get_or_init(Default::default)
+}
+
+/// Remember that the app just wrote `content` to `path`
+pub fn record_write(path: &Path, content: &str) {
+    record(path, Some(content_hash(content)));
+}
+
+/// Remember that the app just removed `path`
+pub fn record_removal(path: &Path) {
+    record(path, None);
+}
+
+fn record(path: &Path, hash: Option<String>) {
+    let mut writes = own_writes().lock();
+    writes.retain(|_, write| write.at.elapsed() < ECHO_WINDOW);
+    writes.insert(
+        key(path),
+        OwnWrite {
+            hash,
+            at: Instant::now(),
+        },
+    );
+}
+
+/// Whether `path` still holds exactly what the app recently wrote (or is
+/// still gone after the app removed it), i.e. a watcher event for it is an
+/// echo of the app's own write rather than an outside change.
+pub fn is_own_change(path: &Path) -> bool {
+    own_writes()
+        .lock()
+        .get(&key(path))
+        .is_some_and(|write| write.is_echo(path, Instant::now()))
+}
+
+impl OwnWrite {
+    /// Whether `path` at time `now` is still what this write left there
+    fn is_echo(&self, path: &Path, now: Instant) -> bool {
+        if now.saturating_duration_since(self.at) >= ECHO_WINDOW {
+            return false;
+        }
+        match &self.hash {
+            Some(hash) => fs::read_to_string(path)
+                .map(|content| content_hash(&content) == *hash)
+                .unwrap_or(false),
+            None => !path.exists(),
+        }
+    }
+}
+
+/// Canonical form of a path that may no longer exist
+fn key(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent().and_then(|p| fs::canonicalize(p).ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bmad-echo-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn writes_are_echoes_only_inside_the_window() {
+        let path = temp_file("story.md");
+        fs::write(&path, "# Story 1.1\n").unwrap();
+        record_write(&path, "# Story 1.1\n");
+        assert!(is_own_change(&path));
+
+        let writes = own_writes().lock();
+        let write = writes.get(&key(&path)).unwrap();
+        assert!(write.is_echo(&path, write.at + Duration::from_secs(9)));
+        assert!(!write.is_echo(&path, write.at + ECHO_WINDOW));
+        drop(writes);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn changed_content_is_not_an_echo() {
+        let path = temp_file("sprint-status.yaml");
+        fs::write(&path, "development_status: {}\n").unwrap();
+        record_write(&path, "development_status: {}\n");
+        fs::write(&path, "development_status:\n  epic-1: done\n").unwrap();
+        assert!(!is_own_change(&path));
+
+        // Never written by the app
+        assert!(!is_own_change(&path.with_file_name("other.md")));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn removals_are_echoes_until_the_file_is_back() {
+        let path = temp_file("1-2-login.md");
+        record_removal(&path);
+        assert!(is_own_change(&path));
+        fs::write(&path, "# Story 1.2\n").unwrap();
+        assert!(!is_own_change(&path));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
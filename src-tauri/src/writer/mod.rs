@@ -0,0 +1,592 @@
+pub mod create_story;
+pub mod document;
+pub mod echo;
+pub mod move_story;
+pub mod sprint_planning;
+pub mod sprint_status;
+pub mod story;
+
+use crate::history::{self, HistoryError};
+use crate::models::{EpicStatus, SprintPlan, StoryRename, StoryStatus};
+use crate::parser::{BmadParser, ProjectOverrides};
+use crate::scope::{ProjectScope, ScopeError};
+use chrono::Local;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+pub use sprint_status::SprintStatusFile;
+
+#[derive(Error, Debug)]
+pub enum WriteError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("No sprint-status.yaml found in {0}")]
+    SprintStatusNotFound(String),
+    #[error("Invalid sprint-status.yaml: {0}")]
+    InvalidSprintStatus(String),
+    #[error("Story not found in sprint-status.yaml: {0}")]
+    UnknownStory(String),
+    #[error("Epic not found in sprint-status.yaml: epic-{0}")]
+    UnknownEpic(u32),
+    #[error("Task not found in story: {0}")]
+    UnknownTask(String),
+    #[error("Story file already exists: {0}")]
+    StoryExists(String),
+    #[error("No title given and none found in epics for story {0}")]
+    MissingStoryTitle(String),
+    #[error("Story number already taken: {0}")]
+    StoryNumberTaken(String),
+    #[error("Story numbers start at 1: {0}")]
+    InvalidStoryNumber(String),
+    #[error("Could not read epics: {0}")]
+    Parse(#[from] crate::parser::ParseError),
+    #[error("Could not save file history: {0}")]
+    History(#[from] HistoryError),
+    #[error("{0}")]
+    Scope(#[from] ScopeError),
+}
+
+/// Write a file the app edits on behalf of the user.
+///
+/// Content goes to a temp file next to the target which is then renamed over
+/// it, so agents and the watcher never see a half-written file. The previous
+/// content is recorded in the edit history first, and the new content is
+/// remembered so the watcher can tell the resulting event is the app's own.
+pub fn write_file(path: &Path, content: &str) -> Result<(), WriteError> {
+    write_file_checked(path, content, |_| Ok(()))
+}
+
+/// `write_file`, with `check` run on the target right before the rename.
+///
+/// The new content is already on disk next to the target by then, so the
+/// check (e.g. that nobody else changed the file) is as close to the
+/// replace as it can be; an error from it leaves the target untouched.
+pub fn write_file_checked<E: From<WriteError>>(
+    path: &Path,
+    content: &str,
+    check: impl FnOnce(&Path) -> Result<(), E>,
+) -> Result<(), E> {
+    // Write through symlinks so the link itself survives the rename
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = dir.join(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4().simple()));
+
+    let result = (|| -> Result<(), E> {
+        let staged = (|| {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            if let Ok(meta) = fs::metadata(&target) {
+                fs::set_permissions(&temp_path, meta.permissions())?;
+            }
+            Ok::<_, std::io::Error>(())
+        })();
+        staged.map_err(WriteError::from)?;
+
+        check(&target)?;
+        history::record(path).map_err(WriteError::from)?;
+        fs::rename(&temp_path, &target).map_err(WriteError::from)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+    echo::record_write(&target, content);
+    Ok(())
+}
+
+/// Remove a file the app manages, recording its content in the edit history first
+pub fn remove_file(path: &Path) -> Result<(), WriteError> {
+    history::record(path)?;
+    fs::remove_file(path)?;
+    echo::record_removal(path);
+    Ok(())
+}
+
+/// A group of file writes (and removals) applied together.
+///
+/// Every target is checked with `ProjectScope::resolve_writable` when it is
+/// staged: story folders and sprint-status locations come from project
+/// files, not from the user, so they may point anywhere.
+///
+/// Original contents are captured before anything is written; if any write
+/// fails, the files already written are restored (or removed if they did not
+/// exist before).
+pub struct Transaction<'a> {
+    scope: &'a ProjectScope,
+    /// Staged content per path; `None` removes the file
+    writes: Vec<(PathBuf, Option<String>)>,
+}
+
+impl<'a> Transaction<'a> {
+    pub fn new(scope: &'a ProjectScope) -> Self {
+        Self {
+            scope,
+            writes: Vec::new(),
+        }
+    }
+
+    pub fn stage(&mut self, path: impl AsRef<Path>, content: impl Into<String>) -> Result<(), WriteError> {
+        let path = self.scope.resolve_writable(path.as_ref())?;
+        self.stage_change(path, Some(content.into()));
+        Ok(())
+    }
+
+    pub fn stage_removal(&mut self, path: impl AsRef<Path>) -> Result<(), WriteError> {
+        let path = self.scope.resolve_writable(path.as_ref())?;
+        self.stage_change(path, None);
+        Ok(())
+    }
+
+    fn stage_change(&mut self, path: PathBuf, content: Option<String>) {
+        match self.writes.iter_mut().find(|(p, _)| *p == path) {
+            Some(existing) => existing.1 = content,
+            None => self.writes.push((path, content)),
+        }
+    }
+
+    pub fn commit(self) -> Result<(), WriteError> {
+        let originals: Vec<Option<String>> = self
+            .writes
+            .iter()
+            .map(|(path, _)| fs::read_to_string(path).ok())
+            .collect();
+
+        for (index, (path, content)) in self.writes.iter().enumerate() {
+            let result = match content {
+                Some(content) => write_file(path, content),
+                None if path.exists() => remove_file(path),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                Self::rollback(&self.writes[..index], &originals);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rollback(applied: &[(PathBuf, Option<String>)], originals: &[Option<String>]) {
+        for ((path, _), original) in applied.iter().zip(originals) {
+            let restored = match original {
+                Some(content) => fs::write(path, content).map(|_| echo::record_write(path, content)),
+                None if path.exists() => fs::remove_file(path).map(|_| echo::record_removal(path)),
+                None => Ok(()),
+            };
+            if let Err(e) = restored {
+                eprintln!("Failed to roll back {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Locate the sprint-status.yaml the parser would read for this docs folder
+pub fn sprint_status_path(bmad_dir: &Path) -> Result<PathBuf, WriteError> {
+    BmadParser::find_sprint_status_file(bmad_dir)
+        .ok_or_else(|| WriteError::SprintStatusNotFound(bmad_dir.to_string_lossy().to_string()))
+}
+
+/// Set a story's status, returning the resolved story key.
+///
+/// Updates the sprint-status.yaml entry and the story file's `Status:` line
+/// together. Like the create-story workflow, moving the first story of a
+/// backlog epic out of backlog also marks the epic in-progress.
+pub fn set_story_status(
+    scope: &ProjectScope,
+    bmad_dir: &Path,
+    story: &str,
+    status: &StoryStatus,
+) -> Result<String, WriteError> {
+    let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+    let sprint_path = sprint_status_path(bmad_dir)?;
+    let mut sprint = SprintStatusFile::load(&sprint_path, overrides.clone())?;
+    let story_key = sprint.set_story_status(story, status)?;
+
+    if *status != StoryStatus::Backlog {
+        let epic_number = story_key
+            .split('-')
+            .next()
+            .and_then(|n| n.parse::<u32>().ok());
+        if let Some(epic_number) = epic_number {
+            if sprint.epic_status(epic_number) == Some(EpicStatus::Backlog) {
+                sprint.set_epic_status(epic_number, &EpicStatus::InProgress)?;
+            }
+        }
+    }
+
+    let mut transaction = Transaction::new(scope);
+    transaction.stage(&sprint_path, sprint.to_string())?;
+
+    if let Some(story_path) = story::find_story_file(bmad_dir, &overrides, &story_key) {
+        let content = fs::read_to_string(&story_path)?;
+        transaction.stage(
+            story_path,
+            story::set_status_line(&content, overrides.story_status_value(status)),
+        )?;
+    }
+
+    transaction.commit()?;
+    Ok(story_key)
+}
+
+/// Create `N-M-kebab-title.md` from the create-story template and track it in sprint-status.yaml.
+///
+/// The story number defaults to the next free number in the epic, and the
+/// title, user story and acceptance criteria are pre-filled from the epic's
+/// story block in epics.md when available. Returns the new story file path.
+pub fn create_story(
+    scope: &ProjectScope,
+    project_path: &Path,
+    bmad_dir: &Path,
+    epic_number: u32,
+    story_number: Option<u32>,
+    title: Option<&str>,
+    template_path: Option<&Path>,
+) -> Result<PathBuf, WriteError> {
+    let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+    let sprint_path = sprint_status_path(bmad_dir)?;
+    let mut sprint = SprintStatusFile::load(&sprint_path, overrides.clone())?;
+    let epic_keys = sprint.story_keys(epic_number);
+
+    let story_number = story_number.unwrap_or_else(|| {
+        epic_keys
+            .iter()
+            .filter_map(|key| sprint_status::story_numbers(key))
+            .map(|(_, n)| n)
+            .max()
+            .unwrap_or(0)
+            + 1
+    });
+    let story_ref = format!("{}.{}", epic_number, story_number);
+
+    let existing_key = epic_keys
+        .into_iter()
+        .find(|key| sprint_status::story_numbers(key) == Some((epic_number, story_number)));
+    let block = create_story::find_epic_story_block(bmad_dir, epic_number, story_number)
+        .map(|(_, block)| block)
+        .unwrap_or_default();
+
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .or_else(|| Some(block.title.clone()).filter(|t| !t.is_empty()))
+        .ok_or_else(|| WriteError::MissingStoryTitle(story_ref.clone()))?;
+
+    let story_key = existing_key.clone().unwrap_or_else(|| {
+        format!(
+            "{}-{}-{}",
+            epic_number,
+            story_number,
+            create_story::kebab_case(&title)
+        )
+    });
+
+    if let Some(existing) = story::find_story_file(bmad_dir, &overrides, &story_key) {
+        return Err(WriteError::StoryExists(existing.to_string_lossy().to_string()));
+    }
+    let story_dir = scope.resolve_dir(&BmadParser::find_story_dir(project_path, bmad_dir))?;
+    let story_path = story_dir.join(format!("{}.md", story_key));
+    if story_path.exists() {
+        return Err(WriteError::StoryExists(story_path.to_string_lossy().to_string()));
+    }
+
+    let mut vars: HashMap<&str, String> = HashMap::new();
+    vars.insert("epic_num", epic_number.to_string());
+    vars.insert("story_num", story_number.to_string());
+    vars.insert("story_title", title);
+    vars.insert("story_key", story_key.clone());
+    vars.insert("date", Local::now().format("%Y-%m-%d").to_string());
+    for (name, value) in [
+        ("role", &block.role),
+        ("action", &block.action),
+        ("benefit", &block.benefit),
+    ] {
+        if !value.is_empty() {
+            vars.insert(name, value.clone());
+        }
+    }
+
+    let template = create_story::load_template(project_path, template_path)?;
+    let mut content = create_story::render_template(&template, &vars);
+    if !block.acceptance_criteria.is_empty() {
+        content = create_story::fill_acceptance_criteria(&content, &block.acceptance_criteria);
+    }
+
+    match existing_key {
+        Some(key) => {
+            sprint.set_story_status(&key, &StoryStatus::ReadyForDev)?;
+        }
+        None => sprint.insert_story(&story_key, &StoryStatus::ReadyForDev)?,
+    }
+    if sprint.epic_status(epic_number) == Some(EpicStatus::Backlog) {
+        sprint.set_epic_status(epic_number, &EpicStatus::InProgress)?;
+    }
+
+    fs::create_dir_all(&story_dir)?;
+    let mut transaction = Transaction::new(scope);
+    transaction.stage(&story_path, content)?;
+    transaction.stage(&sprint_path, sprint.to_string())?;
+    transaction.commit()?;
+
+    Ok(story_path)
+}
+
+/// Move a story to another epic and/or number.
+///
+/// Renames the story file, updates its `# Story N.M` heading and
+/// sprint-status key, fixes `[Source: ...]` references in the docs and
+/// relocates its block in epics.md. With `renumber_siblings`, the stories
+/// around the old and new positions shift to keep numbering contiguous.
+pub fn move_story(
+    scope: &ProjectScope,
+    bmad_dir: &Path,
+    story: &str,
+    to_epic: u32,
+    to_number: Option<u32>,
+    renumber_siblings: bool,
+) -> Result<Vec<StoryRename>, WriteError> {
+    let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+    let sprint_path = sprint_status_path(bmad_dir)?;
+    let mut sprint = SprintStatusFile::load(&sprint_path, overrides.clone())?;
+
+    let from_key = sprint
+        .resolve_story_key(story)
+        .ok_or_else(|| WriteError::UnknownStory(story.to_string()))?;
+    let from = sprint_status::story_numbers(&from_key)
+        .ok_or_else(|| WriteError::UnknownStory(from_key.clone()))?;
+
+    let existing: Vec<move_story::StoryRef> = sprint
+        .all_story_keys()
+        .iter()
+        .filter_map(|key| sprint_status::story_numbers(key))
+        .collect();
+    let to_number = to_number.unwrap_or_else(|| {
+        existing
+            .iter()
+            .filter(|(epic, _)| *epic == to_epic)
+            .map(|(_, n)| *n)
+            .max()
+            .unwrap_or(0)
+            + 1
+    });
+    if to_number == 0 {
+        return Err(WriteError::InvalidStoryNumber(format!("{}.0", to_epic)));
+    }
+    let to = (to_epic, to_number);
+    if to == from {
+        return Ok(Vec::new());
+    }
+
+    let moves = move_story::plan_moves(&existing, from, to, renumber_siblings)
+        .map_err(|(e, s)| WriteError::StoryNumberTaken(format!("{}.{}", e, s)))?;
+
+    let all_keys = sprint.all_story_keys();
+    let mut renames = Vec::new();
+    let mut keys: HashMap<String, String> = HashMap::new();
+    let mut numbers: HashMap<move_story::StoryRef, move_story::StoryRef> = HashMap::new();
+    for (old, new) in &moves {
+        let old_key = all_keys
+            .iter()
+            .find(|key| sprint_status::story_numbers(key) == Some(*old))
+            .cloned()
+            .ok_or_else(|| WriteError::UnknownStory(format!("{}.{}", old.0, old.1)))?;
+        let new_key = move_story::renamed_key(&old_key, *new);
+
+        keys.insert(old_key.clone(), new_key.clone());
+        numbers.insert(*old, *new);
+        renames.push(StoryRename {
+            from_key: old_key,
+            to_key: new_key,
+            from_number: format!("{}.{}", old.0, old.1),
+            to_number: format!("{}.{}", new.0, new.1),
+        });
+    }
+
+    let key_pairs: Vec<(String, String)> = renames
+        .iter()
+        .map(|r| (r.from_key.clone(), r.to_key.clone()))
+        .collect();
+    sprint.rename_stories(&key_pairs)?;
+
+    let mut transaction = Transaction::new(scope);
+    // Renamed story files: (old path, new path, new content), staged from
+    // the path `find_story_file` returned so a move never only deletes one
+    let mut renamed_files: Vec<(PathBuf, PathBuf, String)> = Vec::new();
+    for ((_, new), rename) in moves.iter().zip(&renames) {
+        if let Some(old_path) = story::find_story_file(bmad_dir, &overrides, &rename.from_key) {
+            // Keep the project's own naming scheme for the file
+            let new_name = old_path
+                .file_name()
+                .and_then(|name| overrides.renamed_story_file(&name.to_string_lossy(), *new))
+                .unwrap_or_else(|| format!("{}.md", rename.to_key));
+            let new_path = old_path.with_file_name(new_name);
+            let content = fs::read_to_string(&old_path)?;
+            let updated = move_story::rewrite_source_refs(&content, &keys, &numbers);
+            let updated = move_story::rewrite_story_heading(&updated, *new);
+            renamed_files.push((old_path, new_path, updated));
+        }
+    }
+    let renamed_from: Vec<PathBuf> = renamed_files
+        .iter()
+        .map(|(old_path, _, _)| fs::canonicalize(old_path).unwrap_or_else(|_| old_path.clone()))
+        .collect();
+
+    let roots = story::story_roots(bmad_dir, &overrides);
+    for entry in roots.iter().flat_map(|root| WalkDir::new(root).max_depth(4)).filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().map(|e| e != "md").unwrap_or(true) {
+            continue;
+        }
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if renamed_from.contains(&canonical) {
+            continue;
+        }
+        // Not Markdown text after all (or unreadable); nothing to fix in it
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+
+        let updated = move_story::rewrite_source_refs(&content, &keys, &numbers);
+        if updated != content {
+            transaction.stage(path, updated)?;
+        }
+    }
+
+    // Removals before the new files: with renumbering, a story's new name
+    // may be the old name of the story it replaces
+    for (old_path, _, _) in &renamed_files {
+        transaction.stage_removal(old_path)?;
+    }
+    for (_, new_path, updated) in renamed_files {
+        transaction.stage(new_path, updated)?;
+    }
+
+    for epics_file in BmadParser::find_epics_files(bmad_dir) {
+        let content = fs::read_to_string(&epics_file)?;
+        let source_fixed = move_story::rewrite_source_refs(&content, &keys, &numbers);
+        let updated = move_story::rewrite_epics(&source_fixed, &numbers, to);
+        if updated != content {
+            transaction.stage(&epics_file, updated)?;
+        }
+    }
+
+    transaction.stage(&sprint_path, sprint.to_string())?;
+    transaction.commit()?;
+    Ok(renames)
+}
+
+/// Generate or refresh sprint-status.yaml from the epics, like the
+/// sprint-planning workflow.
+///
+/// An existing file is merged in place (see `sprint_planning::merge_epics`);
+/// otherwise a new one is created next to the story files. With `dry_run`
+/// nothing is written and the returned plan only carries the diff.
+pub fn plan_sprint_status(
+    scope: &ProjectScope,
+    project_path: &Path,
+    bmad_dir: &Path,
+    dry_run: bool,
+) -> Result<SprintPlan, WriteError> {
+    let epics = BmadParser::parse_planned_epics(bmad_dir)?;
+    let story_dir = BmadParser::find_story_dir(project_path, bmad_dir);
+
+    let (sprint_path, original) = match BmadParser::find_sprint_status_file(bmad_dir) {
+        Some(path) => {
+            let content = fs::read_to_string(&path)?;
+            (path, Some(content))
+        }
+        None => (story_dir.join("sprint-status.yaml"), None),
+    };
+
+    let base = match &original {
+        Some(content) => content.clone(),
+        None => {
+            let project_name = BmadParser::bmm_config_value(project_path, "project_name")
+                .or_else(|| {
+                    project_path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                })
+                .unwrap_or_default();
+            sprint_planning::new_sprint_status(
+                &project_name,
+                &sprint_planning::story_location_value(project_path, &story_dir),
+                &Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            )
+        }
+    };
+
+    let overrides = ProjectOverrides::for_docs_dir(bmad_dir)?;
+    let mut sprint = SprintStatusFile::parse(&base, overrides.clone())?;
+    let report = sprint_planning::merge_epics(&mut sprint, &epics, |key| {
+        story_dir.join(format!("{}.md", key)).exists()
+            || story::find_story_file(bmad_dir, &overrides, key).is_some()
+    })?;
+    let updated = sprint.to_string();
+
+    let changed = original.as_deref() != Some(updated.as_str());
+    let written = changed && !dry_run;
+    if written {
+        if let Some(dir) = sprint_path.parent() {
+            fs::create_dir_all(scope.resolve_dir(dir)?)?;
+        }
+        write_file(&scope.resolve_writable(&sprint_path)?, &updated)?;
+    }
+
+    Ok(SprintPlan {
+        file_path: sprint_path.to_string_lossy().to_string(),
+        created: original.is_none(),
+        written,
+        added_keys: report.added_keys,
+        upgraded_keys: report.upgraded_keys,
+        unplanned_keys: report.unplanned_keys,
+        diff: history::diff(
+            original.as_deref().unwrap_or(""),
+            &updated,
+            "sprint-status.yaml",
+            "sprint-status.yaml (planned)",
+        ),
+    })
+}
+
+/// Set an epic's status in sprint-status.yaml
+pub fn set_epic_status(
+    scope: &ProjectScope,
+    bmad_dir: &Path,
+    epic_number: u32,
+    status: &EpicStatus,
+) -> Result<(), WriteError> {
+    let path = scope.resolve_writable(&sprint_status_path(bmad_dir)?)?;
+    let mut file = SprintStatusFile::load(&path, ProjectOverrides::for_docs_dir(bmad_dir)?)?;
+    file.set_epic_status(epic_number, status)?;
+    write_file(&path, &file.to_string())
+}
+
+/// Tick or untick a task in a story file (flips it when `completed` is `None`)
+pub fn toggle_story_task(
+    story_path: &Path,
+    task_id: &str,
+    completed: Option<bool>,
+) -> Result<(), WriteError> {
+    let content = fs::read_to_string(story_path)?;
+    let updated = story::set_task_completed(&content, task_id, completed)
+        .ok_or_else(|| WriteError::UnknownTask(task_id.to_string()))?;
+    if updated != content {
+        write_file(story_path, &updated)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,310 @@
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+/// (epic number, story number)
+pub type StoryRef = (u32, u32);
+
+/// Work out every story that changes number when `from` moves to `to`.
+///
+/// With `renumber`, the gap left in the source epic is closed and the
+/// target epic makes room at the destination; otherwise only the moved
+/// story changes and the destination must be free.
+pub fn plan_moves(
+    existing: &[StoryRef],
+    from: StoryRef,
+    to: StoryRef,
+    renumber: bool,
+) -> Result<Vec<(StoryRef, StoryRef)>, StoryRef> {
+    let mut moves = vec![(from, to)];
+
+    if !renumber {
+        if existing.iter().any(|s| *s == to && *s != from) {
+            return Err(to);
+        }
+        return Ok(moves);
+    }
+
+    let in_epic = |epic: u32| existing.iter().filter(move |(e, s)| *e == epic && (*e, *s) != from);
+
+    if from.0 == to.0 {
+        let (low, high) = (from.1.min(to.1), from.1.max(to.1));
+        for &(epic, story) in in_epic(from.0) {
+            if story < low || story > high {
+                continue;
+            }
+            let shifted = if to.1 < from.1 { story + 1 } else { story - 1 };
+            moves.push(((epic, story), (epic, shifted)));
+        }
+    } else {
+        for &(epic, story) in in_epic(from.0) {
+            if story > from.1 {
+                moves.push(((epic, story), (epic, story - 1)));
+            }
+        }
+        for &(epic, story) in in_epic(to.0) {
+            if story >= to.1 {
+                moves.push(((epic, story), (epic, story + 1)));
+            }
+        }
+    }
+
+    Ok(moves)
+}
+
+/// Rewrite a story key ("2-3-login-form") for its new position
+pub fn renamed_key(story_key: &str, to: StoryRef) -> String {
+    let slug = story_key.splitn(3, '-').nth(2).unwrap_or("");
+    if slug.is_empty() {
+        format!("{}-{}", to.0, to.1)
+    } else {
+        format!("{}-{}-{}", to.0, to.1, slug)
+    }
+}
+
+/// Update the `# Story N.M:` heading of a story file
+pub fn rewrite_story_heading(content: &str, to: StoryRef) -> String {
+    let heading_regex = Regex::new(r"(?m)^(#[ \t]+Story[ \t]+)\d+\.\d+").unwrap();
+    heading_regex
+        .replacen(content, 1, |caps: &Captures| {
+            format!("{}{}.{}", &caps[1], to.0, to.1)
+        })
+        .to_string()
+}
+
+/// Point `[Source: ...]` references at renamed story files and story numbers
+pub fn rewrite_source_refs(
+    content: &str,
+    keys: &HashMap<String, String>,
+    numbers: &HashMap<StoryRef, StoryRef>,
+) -> String {
+    let source_regex = Regex::new(r"\[Source:[^\]]*\]").unwrap();
+    let key_regex = Regex::new(r"\b\d+-\d+-[A-Za-z0-9-]+").unwrap();
+    let number_regex = Regex::new(r"(Story[ \-]?)(\d+)\.(\d+)").unwrap();
+
+    source_regex
+        .replace_all(content, |source: &Captures| {
+            let text = key_regex.replace_all(&source[0], |caps: &Captures| {
+                keys.get(&caps[0]).cloned().unwrap_or_else(|| caps[0].to_string())
+            });
+            number_regex
+                .replace_all(&text, |caps: &Captures| {
+                    renumbered(caps, numbers)
+                        .unwrap_or_else(|| caps[0].to_string())
+                })
+                .to_string()
+        })
+        .to_string()
+}
+
+fn renumbered(caps: &Captures, numbers: &HashMap<StoryRef, StoryRef>) -> Option<String> {
+    let from = (caps[2].parse().ok()?, caps[3].parse().ok()?);
+    let to = numbers.get(&from)?;
+    Some(format!("{}{}.{}", &caps[1], to.0, to.1))
+}
+
+/// Renumber `### Story N.M` blocks in epics.md and move the moved story's
+/// block into its new epic section, ahead of any later-numbered story.
+pub fn rewrite_epics(content: &str, numbers: &HashMap<StoryRef, StoryRef>, moved: StoryRef) -> String {
+    let heading_regex = Regex::new(r"(?m)^(#{2,4}[ \t]*Story[ \t]*)(\d+)\.(\d+)").unwrap();
+    let renumbered_content = heading_regex
+        .replace_all(content, |caps: &Captures| {
+            renumbered(caps, numbers).unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string();
+
+    relocate_story_block(&renumbered_content, moved).unwrap_or(renumbered_content)
+}
+
+fn relocate_story_block(content: &str, story: StoryRef) -> Option<String> {
+    let block_regex = Regex::new(&format!(
+        r"(?m)^#{{2,4}}[ \t]*Story[ \t]*{}\.{}\b",
+        story.0, story.1
+    ))
+    .ok()?;
+    let block_start = block_regex.find(content)?.start();
+    // The block runs until a heading of the same or a higher level
+    let level = content[block_start..].chars().take_while(|c| *c == '#').count();
+    let next_heading = Regex::new(&format!(r"(?m)^#{{1,{}}}\s", level)).ok()?;
+
+    let after_heading = content[block_start..].find('\n').map(|i| block_start + i + 1)?;
+    let block_end = next_heading
+        .find(&content[after_heading..])
+        .map(|m| after_heading + m.start())
+        .unwrap_or(content.len());
+
+    let mut block = content[block_start..block_end].to_string();
+    if !block.ends_with('\n') {
+        block.push('\n');
+    }
+    let remaining = format!("{}{}", &content[..block_start], &content[block_end..]);
+
+    let epic_regex = Regex::new(&format!(r"(?m)^#{{2,3}}\s+Epic\s+{}:", story.0)).ok()?;
+    let any_epic_regex = Regex::new(r"(?m)^#{2,3}\s+Epic\s+\d+:").unwrap();
+    let epic_start = epic_regex.find(&remaining)?.end();
+    let epic_end = any_epic_regex
+        .find(&remaining[epic_start..])
+        .map(|m| epic_start + m.start())
+        .unwrap_or(remaining.len());
+
+    let story_regex = Regex::new(r"(?m)^#{2,4}[ \t]*Story[ \t]*(\d+)\.(\d+)").unwrap();
+    let insert_at = story_regex
+        .captures_iter(&remaining[epic_start..epic_end])
+        .find(|caps| {
+            caps[1].parse::<u32>().ok() == Some(story.0)
+                && caps[2].parse::<u32>().map(|n| n > story.1).unwrap_or(false)
+        })
+        .map(|caps| epic_start + caps.get(0).unwrap().start())
+        .unwrap_or(epic_end);
+
+    let (before, after) = remaining.split_at(insert_at);
+    if after.is_empty() {
+        block = format!("{}\n", block.trim_end_matches('\n'));
+    }
+    let separator = if before.ends_with("\n\n") || before.is_empty() {
+        ""
+    } else if before.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    let block_separator = if block.ends_with("\n\n") || after.is_empty() {
+        ""
+    } else {
+        "\n"
+    };
+
+    Some(format!("{}{}{}{}{}", before, separator, block, block_separator, after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut moves: Vec<(StoryRef, StoryRef)>) -> Vec<(StoryRef, StoryRef)> {
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn plain_move_needs_a_free_number() {
+        let existing = [(1, 1), (1, 2), (2, 1)];
+        assert_eq!(plan_moves(&existing, (1, 2), (2, 2), false), Ok(vec![((1, 2), (2, 2))]));
+        assert_eq!(plan_moves(&existing, (1, 2), (2, 1), false), Err((2, 1)));
+    }
+
+    #[test]
+    fn renumbering_within_an_epic() {
+        let existing = [(1, 1), (1, 2), (1, 3), (1, 4)];
+        assert_eq!(
+            sorted(plan_moves(&existing, (1, 4), (1, 2), true).unwrap()),
+            [((1, 2), (1, 3)), ((1, 3), (1, 4)), ((1, 4), (1, 2))]
+        );
+        assert_eq!(
+            sorted(plan_moves(&existing, (1, 1), (1, 3), true).unwrap()),
+            [((1, 1), (1, 3)), ((1, 2), (1, 1)), ((1, 3), (1, 2))]
+        );
+    }
+
+    #[test]
+    fn renumbering_across_epics() {
+        let existing = [(1, 1), (1, 2), (1, 3), (2, 1), (2, 2)];
+        assert_eq!(
+            sorted(plan_moves(&existing, (1, 2), (2, 2), true).unwrap()),
+            [((1, 2), (2, 2)), ((1, 3), (1, 2)), ((2, 2), (2, 3))]
+        );
+    }
+
+    #[test]
+    fn renamed_keys_keep_the_slug() {
+        assert_eq!(renamed_key("2-3-login-form", (4, 1)), "4-1-login-form");
+        assert_eq!(renamed_key("2-3", (4, 1)), "4-1");
+    }
+
+    #[test]
+    fn story_block_moves_to_its_new_epic() {
+        let epics = "\
+## Epic 1: Accounts
+
+#### Story 1.1: Setup
+
+Setup text.
+
+#### Story 1.2: Login
+
+Login text.
+
+## Epic 2: Reports
+
+#### Story 2.1: Export
+
+Export text.
+";
+        // The first story moves, so its block must stop at the next `####` story
+        let numbers = HashMap::from([((1, 1), (2, 2)), ((1, 2), (1, 1))]);
+        let updated = rewrite_epics(epics, &numbers, (2, 2));
+        assert_eq!(
+            updated,
+            "\
+## Epic 1: Accounts
+
+#### Story 1.1: Login
+
+Login text.
+
+## Epic 2: Reports
+
+#### Story 2.1: Export
+
+Export text.
+
+#### Story 2.2: Setup
+
+Setup text.
+"
+        );
+    }
+
+    #[test]
+    fn move_renames_story_files_and_skips_binary_markdown() {
+        use crate::scope::ProjectScope;
+        use crate::writer::WriteError;
+        use std::fs;
+
+        let project = std::env::temp_dir().join(format!("bmad-move-story-{}", uuid::Uuid::new_v4()));
+        let docs = project.join("docs");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(
+            docs.join("sprint-status.yaml"),
+            "development_status:\n  epic-1: in-progress\n  1-1-setup: done\n  1-2-login: review\n  1-3-logout: backlog\n",
+        )
+        .unwrap();
+        let stories = [("1-1-setup", "1.1: Setup"), ("1-2-login", "1.2: Login"), ("1-3-logout", "1.3: Logout")];
+        for (key, heading) in stories {
+            fs::write(docs.join(format!("{}.md", key)), format!("# Story {}\n", heading)).unwrap();
+        }
+        fs::write(docs.join("notes.md"), [0xff, 0xfe, b'1', b'.', b'3']).unwrap();
+        let mut scope = ProjectScope::default();
+        scope.register(&project).unwrap();
+
+        assert!(matches!(
+            crate::writer::move_story(&scope, &docs, "1.3", 1, Some(0), true),
+            Err(WriteError::InvalidStoryNumber(_))
+        ));
+
+        let renames = crate::writer::move_story(&scope, &docs, "1.3", 1, Some(1), true).unwrap();
+        assert_eq!(renames.len(), 3);
+        let mut files: Vec<String> = fs::read_dir(&docs)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name != "sprint-status.yaml")
+            .collect();
+        files.sort();
+        assert_eq!(files, ["1-1-logout.md", "1-2-setup.md", "1-3-login.md", "notes.md"]);
+        assert_eq!(
+            fs::read_to_string(docs.join("1-1-logout.md")).unwrap(),
+            "# Story 1.1: Logout\n"
+        );
+        assert_eq!(fs::read(docs.join("notes.md")).unwrap(), [0xff, 0xfe, b'1', b'.', b'3']);
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
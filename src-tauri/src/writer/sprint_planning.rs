@@ -0,0 +1,293 @@
+use super::create_story::kebab_case;
+use super::sprint_status::{story_numbers, SprintStatusFile};
+use super::WriteError;
+use crate::models::{Epic, StoryStatus};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Header written above `development_status` in a new sprint-status.yaml,
+/// as laid out by the sprint-planning workflow. `{name}` is the value on a
+/// single line for the comments, `{name_value}` the value as a YAML scalar.
+const SPRINT_STATUS_HEADER: &str = r#"# generated: {date}
+# project: {project}
+# project_key: {project_key}
+# tracking_system: file-system
+# story_location: {story_location}
+
+# STATUS DEFINITIONS:
+# ==================
+# Epic Status:
+#   - backlog: Epic not yet started
+#   - in-progress: Epic actively being worked on
+#   - done: All stories in epic completed
+#
+# Story Status:
+#   - backlog: Story only exists in epic file
+#   - ready-for-dev: Story file created in stories folder
+#   - in-progress: Developer actively working on implementation
+#   - review: Ready for code review (via Dev's code-review workflow)
+#   - done: Story completed
+#
+# Retrospective Status:
+#   - optional: Can be completed but not required
+#   - done: Retrospective has been completed
+
+generated: {date}
+project: {project_value}
+project_key: {project_key_value}
+tracking_system: file-system
+story_location: {story_location_value}
+
+development_status:
+"#;
+
+/// Keys touched while merging the epics into sprint-status.yaml
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    pub added_keys: Vec<String>,
+    pub upgraded_keys: Vec<String>,
+    pub unplanned_keys: Vec<String>,
+}
+
+/// Skeleton for a project without a sprint-status.yaml yet
+pub fn new_sprint_status(project: &str, story_location: &str, date: &str) -> String {
+    let project_key = kebab_case(project);
+    let values: HashMap<&str, &str> = HashMap::from([
+        ("date", date),
+        ("project", project),
+        ("project_key", project_key.as_str()),
+        ("story_location", story_location),
+    ]);
+
+    // One pass, so placeholders inside the values are left alone
+    let placeholder_regex = Regex::new(r"\{([a-z_]+?)(_value)?\}").unwrap();
+    placeholder_regex
+        .replace_all(SPRINT_STATUS_HEADER, |caps: &Captures| {
+            let value = values.get(&caps[1]).copied().unwrap_or_default();
+            if caps.get(2).is_some() {
+                yaml_scalar(value)
+            } else {
+                value.replace(['\r', '\n'], " ")
+            }
+        })
+        .to_string()
+}
+
+/// A string as a YAML scalar, quoted when it would otherwise read as
+/// something else (`:`, `#`, quotes, a number)
+fn yaml_scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|yaml| yaml.trim_end().to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// Bring sprint-status.yaml in line with the planned epics.
+///
+/// Missing epics, stories and retrospectives are added in order with
+/// default statuses. Existing statuses are never lowered; the only change
+/// to them is moving a backlog story to ready-for-dev once its story file
+/// exists. Stories no epic lists any more are reported, not removed.
+pub fn merge_epics(
+    sprint: &mut SprintStatusFile,
+    epics: &[Epic],
+    story_file_exists: impl Fn(&str) -> bool,
+) -> Result<MergeReport, WriteError> {
+    let mut report = MergeReport::default();
+    let mut planned = Vec::new();
+
+    for epic in epics {
+        let retrospective_key = format!("epic-{}-retrospective", epic.number);
+        let had_retrospective = sprint.has_key(&retrospective_key);
+
+        if sprint.epic_status(epic.number).is_none() {
+            sprint.insert_epic(epic.number)?;
+            report.added_keys.push(format!("epic-{}", epic.number));
+        }
+
+        for story in &epic.stories {
+            let story_number = match story
+                .number
+                .split_once('.')
+                .and_then(|(_, n)| n.parse::<u32>().ok())
+            {
+                Some(n) => n,
+                None => continue,
+            };
+            let short_key = format!("{}-{}", epic.number, story_number);
+            planned.push((epic.number, story_number));
+
+            let key = match sprint.resolve_story_key(&short_key) {
+                Some(key) => key,
+                None => {
+                    let slug = kebab_case(&story.title);
+                    let key = if slug.is_empty() {
+                        short_key
+                    } else {
+                        format!("{}-{}", short_key, slug)
+                    };
+                    sprint.insert_story(&key, &StoryStatus::Backlog)?;
+                    report.added_keys.push(key.clone());
+                    key
+                }
+            };
+
+            if sprint.story_status(&key) == Some(StoryStatus::Backlog) && story_file_exists(&key) {
+                sprint.set_story_status(&key, &StoryStatus::ReadyForDev)?;
+                report.upgraded_keys.push(key);
+            }
+        }
+
+        sprint.insert_retrospective(epic.number)?;
+        if !had_retrospective && sprint.has_key(&retrospective_key) {
+            report.added_keys.push(retrospective_key);
+        }
+    }
+
+    report.unplanned_keys = sprint
+        .all_story_keys()
+        .into_iter()
+        .filter(|key| {
+            story_numbers(key)
+                .map(|numbers| !planned.contains(&numbers))
+                .unwrap_or(true)
+        })
+        .collect();
+
+    Ok(report)
+}
+
+/// `story_location` value for the header, relative to the project root when possible
+pub fn story_location_value(project_path: &Path, story_dir: &Path) -> String {
+    match story_dir.strip_prefix(project_path) {
+        Ok(relative) if relative.as_os_str().is_empty() => "{project-root}".to_string(),
+        Ok(relative) => format!("{{project-root}}/{}", relative.to_string_lossy().replace('\\', "/")),
+        Err(_) => story_dir.to_string_lossy().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EpicStatus, Story};
+    use crate::parser::ProjectOverrides;
+
+    const SPRINT: &str = "\
+development_status:
+  epic-1: in-progress
+  1-1-setup: done
+  1-2-login: backlog
+  1-9-legacy: review
+  epic-1-retrospective: optional
+";
+
+    fn story(epic: u32, number: u32, title: &str) -> Story {
+        Story {
+            id: format!("{}-{}", epic, number),
+            epic_id: format!("epic-{}", epic),
+            number: format!("{}.{}", epic, number),
+            title: title.to_string(),
+            user_type: String::new(),
+            capability: String::new(),
+            value_benefit: String::new(),
+            acceptance_criteria: Vec::new(),
+            status: StoryStatus::Backlog,
+            blocked: false,
+            tasks: Vec::new(),
+            file_path: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn epic(number: u32, stories: Vec<Story>) -> Epic {
+        Epic {
+            id: format!("epic-{}", number),
+            number,
+            title: format!("Epic {}", number),
+            goal: String::new(),
+            stories,
+            status: EpicStatus::Backlog,
+            retrospective: None,
+            file_path: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn planned_epics() -> Vec<Epic> {
+        vec![
+            epic(1, vec![story(1, 1, "Setup"), story(1, 2, "Login"), story(1, 3, "Password Reset")]),
+            epic(2, vec![story(2, 1, "Search")]),
+        ]
+    }
+
+    #[test]
+    fn adds_missing_epics_stories_and_retrospectives() {
+        let mut sprint = SprintStatusFile::parse(SPRINT, ProjectOverrides::default()).unwrap();
+        let report = merge_epics(&mut sprint, &planned_epics(), |_| false).unwrap();
+
+        assert_eq!(
+            report.added_keys,
+            ["1-3-password-reset", "epic-2", "2-1-search", "epic-2-retrospective"]
+        );
+        assert_eq!(sprint.story_status("1-3-password-reset"), Some(StoryStatus::Backlog));
+        assert_eq!(sprint.epic_status(2), Some(EpicStatus::Backlog));
+        assert!(sprint.has_key("epic-2-retrospective"));
+        assert!(report.upgraded_keys.is_empty());
+    }
+
+    #[test]
+    fn backlog_story_with_a_file_becomes_ready_for_dev() {
+        let mut sprint = SprintStatusFile::parse(SPRINT, ProjectOverrides::default()).unwrap();
+        let report = merge_epics(&mut sprint, &planned_epics(), |key| {
+            key == "1-1-setup" || key == "1-2-login"
+        })
+        .unwrap();
+
+        assert_eq!(report.upgraded_keys, ["1-2-login"]);
+        assert_eq!(sprint.story_status("1-2-login"), Some(StoryStatus::ReadyForDev));
+        // A story further along is never moved back
+        assert_eq!(sprint.story_status("1-1-setup"), Some(StoryStatus::Done));
+        assert_eq!(sprint.epic_status(1), Some(EpicStatus::InProgress));
+    }
+
+    #[test]
+    fn stories_no_epic_lists_are_reported_not_removed() {
+        let mut sprint = SprintStatusFile::parse(SPRINT, ProjectOverrides::default()).unwrap();
+        let report = merge_epics(&mut sprint, &planned_epics(), |_| false).unwrap();
+
+        assert_eq!(report.unplanned_keys, ["1-9-legacy"]);
+        assert_eq!(sprint.story_status("1-9-legacy"), Some(StoryStatus::Review));
+    }
+
+    #[test]
+    fn merging_twice_changes_nothing() {
+        let mut sprint = SprintStatusFile::parse(SPRINT, ProjectOverrides::default()).unwrap();
+        merge_epics(&mut sprint, &planned_epics(), |_| true).unwrap();
+        let merged = sprint.to_string();
+
+        let report = merge_epics(&mut sprint, &planned_epics(), |_| true).unwrap();
+        assert!(report.added_keys.is_empty());
+        assert!(report.upgraded_keys.is_empty());
+        assert_eq!(sprint.to_string(), merged);
+    }
+
+    #[test]
+    fn header_values_survive_yaml_special_characters() {
+        let project = "Acme: \"v2\" # beta\nnext";
+        let location = "{project-root}/docs/\"stories\"";
+        let content = new_sprint_status(project, location, "2026-01-02 10:00");
+
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&content).unwrap();
+        assert_eq!(yaml["project"].as_str(), Some(project));
+        assert_eq!(yaml["story_location"].as_str(), Some(location));
+        assert_eq!(yaml["project_key"].as_str(), Some("acme-v2-beta-next"));
+        assert!(content.contains("# project: Acme: \"v2\" # beta next\n"));
+
+        let mut sprint = SprintStatusFile::parse(&content, ProjectOverrides::default()).unwrap();
+        merge_epics(&mut sprint, &planned_epics(), |_| false).unwrap();
+        let merged: serde_yaml::Value = serde_yaml::from_str(&sprint.to_string()).unwrap();
+        assert_eq!(merged["project"].as_str(), Some(project));
+    }
+}
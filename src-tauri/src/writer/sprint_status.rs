@@ -0,0 +1,709 @@
+use super::WriteError;
+use crate::models::{EpicStatus, StoryStatus};
+use crate::parser::ProjectOverrides;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A `key: value` line inside the `development_status` block
+#[derive(Debug, Clone)]
+struct Entry {
+    line: usize,
+    indent: usize,
+    key: String,
+    /// Byte range of the scalar value within the line (empty for nested epic headers)
+    value: (usize, usize),
+    /// Epic key this entry is nested under (nested layout only)
+    parent: Option<String>,
+}
+
+impl Entry {
+    fn is_header(&self) -> bool {
+        self.value.0 == self.value.1
+    }
+}
+
+/// sprint-status.yaml kept as raw lines so edits preserve comments,
+/// key order and the header block.
+///
+/// Understands both layouts `parse_sprint_status` accepts:
+/// flat (`epic-1: in-progress` followed by `1-1-setup: done` siblings) and
+/// nested (`epic-1:` mapping holding `status:` and its story keys).
+/// Statuses are read and written in the project's own words when its
+/// `.bmad-dash.yaml` maps custom ones to the built-in statuses.
+pub struct SprintStatusFile {
+    lines: Vec<String>,
+    entries: Vec<Entry>,
+    /// Line index of the `development_status:` key
+    block_start: usize,
+    overrides: ProjectOverrides,
+}
+
+impl SprintStatusFile {
+    pub fn load(path: &Path, overrides: ProjectOverrides) -> Result<Self, WriteError> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content, overrides)
+    }
+
+    pub fn parse(content: &str, overrides: ProjectOverrides) -> Result<Self, WriteError> {
+        let mut file = Self {
+            lines: content.split_inclusive('\n').map(String::from).collect(),
+            entries: Vec::new(),
+            block_start: 0,
+            overrides,
+        };
+        file.index()?;
+        Ok(file)
+    }
+
+    fn index(&mut self) -> Result<(), WriteError> {
+        self.entries.clear();
+
+        let start = self
+            .lines
+            .iter()
+            .position(|line| {
+                parse_entry(strip_eol(line))
+                    .map(|(key, _)| key == "development_status")
+                    .unwrap_or(false)
+                    && !line.starts_with(char::is_whitespace)
+            })
+            .ok_or_else(|| {
+                WriteError::InvalidSprintStatus("missing development_status block".into())
+            })?;
+        self.block_start = start;
+
+        let mut top_indent: Option<usize> = None;
+        let mut current_epic: Option<String> = None;
+
+        for (index, line) in self.lines.iter().enumerate().skip(start + 1) {
+            let content = strip_eol(line);
+            let trimmed = content.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let indent = content.len() - trimmed.len();
+            if indent == 0 {
+                break;
+            }
+
+            let (key, value) = match parse_entry(content) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+
+            let top = *top_indent.get_or_insert(indent);
+            let entry = if indent <= top {
+                let entry = Entry {
+                    line: index,
+                    indent,
+                    key,
+                    value,
+                    parent: None,
+                };
+                current_epic = if entry.is_header() {
+                    Some(entry.key.clone())
+                } else {
+                    None
+                };
+                entry
+            } else if let Some(epic_key) = &current_epic {
+                Entry {
+                    line: index,
+                    indent,
+                    key,
+                    value,
+                    parent: Some(epic_key.clone()),
+                }
+            } else {
+                continue;
+            };
+
+            self.entries.push(entry);
+        }
+
+        Ok(())
+    }
+
+    fn value_of(&self, entry: &Entry) -> &str {
+        let raw = &self.lines[entry.line][entry.value.0..entry.value.1];
+        raw.trim_matches(|c| c == '"' || c == '\'')
+    }
+
+    fn is_story_entry(entry: &Entry) -> bool {
+        if entry.is_header() {
+            return false;
+        }
+        match entry.parent {
+            Some(_) => entry.key != "status" && entry.key != "retrospective",
+            None => !entry.key.starts_with("epic-"),
+        }
+    }
+
+    fn find_story(&self, story: &str) -> Option<&Entry> {
+        let stories = || self.entries.iter().filter(|e| Self::is_story_entry(e));
+
+        if let Some(entry) = stories().find(|e| e.key == story) {
+            return Some(entry);
+        }
+
+        // Accept "2.3" or "2-3" as shorthand for "2-3-some-title"
+        let short = story.replace('.', "-");
+        let prefix = format!("{}-", short);
+        stories().find(|e| e.key == short || e.key.starts_with(&prefix))
+    }
+
+    fn find_epic(&self, epic_number: u32) -> Option<&Entry> {
+        let epic_key = format!("epic-{}", epic_number);
+        self.entries
+            .iter()
+            .find(|e| e.parent.is_none() && e.key == epic_key)
+    }
+
+    fn epic_status_entry(&self, epic_number: u32) -> Option<&Entry> {
+        let epic = self.find_epic(epic_number)?;
+        if !epic.is_header() {
+            return Some(epic);
+        }
+        self.entries
+            .iter()
+            .find(|e| e.parent.as_deref() == Some(epic.key.as_str()) && e.key == "status")
+    }
+
+    /// Full sprint-status key for a story given its key, "N.M" or "N-M"
+    pub fn resolve_story_key(&self, story: &str) -> Option<String> {
+        self.find_story(story).map(|e| e.key.clone())
+    }
+
+    pub fn has_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|e| e.key == key)
+    }
+
+    /// Every story key in the file, in file order
+    pub fn all_story_keys(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| Self::is_story_entry(e))
+            .map(|e| e.key.clone())
+            .collect()
+    }
+
+    pub fn story_status(&self, story: &str) -> Option<StoryStatus> {
+        self.find_story(story)
+            .map(|e| self.overrides.story_status(self.value_of(e)))
+    }
+
+    pub fn epic_status(&self, epic_number: u32) -> Option<EpicStatus> {
+        match self.epic_status_entry(epic_number) {
+            Some(entry) => Some(self.overrides.epic_status(self.value_of(entry))),
+            None => self.find_epic(epic_number).map(|_| EpicStatus::Backlog),
+        }
+    }
+
+    fn epic_story_entries(&self, epic_number: u32) -> Vec<&Entry> {
+        self.entries
+            .iter()
+            .filter(|e| Self::is_story_entry(e))
+            .filter(|e| story_numbers(&e.key).map(|(epic, _)| epic) == Some(epic_number))
+            .collect()
+    }
+
+    /// Story keys belonging to an epic, in file order
+    pub fn story_keys(&self, epic_number: u32) -> Vec<String> {
+        self.epic_story_entries(epic_number)
+            .into_iter()
+            .map(|e| e.key.clone())
+            .collect()
+    }
+
+    /// Add a story key next to its siblings, ordered by story number.
+    ///
+    /// Epics missing from the file are appended at the end of
+    /// `development_status` in the layout the file already uses, together
+    /// with their retrospective entry.
+    pub fn insert_story(&mut self, story_key: &str, status: &StoryStatus) -> Result<(), WriteError> {
+        let value = self.overrides.story_status_value(status).to_string();
+        self.insert_story_value(story_key, &value)
+    }
+
+    fn insert_story_value(&mut self, story_key: &str, value: &str) -> Result<(), WriteError> {
+        let (epic_number, story_number) = story_numbers(story_key).ok_or_else(|| {
+            WriteError::InvalidSprintStatus(format!("not a story key: {}", story_key))
+        })?;
+        if self.entries.iter().any(|e| e.key == story_key) {
+            return Ok(());
+        }
+
+        let top_indent = self
+            .entries
+            .iter()
+            .find(|e| e.parent.is_none())
+            .map(|e| e.indent)
+            .unwrap_or(2);
+
+        if let Some(epic) = self.find_epic(epic_number).cloned() {
+            let siblings = self.epic_story_entries(epic_number);
+            let previous = siblings
+                .iter()
+                .filter(|e| story_numbers(&e.key).map(|(_, n)| n < story_number).unwrap_or(false))
+                .map(|e| e.line)
+                .max();
+
+            let (anchor, indent) = if epic.is_header() {
+                let children: Vec<&Entry> = self
+                    .entries
+                    .iter()
+                    .filter(|e| e.parent.as_deref() == Some(epic.key.as_str()))
+                    .collect();
+                let indent = children.first().map(|e| e.indent).unwrap_or(epic.indent + 2);
+                let status_line = children.iter().find(|e| e.key == "status").map(|e| e.line);
+                (previous.or(status_line).unwrap_or(epic.line), indent)
+            } else {
+                (previous.unwrap_or(epic.line), top_indent)
+            };
+
+            self.insert_line(
+                anchor + 1,
+                &format!("{}{}: {}", " ".repeat(indent), story_key, value),
+            );
+            return self.index();
+        }
+
+        self.insert_epic(epic_number)?;
+        self.insert_story_value(story_key, value)
+    }
+
+    /// Add an epic entry (with its retrospective in the flat layout) if missing.
+    ///
+    /// The epic goes ahead of the next higher-numbered epic, or at the end of
+    /// `development_status`, in the layout the file already uses.
+    pub fn insert_epic(&mut self, epic_number: u32) -> Result<(), WriteError> {
+        if self.find_epic(epic_number).is_some() {
+            return Ok(());
+        }
+
+        let top_indent = self
+            .entries
+            .iter()
+            .find(|e| e.parent.is_none())
+            .map(|e| e.indent)
+            .unwrap_or(2);
+        let nested = self.entries.iter().any(|e| e.parent.is_none() && e.is_header());
+        let indent = " ".repeat(top_indent);
+        let child_indent = " ".repeat(top_indent + 2);
+
+        let next_epic = self
+            .entries
+            .iter()
+            .filter(|e| e.parent.is_none())
+            .filter_map(|e| epic_number_of(&e.key).map(|n| (n, e.line)))
+            .filter(|(n, _)| *n > epic_number)
+            .min();
+
+        let backlog = self.overrides.epic_status_value(&EpicStatus::Backlog);
+        let mut new_lines = Vec::new();
+        if nested {
+            new_lines.push(format!("{}epic-{}:", indent, epic_number));
+            new_lines.push(format!("{}status: {}", child_indent, backlog));
+        } else {
+            new_lines.push(format!("{}epic-{}: {}", indent, epic_number, backlog));
+            new_lines.push(format!("{}epic-{}-retrospective: optional", indent, epic_number));
+        }
+
+        let at = match next_epic {
+            Some((_, line)) => {
+                if !nested {
+                    new_lines.push(String::new());
+                }
+                line
+            }
+            None => {
+                if !nested && !self.entries.is_empty() {
+                    new_lines.insert(0, String::new());
+                }
+                self.entries
+                    .last()
+                    .map(|e| e.line)
+                    .unwrap_or(self.block_start)
+                    + 1
+            }
+        };
+
+        for (offset, line) in new_lines.iter().enumerate() {
+            self.insert_line(at + offset, line);
+        }
+        self.index()
+    }
+
+    /// Add `epic-N-retrospective: optional` after the epic's last story (flat layout only)
+    pub fn insert_retrospective(&mut self, epic_number: u32) -> Result<(), WriteError> {
+        let epic = self
+            .find_epic(epic_number)
+            .cloned()
+            .ok_or(WriteError::UnknownEpic(epic_number))?;
+        let retrospective_key = format!("epic-{}-retrospective", epic_number);
+        if epic.is_header() || self.entries.iter().any(|e| e.key == retrospective_key) {
+            return Ok(());
+        }
+
+        let anchor = self
+            .epic_story_entries(epic_number)
+            .iter()
+            .map(|e| e.line)
+            .max()
+            .unwrap_or(epic.line);
+        self.insert_line(
+            anchor + 1,
+            &format!("{}{}: optional", " ".repeat(epic.indent), retrospective_key),
+        );
+        self.index()
+    }
+
+    /// Rename story keys (possibly across epics), keeping their status values.
+    ///
+    /// All renamed entries are lifted out first and re-inserted in story
+    /// order, so chains like 2-3 -> 2-4 -> 2-5 land in the right place.
+    pub fn rename_stories(&mut self, renames: &[(String, String)]) -> Result<(), WriteError> {
+        let mut lifted = Vec::new();
+        for (old_key, new_key) in renames {
+            let entry = self
+                .entries
+                .iter()
+                .find(|e| Self::is_story_entry(e) && e.key == *old_key)
+                .ok_or_else(|| WriteError::UnknownStory(old_key.clone()))?;
+            let value = self.lines[entry.line][entry.value.0..entry.value.1].to_string();
+            lifted.push((entry.line, new_key.clone(), value));
+        }
+
+        lifted.sort_by_key(|(line, _, _)| std::cmp::Reverse(*line));
+        for (line, _, _) in &lifted {
+            self.lines.remove(*line);
+        }
+        self.index()?;
+
+        lifted.sort_by_key(|(_, key, _)| story_numbers(key));
+        for (_, new_key, value) in lifted {
+            self.insert_story_value(&new_key, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Update a story's status, returning the resolved sprint-status key
+    pub fn set_story_status(
+        &mut self,
+        story: &str,
+        status: &StoryStatus,
+    ) -> Result<String, WriteError> {
+        let entry = self
+            .find_story(story)
+            .cloned()
+            .ok_or_else(|| WriteError::UnknownStory(story.to_string()))?;
+
+        let value = self.overrides.story_status_value(status).to_string();
+        self.replace_value(&entry, &value)?;
+        Ok(entry.key)
+    }
+
+    pub fn set_epic_status(
+        &mut self,
+        epic_number: u32,
+        status: &EpicStatus,
+    ) -> Result<(), WriteError> {
+        if let Some(entry) = self.epic_status_entry(epic_number).cloned() {
+            let value = self.overrides.epic_status_value(status).to_string();
+            return self.replace_value(&entry, &value);
+        }
+
+        // Nested epic without a `status:` key yet - add one as its first child
+        let header = self
+            .find_epic(epic_number)
+            .cloned()
+            .ok_or(WriteError::UnknownEpic(epic_number))?;
+        let child_indent = self
+            .entries
+            .iter()
+            .find(|e| e.parent.as_deref() == Some(header.key.as_str()))
+            .map(|e| e.indent)
+            .unwrap_or(header.indent + 2);
+
+        self.insert_line(
+            header.line + 1,
+            &format!(
+                "{}status: {}",
+                " ".repeat(child_indent),
+                self.overrides.epic_status_value(status)
+            ),
+        );
+        self.index()
+    }
+
+    fn replace_value(&mut self, entry: &Entry, value: &str) -> Result<(), WriteError> {
+        let line = &self.lines[entry.line];
+        let (start, end) = entry.value;
+        let quote = line[start..end]
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'');
+
+        let replacement = match quote {
+            Some(q) => format!("{}{}{}", q, value, q),
+            None => value.to_string(),
+        };
+
+        let updated = format!("{}{}{}", &line[..start], replacement, &line[end..]);
+        self.lines[entry.line] = updated;
+        self.index()
+    }
+
+    /// Insert a raw line (without terminator) before `at`, matching the file's line endings
+    fn insert_line(&mut self, at: usize, text: &str) {
+        let eol = self
+            .lines
+            .iter()
+            .find(|l| l.ends_with('\n'))
+            .map(|l| if l.ends_with("\r\n") { "\r\n" } else { "\n" })
+            .unwrap_or("\n");
+        let trailing_newline = self.lines.last().map(|l| l.ends_with('\n')).unwrap_or(true);
+
+        let at = at.min(self.lines.len());
+        if at > 0 {
+            let previous = &mut self.lines[at - 1];
+            if !previous.ends_with('\n') {
+                previous.push_str(eol);
+            }
+        }
+
+        let terminator = if at == self.lines.len() && !trailing_newline {
+            ""
+        } else {
+            eol
+        };
+        self.lines.insert(at, format!("{}{}", text, terminator));
+    }
+}
+
+impl fmt::Display for SprintStatusFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            f.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Epic and story numbers from a story key like "2-3-login-form"
+pub fn story_numbers(story_key: &str) -> Option<(u32, u32)> {
+    let mut parts = story_key.split('-');
+    let epic = parts.next()?.parse().ok()?;
+    let story = parts.next()?.parse().ok()?;
+    Some((epic, story))
+}
+
+/// Epic number from an epic key like "epic-2" (not "epic-2-retrospective")
+fn epic_number_of(key: &str) -> Option<u32> {
+    key.strip_prefix("epic-")?.parse().ok()
+}
+
+fn strip_eol(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+/// Split a `key: value  # comment` line into its key and the byte range of the value
+fn parse_entry(line: &str) -> Option<(String, (usize, usize))> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    if trimmed.starts_with('#') || trimmed.starts_with('-') {
+        return None;
+    }
+
+    let (key, after_key) = if let Some(quote) = trimmed.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let close = trimmed[1..].find(quote)? + 1;
+        (&trimmed[1..close], close + 1)
+    } else {
+        let colon = trimmed
+            .char_indices()
+            .find(|(i, c)| {
+                *c == ':'
+                    && trimmed[i + 1..]
+                        .chars()
+                        .next()
+                        .map(char::is_whitespace)
+                        .unwrap_or(true)
+            })
+            .map(|(i, _)| i)?;
+        (trimmed[..colon].trim_end(), colon)
+    };
+
+    let rest = &trimmed[after_key..];
+    let rest = rest.strip_prefix(':')?;
+    let value_offset = indent + after_key + 1 + (rest.len() - rest.trim_start().len());
+    let value_text = rest.trim_start();
+
+    let value_len = match value_text.chars().next() {
+        Some(q) if q == '"' || q == '\'' => value_text[1..].find(q).map(|i| i + 2).unwrap_or(value_text.len()),
+        Some('#') | None => 0,
+        Some(_) => {
+            let end = value_text.find(" #").unwrap_or(value_text.len());
+            value_text[..end].trim_end().len()
+        }
+    };
+
+    Some((
+        key.to_string(),
+        (value_offset, value_offset + value_len),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAT: &str = "\
+# generated: 2025-01-10
+project: demo
+
+development_status:
+  # Epic 1
+  epic-1: in-progress
+  1-1-setup: done   # shipped
+  1-2-login: \"ready-for-dev\"
+  epic-1-retrospective: optional
+
+  epic-3: backlog
+  3-1-reports: backlog
+";
+
+    const NESTED: &str = "\
+development_status:
+  epic-1:
+    status: in-progress
+    1-1-setup: done
+    1-2-login: 'review'
+  epic-2:
+    2-1-search: backlog
+";
+
+    fn edit(content: &str, change: impl FnOnce(&mut SprintStatusFile)) -> String {
+        let mut file = SprintStatusFile::parse(content, ProjectOverrides::default()).unwrap();
+        change(&mut file);
+        file.to_string()
+    }
+
+    #[test]
+    fn untouched_file_round_trips() {
+        assert_eq!(edit(FLAT, |_| {}), FLAT);
+        assert_eq!(edit(NESTED, |_| {}), NESTED);
+    }
+
+    #[test]
+    fn flat_story_status_keeps_comments_and_quotes() {
+        let updated = edit(FLAT, |file| {
+            file.set_story_status("1.1", &StoryStatus::Review).unwrap();
+            file.set_story_status("1-2-login", &StoryStatus::InProgress).unwrap();
+        });
+        assert!(updated.contains("  1-1-setup: review   # shipped\n"));
+        assert!(updated.contains("  1-2-login: \"in-progress\"\n"));
+        assert!(updated.starts_with("# generated: 2025-01-10\n"));
+        assert!(updated.contains("  # Epic 1\n"));
+    }
+
+    #[test]
+    fn nested_statuses() {
+        let file = SprintStatusFile::parse(NESTED, ProjectOverrides::default()).unwrap();
+        assert_eq!(file.story_status("1.2"), Some(StoryStatus::Review));
+        assert_eq!(file.epic_status(1), Some(EpicStatus::InProgress));
+        assert_eq!(file.epic_status(2), Some(EpicStatus::Backlog));
+        assert_eq!(file.all_story_keys(), ["1-1-setup", "1-2-login", "2-1-search"]);
+
+        let updated = edit(NESTED, |file| {
+            file.set_story_status("1.2", &StoryStatus::Done).unwrap();
+            file.set_epic_status(2, &EpicStatus::InProgress).unwrap();
+        });
+        assert!(updated.contains("    1-2-login: 'done'\n"));
+        assert!(updated.contains("  epic-2:\n    status: in-progress\n    2-1-search: backlog\n"));
+    }
+
+    #[test]
+    fn inserts_follow_the_layout() {
+        let flat = edit(FLAT, |file| {
+            file.insert_story("1-3-logout", &StoryStatus::Backlog).unwrap();
+            file.insert_story("2-1-search", &StoryStatus::Backlog).unwrap();
+        });
+        assert!(flat.contains("  1-2-login: \"ready-for-dev\"\n  1-3-logout: backlog\n"));
+        assert!(flat.contains(
+            "  epic-2: backlog\n  2-1-search: backlog\n  epic-2-retrospective: optional\n\n  epic-3: backlog\n"
+        ));
+
+        let nested = edit(NESTED, |file| {
+            file.insert_story("1-3-logout", &StoryStatus::Backlog).unwrap();
+            file.insert_story("3-1-reports", &StoryStatus::Backlog).unwrap();
+        });
+        assert!(nested.contains("    1-2-login: 'review'\n    1-3-logout: backlog\n"));
+        assert!(nested.ends_with("  epic-3:\n    status: backlog\n    3-1-reports: backlog\n"));
+    }
+
+    #[test]
+    fn renames_keep_values() {
+        let updated = edit(FLAT, |file| {
+            file.rename_stories(&[
+                ("1-1-setup".into(), "1-2-setup".into()),
+                ("1-2-login".into(), "1-3-login".into()),
+            ])
+            .unwrap();
+        });
+        assert!(updated.contains("  1-2-setup: done\n  1-3-login: \"ready-for-dev\"\n"));
+        assert!(!updated.contains("1-1-setup"));
+    }
+
+    #[test]
+    fn crlf_endings_are_kept() {
+        let content = FLAT.replace('\n', "\r\n");
+        let updated = edit(&content, |file| {
+            file.insert_story("1-3-logout", &StoryStatus::Backlog).unwrap();
+        });
+        assert!(updated.contains("  1-3-logout: backlog\r\n"));
+        assert!(!updated.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn missing_block_is_an_error() {
+        assert!(SprintStatusFile::parse("project: demo\n", ProjectOverrides::default()).is_err());
+    }
+
+    #[test]
+    fn custom_status_words_are_read_and_written() {
+        let overrides = ProjectOverrides::parse(
+            Path::new("/project"),
+            "statuses:\n  story: { todo: backlog, doing: in-progress, qa: review }\n  epic: { active: in-progress }\n",
+        )
+        .unwrap();
+        let content = "\
+development_status:
+  epic-1: active
+  1-1-setup: qa
+  1-2-login: todo
+";
+        let mut file = SprintStatusFile::parse(content, overrides).unwrap();
+        assert_eq!(file.story_status("1-1"), Some(StoryStatus::Review));
+        assert_eq!(file.epic_status(1), Some(EpicStatus::InProgress));
+
+        file.set_story_status("1-2", &StoryStatus::InProgress).unwrap();
+        file.set_story_status("1-1", &StoryStatus::Done).unwrap();
+        file.insert_story("1-3-logout", &StoryStatus::Backlog).unwrap();
+        file.insert_epic(2).unwrap();
+        assert_eq!(
+            file.to_string(),
+            "\
+development_status:
+  epic-1: active
+  1-1-setup: done
+  1-2-login: doing
+  1-3-logout: todo
+
+  epic-2: backlog
+  epic-2-retrospective: optional
+"
+        );
+    }
+}
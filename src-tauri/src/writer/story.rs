@@ -0,0 +1,204 @@
+use super::sprint_status::story_numbers;
+use crate::parser::{BmadParser, ProjectOverrides};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Find the markdown file for a sprint-status story key: `<key>.md`
+/// (e.g. "2-3-login-form.md"), else a file the project's story pattern
+/// gives the same epic and story number
+pub fn find_story_file(bmad_dir: &Path, overrides: &ProjectOverrides, story_key: &str) -> Option<PathBuf> {
+    let filename = format!("{}.md", story_key);
+    let numbers = story_numbers(story_key);
+
+    let files: Vec<PathBuf> = story_roots(bmad_dir, overrides)
+        .iter()
+        .flat_map(|root| WalkDir::new(root).max_depth(4))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && !overrides.is_ignored(e.path()))
+        .map(|e| e.into_path())
+        .collect();
+    let name = |path: &PathBuf| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    files
+        .iter()
+        .find(|path| name(path) == filename)
+        .or_else(|| {
+            files
+                .iter()
+                .find(|path| numbers.is_some() && overrides.story_numbers(&name(path)) == numbers)
+        })
+        .cloned()
+}
+
+/// Folders holding story files: the docs folder, plus the story folder
+/// (the `stories` folder from `.bmad-dash.yaml`, or where `create_story`
+/// writes) when that lives elsewhere
+pub fn story_roots(bmad_dir: &Path, overrides: &ProjectOverrides) -> Vec<PathBuf> {
+    let mut roots = vec![bmad_dir.to_path_buf()];
+    let story_dir = overrides
+        .stories_dir
+        .clone()
+        .filter(|dir| dir.is_dir())
+        .or_else(|| {
+            BmadParser::project_root(bmad_dir)
+                .map(|project_path| BmadParser::find_story_dir(&project_path, bmad_dir))
+        });
+    if let Some(story_dir) = story_dir.filter(|dir| dir.is_dir() && !dir.starts_with(bmad_dir)) {
+        roots.push(story_dir);
+    }
+    roots
+}
+
+/// Rewrite the `Status:` line at the top of a story file with a status
+/// value as the project writes it (see `ProjectOverrides::story_status_value`).
+///
+/// Only the value is replaced; if the story has no status line one is added
+/// below the `# Story N.M` heading, matching the create-story template.
+pub fn set_status_line(content: &str, status: &str) -> String {
+    let status_regex = Regex::new(r"(?m)^(\*{0,2}Status\*{0,2}:\*{0,2}[ \t]*)([^\r\n]*)").unwrap();
+
+    if let Some(caps) = status_regex.captures(content) {
+        let value = caps.get(2).unwrap();
+        return format!(
+            "{}{}{}",
+            &content[..value.start()],
+            status,
+            &content[value.end()..]
+        );
+    }
+
+    let eol = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let heading_regex = Regex::new(r"(?m)^#[ \t]+[^\r\n]*").unwrap();
+    match heading_regex.find(content) {
+        Some(heading) => format!(
+            "{}{}{}Status: {}{}",
+            &content[..heading.end()],
+            eol,
+            eol,
+            status,
+            &content[heading.end()..]
+        ),
+        None => format!("Status: {}{}{}{}", status, eol, eol, content),
+    }
+}
+
+/// Tick or untick a task checkbox addressed by its task id.
+///
+/// Only the checkbox mark changes; `completed: None` flips the current state.
+/// Returns `None` if the story has no task with that id.
+pub fn set_task_completed(content: &str, task_id: &str, completed: Option<bool>) -> Option<String> {
+    let task = BmadParser::task_lines(content)
+        .into_iter()
+        .find(|t| t.id == task_id)?;
+
+    let completed = completed.unwrap_or(!task.completed);
+    if completed == task.completed {
+        return Some(content.to_string());
+    }
+
+    let mark = if completed { "x" } else { " " };
+
+    Some(format!(
+        "{}{}{}",
+        &content[..task.mark_offset],
+        mark,
+        &content[task.mark_offset + 1..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORY: &str = "\
+# Story 1.2: Login
+
+Status: in-progress
+
+## Tasks / Subtasks
+
+- [ ] Task 1: Build the form (AC: 1)
+  - [x] 1.1 Fields
+  - [ ] 1.2 Validation
+- [x] Wire the API
+  - [ ] Call the endpoint
+- [ ] Task 1: Duplicate number
+
+## Dev Notes
+
+- [ ] Not a task
+";
+
+    fn ids(content: &str) -> Vec<(String, bool, usize)> {
+        BmadParser::task_lines(content)
+            .into_iter()
+            .map(|t| (t.id, t.completed, t.depth))
+            .collect()
+    }
+
+    #[test]
+    fn task_ids_use_numbering_then_position() {
+        assert_eq!(
+            ids(STORY),
+            [
+                ("1".to_string(), false, 0),
+                ("1.1".to_string(), true, 1),
+                ("1.2".to_string(), false, 1),
+                ("2".to_string(), true, 0),
+                ("2.1".to_string(), false, 1),
+                // "Task 1" is taken, so the position is used
+                ("3".to_string(), false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn task_ids_survive_edits_elsewhere() {
+        let edited = STORY.replace("# Story 1.2: Login", "# Story 1.2: Login\n\nSome intro text.");
+        assert_eq!(ids(&edited), ids(STORY));
+    }
+
+    #[test]
+    fn toggling_changes_only_the_mark() {
+        let ticked = set_task_completed(STORY, "1.2", None).unwrap();
+        assert_eq!(ticked, STORY.replace("- [ ] 1.2 Validation", "- [x] 1.2 Validation"));
+
+        let unticked = set_task_completed(&ticked, "1.2", Some(false)).unwrap();
+        assert_eq!(unticked, STORY);
+        assert_eq!(set_task_completed(STORY, "2", Some(true)).unwrap(), STORY);
+        assert!(set_task_completed(STORY, "9", None).is_none());
+    }
+
+    #[test]
+    fn story_files_are_found_by_the_project_pattern() {
+        let project = std::env::temp_dir().join(format!("bmad-story-{}", uuid::Uuid::new_v4()));
+        let docs = project.join("docs");
+        let stories = project.join("work/stories");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::create_dir_all(&stories).unwrap();
+        std::fs::write(
+            project.join(crate::parser::OVERRIDES_FILE),
+            "stories: work/stories\npatterns:\n  story: '^S(?P<epic>\\d+)\\.(?P<story>\\d+)-.+\\.md$'\n",
+        )
+        .unwrap();
+        std::fs::write(stories.join("S2.3-login.md"), "# Story 2.3: Login\n").unwrap();
+
+        let overrides = ProjectOverrides::for_docs_dir(&docs).unwrap();
+        assert_eq!(story_roots(&docs, &overrides), [docs.clone(), stories.clone()]);
+        assert_eq!(
+            find_story_file(&docs, &overrides, "2-3-login-form"),
+            Some(stories.join("S2.3-login.md"))
+        );
+        assert_eq!(find_story_file(&docs, &overrides, "2-4-logout"), None);
+        assert_eq!(
+            overrides.renamed_story_file("S2.3-login.md", (4, 1)).as_deref(),
+            Some("S4.1-login.md")
+        );
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+}
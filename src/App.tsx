@@ -7,14 +7,16 @@ import { KanbanBoard } from "@/components/kanban/KanbanBoard";
 import { DocumentEditor } from "@/components/documents/DocumentEditor";
 import { TimelineView } from "@/components/timeline/TimelineView";
 import { SelectBmadDocsDialog } from "@/components/dialogs/SelectBmadDocsDialog";
+import { DiscoveredProjectsPrompt } from "@/components/dialogs/DiscoveredProjectsPrompt";
 import { useProjectStore, useActiveProject } from "@/stores/projectStore";
 import { useTauri } from "@/hooks/useTauri";
-
-interface FileChangePayload {
-  projectId: string;
-  path: string;
-  kind: string;
-}
+import type {
+  BmadProject,
+  DiscoveredProject,
+  ProjectUpdatePayload,
+  WatchErrorPayload,
+  WatchModePayload,
+} from "@/types";
 
 function App() {
   const [isLoading, setIsLoading] = useState(false);
@@ -22,18 +24,22 @@ function App() {
   const [pendingProjectPath, setPendingProjectPath] = useState<string | null>(null);
   const [bmadCandidates, setBmadCandidates] = useState<string[]>([]);
   const [refreshCount, setRefreshCount] = useState(0);
+  const [discovered, setDiscovered] = useState<DiscoveredProject[]>([]);
 
   const currentView = useProjectStore((state) => state.currentView);
   const projects = useProjectStore((state) => state.projects);
   const addProject = useProjectStore((state) => state.addProject);
   const updateProject = useProjectStore((state) => state.updateProject);
+  const setWatchStatus = useProjectStore((state) => state.setWatchStatus);
+  const setWatchError = useProjectStore((state) => state.setWatchError);
   const activeProject = useActiveProject();
   const tauri = useTauri();
   const tauriRef = useRef(tauri);
   const projectsRef = useRef(projects);
   const refreshInFlight = useRef(new Set<string>());
-  const watchedProjects = useRef(new Map<string, string>());
+  const refreshQueued = useRef(new Set<string>());
   const didInitialRefresh = useRef(false);
+  const refreshProjectRef = useRef<((projectId: string) => Promise<void>) | null>(null);
 
   useEffect(() => {
     tauriRef.current = tauri;
@@ -43,10 +49,27 @@ function App() {
     projectsRef.current = projects;
   }, [projects]);
 
+  const applyParsedProject = useCallback(
+    (project: BmadProject, parsedProject: BmadProject) => {
+      const { id: _ignored, createdAt: _createdAt, ...updates } = parsedProject;
+      updateProject(project.id, {
+        ...updates,
+        createdAt: project.createdAt,
+        bmadDocsPath: parsedProject.bmadDocsPath || project.bmadDocsPath,
+      });
+    },
+    [updateProject]
+  );
+
   const refreshProject = useCallback(
     async (projectId: string) => {
       const project = projectsRef.current.find((p) => p.id === projectId);
-      if (!project || refreshInFlight.current.has(projectId)) {
+      if (!project) {
+        return;
+      }
+      if (refreshInFlight.current.has(projectId)) {
+        // Re-parse once more after the current refresh so the latest change is picked up
+        refreshQueued.current.add(projectId);
         return;
       }
 
@@ -55,22 +78,45 @@ function App() {
       try {
         const parsedProject = await tauriRef.current.parseProject(
           project.path,
-          project.bmadDocsPath
+          project.bmadDocsPath,
+          project.id
         );
-        const { id: _ignored, createdAt: _createdAt, ...updates } = parsedProject;
-        updateProject(projectId, {
-          ...updates,
-          createdAt: project.createdAt,
-          bmadDocsPath: parsedProject.bmadDocsPath || project.bmadDocsPath,
-        });
+        applyParsedProject(project, parsedProject);
       } catch (error) {
         console.error("Failed to refresh project:", error);
       } finally {
         refreshInFlight.current.delete(projectId);
         setRefreshCount((count) => Math.max(0, count - 1));
+        if (refreshQueued.current.delete(projectId)) {
+          refreshProjectRef.current?.(projectId);
+        }
       }
     },
-    [updateProject]
+    [applyParsedProject]
+  );
+
+  useEffect(() => {
+    refreshProjectRef.current = refreshProject;
+  }, [refreshProject]);
+
+  // Registering opens the project's folders to the backend, so it comes
+  // before parsing; a folder that turns out not to parse is unregistered again
+  const registerAndParse = useCallback(
+    async (projectPath: string, bmadDocsPath?: string): Promise<BmadProject> => {
+      const entry = await tauriRef.current.registerProject(projectPath, bmadDocsPath);
+      try {
+        const project = await tauriRef.current.parseProject(
+          entry.path,
+          entry.bmadDocsPath,
+          entry.id
+        );
+        return { ...project, id: entry.id };
+      } catch (error) {
+        await tauriRef.current.unregisterProject(entry.id).catch(() => undefined);
+        throw error;
+      }
+    },
+    []
   );
 
   useEffect(() => {
@@ -94,13 +140,20 @@ function App() {
   useEffect(() => {
     let unlisten: UnlistenFn | null = null;
 
+    let unlistenError: UnlistenFn | null = null;
+
     const setup = async () => {
-      unlisten = await listen<FileChangePayload>(
-        "bmad-file-change",
-        (event) => {
-          refreshProject(event.payload.projectId);
+      // Sent when a watcher starts and when it falls back to polling
+      unlisten = await listen<WatchModePayload>("bmad-watch-mode", (event) => {
+        const { projectId, ...status } = event.payload;
+        setWatchStatus(projectId, status);
+      });
+      unlistenError = await listen<WatchErrorPayload>("bmad-watch-error", (event) => {
+        const { projectId, message, recovered } = event.payload;
+        if (!recovered) {
+          setWatchError(projectId, message);
         }
-      );
+      });
     };
 
     setup();
@@ -109,60 +162,182 @@ function App() {
       if (unlisten) {
         unlisten();
       }
+      if (unlistenError) {
+        unlistenError();
+      }
     };
-  }, [refreshProject]);
+  }, [setWatchStatus, setWatchError]);
 
   useEffect(() => {
-    const nextIds = new Set(projects.map((project) => project.id));
-    const watched = watchedProjects.current;
+    let unlisten: UnlistenFn | null = null;
 
-    const syncWatchers = async () => {
-      for (const project of projects) {
-        const watchPath = project.bmadDocsPath || project.path;
-        const existingPath = watched.get(project.id);
-        if (existingPath === watchPath) continue;
+    const setup = async () => {
+      // The watcher re-reads changed files itself and sends the updated project
+      unlisten = await listen<ProjectUpdatePayload>(
+        "bmad-project-updated",
+        (event) => {
+          const { projectId, changes, origin } = event.payload;
+          // A document the editor just saved already shows the saved content;
+          // reloading it would reset the editor
+          if (
+            origin === "app" &&
+            changes.every((change) => change.type === "documentUpdated")
+          ) {
+            return;
+          }
 
-        try {
-          await tauriRef.current.startProjectWatcher(project.id, watchPath);
-          watched.set(project.id, watchPath);
-        } catch (error) {
-          console.error("Failed to start watcher:", error);
+          const project = projectsRef.current.find((p) => p.id === projectId);
+          if (project) {
+            applyParsedProject(project, event.payload.project);
+          }
         }
+      );
+    };
+
+    setup();
+
+    return () => {
+      if (unlisten) {
+        unlisten();
       }
+    };
+  }, [applyParsedProject]);
+
+  // Projects found by background discovery are only proposed; the user
+  // adds or dismisses each one
+  const proposeDiscoveredProject = useCallback((discovered: DiscoveredProject) => {
+    if (projectsRef.current.some((p) => p.path === discovered.path)) return;
+    setDiscovered((current) =>
+      current.some((p) => p.path === discovered.path) ? current : [...current, discovered]
+    );
+  }, []);
 
-      for (const watchedId of Array.from(watched.keys())) {
-        if (nextIds.has(watchedId)) continue;
-        try {
-          await tauriRef.current.stopProjectWatcher(watchedId);
-        } catch (error) {
-          console.error("Failed to stop watcher:", error);
-        } finally {
-          watched.delete(watchedId);
-        }
+  useEffect(() => {
+    let unlisten: UnlistenFn | null = null;
+
+    const setup = async () => {
+      unlisten = await listen<DiscoveredProject>("bmad-project-discovered", (event) => {
+        proposeDiscoveredProject(event.payload);
+      });
+      // Found before this window was listening
+      for (const discovered of await tauriRef.current.listDiscoveredProjects()) {
+        proposeDiscoveredProject(discovered);
       }
     };
 
-    syncWatchers();
-  }, [projects]);
+    setup();
 
-  useEffect(() => {
     return () => {
-      tauriRef.current.stopAllWatchers().catch((error) => {
-        console.error("Failed to stop watchers:", error);
-      });
+      if (unlisten) {
+        unlisten();
+      }
     };
+  }, [proposeDiscoveredProject]);
+
+  const handleAddDiscovered = useCallback(
+    async (discovered: DiscoveredProject) => {
+      setDiscovered((current) => current.filter((p) => p.path !== discovered.path));
+      if (projectsRef.current.some((p) => p.path === discovered.path)) return;
+      try {
+        addProject(await registerAndParse(discovered.path, discovered.bmadDocsPath));
+      } catch (error) {
+        console.error(`Failed to add discovered project at ${discovered.path}:`, error);
+        await tauriRef.current.showMessage("Error", `Failed to add project: ${error}`);
+      }
+    },
+    [addProject, registerAndParse]
+  );
+
+  const handleDismissDiscovered = useCallback(async (discovered: DiscoveredProject) => {
+    setDiscovered((current) => current.filter((p) => p.path !== discovered.path));
+    try {
+      await tauriRef.current.dismissDiscoveredProject(discovered.path);
+    } catch (error) {
+      console.warn(`Failed to dismiss discovered project at ${discovered.path}:`, error);
+    }
   }, []);
 
+  useEffect(() => {
+    // The backend registry owns the project list and starts the watchers;
+    // bring the persisted store and the registry in line once at launch
+    const reconcile = async () => {
+      try {
+        const entries = await tauriRef.current.listRegisteredProjects();
+        const registeredIds = new Set(entries.map((entry) => entry.id));
+
+        // Projects added before the registry existed; the user confirms
+        // them once in a native dialog before the backend opens them
+        const legacy = projectsRef.current.filter((project) => !registeredIds.has(project.id));
+        const granted = legacy.length
+          ? await tauriRef.current.grantProjectFolders(
+              legacy.flatMap((project) =>
+                project.bmadDocsPath ? [project.path, project.bmadDocsPath] : [project.path]
+              )
+            )
+          : [];
+        for (const project of legacy) {
+          if (!granted.includes(project.path)) continue;
+          try {
+            await tauriRef.current.registerProject(
+              project.path,
+              project.bmadDocsPath || undefined,
+              project.id
+            );
+            if (project.watchOptions) {
+              await tauriRef.current.updateRegisteredProject(project.id, {
+                watchOptions: project.watchOptions,
+              });
+            }
+          } catch (error) {
+            console.error("Failed to register project:", error);
+          }
+        }
+
+        // Registered projects this window does not know yet: show the last
+        // parsed state right away and re-parse in the background
+        const cached = await tauriRef.current.getCachedProjects().catch(() => []);
+        for (const entry of entries) {
+          if (projectsRef.current.some((p) => p.id === entry.id)) continue;
+          const cachedProject = cached.find((p) => p.id === entry.id);
+          if (cachedProject) {
+            addProject({ ...cachedProject, watchOptions: entry.watchOptions });
+            projectsRef.current = useProjectStore.getState().projects;
+            refreshProject(entry.id);
+            continue;
+          }
+          try {
+            const project = await tauriRef.current.parseProject(
+              entry.path,
+              entry.bmadDocsPath,
+              entry.id
+            );
+            addProject({ ...project, id: entry.id, watchOptions: entry.watchOptions });
+          } catch (error) {
+            console.error(`Failed to parse project at ${entry.path}:`, error);
+          }
+        }
+
+        for (const info of await tauriRef.current.listProjectWatches()) {
+          setWatchStatus(info.projectId, info);
+          setWatchError(info.projectId, info.live ? null : info.lastError ?? null);
+        }
+      } catch (error) {
+        console.error("Failed to load project registry:", error);
+      }
+    };
+
+    reconcile();
+  }, [addProject, refreshProject, setWatchStatus, setWatchError]);
+
   const completeProjectImport = useCallback(
     async (projectPath: string, bmadDocsPath?: string) => {
       try {
-        const project = await tauri.parseProject(projectPath, bmadDocsPath);
-
-        if (projects.some((p) => p.path === project.path)) {
+        if (projects.some((p) => p.path === projectPath)) {
           await tauri.showMessage("Already Added", "This project is already in your list.");
           return;
         }
 
+        const project = await registerAndParse(projectPath, bmadDocsPath);
         addProject(project);
         await tauri.showMessage("Success", `Project "${project.name}" added successfully!`);
       } catch (error) {
@@ -170,7 +345,7 @@ function App() {
         await tauri.showMessage("Error", `Failed to add project: ${error}`);
       }
     },
-    [tauri, projects, addProject]
+    [tauri, projects, addProject, registerAndParse]
   );
 
   const handleAddProject = useCallback(async () => {
@@ -250,18 +425,17 @@ function App() {
 
   const handleScanProjects = useCallback(async () => {
     try {
-      const homeDir = await tauri.getHomeDirectory();
       setIsLoading(true);
 
-      const projectPaths = await tauri.scanProjects(homeDir, 3);
+      // Scan roots and depth come from the app settings
+      const projectPaths = await tauri.scanProjects();
       let addedCount = 0;
 
       for (const path of projectPaths) {
         if (projects.some((p) => p.path === path)) continue;
 
         try {
-          const project = await tauri.parseProject(path);
-          addProject(project);
+          addProject(await registerAndParse(path));
           addedCount++;
         } catch (e) {
           console.warn(`Failed to parse project at ${path}:`, e);
@@ -279,7 +453,7 @@ function App() {
     } finally {
       setIsLoading(false);
     }
-  }, [tauri, projects, addProject]);
+  }, [tauri, projects, addProject, registerAndParse]);
 
   const renderContent = () => {
     switch (currentView) {
@@ -357,8 +531,13 @@ function App() {
         onCancel={handleCancelSelect}
       />
 
-      {refreshCount > 0 && (
-        <div className="pointer-events-none fixed bottom-4 right-4 z-50">
+      <div className="pointer-events-none fixed bottom-4 right-4 z-50 flex flex-col items-end gap-2">
+        <DiscoveredProjectsPrompt
+          projects={discovered}
+          onAdd={handleAddDiscovered}
+          onDismiss={handleDismissDiscovered}
+        />
+        {refreshCount > 0 && (
           <div className="flex items-center gap-3 rounded-lg border border-border bg-card px-4 py-3 shadow-lg">
             <Loader2 className="h-4 w-4 animate-spin text-muted-foreground" />
             <div>
@@ -370,8 +549,8 @@ function App() {
               </p>
             </div>
           </div>
-        </div>
-      )}
+        )}
+      </div>
     </div>
   );
 }
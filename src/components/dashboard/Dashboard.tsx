@@ -9,6 +9,8 @@ import {
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { useProjectStore } from "@/stores/projectStore";
+import { useTauri } from "@/hooks/useTauri";
+import type { BmadProject } from "@/types";
 import { ProjectCard } from "./ProjectCard";
 import { EmptyState } from "./EmptyState";
 
@@ -28,6 +30,14 @@ export function Dashboard({
 
   const { projects, activeProjectId, setActiveProject, removeProject } =
     useProjectStore();
+  const tauri = useTauri();
+
+  const handleRemove = (project: BmadProject) => {
+    removeProject(project.id);
+    tauri.unregisterProject(project.id).catch((error) => {
+      console.error("Failed to remove project:", error);
+    });
+  };
 
   const filteredProjects = projects.filter((project) =>
     project.name.toLowerCase().includes(searchQuery.toLowerCase())
@@ -106,7 +116,7 @@ export function Dashboard({
                 project={project}
                 isActive={project.id === activeProjectId}
                 onClick={() => setActiveProject(project.id)}
-                onRemove={() => removeProject(project.id)}
+                onRemove={() => handleRemove(project)}
               />
             ))}
           </div>
@@ -118,7 +128,7 @@ export function Dashboard({
                 project={project}
                 isActive={project.id === activeProjectId}
                 onClick={() => setActiveProject(project.id)}
-                onRemove={() => removeProject(project.id)}
+                onRemove={() => handleRemove(project)}
               />
             ))}
           </div>
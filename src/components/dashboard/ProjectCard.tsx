@@ -6,6 +6,7 @@ import {
   CheckCircle2,
   Circle,
   Clock,
+  Radar,
 } from "lucide-react";
 import { cn } from "@/lib/utils";
 import { Card, CardContent, CardHeader } from "@/components/ui/card";
@@ -14,13 +15,21 @@ import { Badge } from "@/components/ui/badge";
 import { Progress } from "@/components/ui/progress";
 import {
   DropdownMenu,
+  DropdownMenuCheckboxItem,
   DropdownMenuContent,
   DropdownMenuItem,
+  DropdownMenuLabel,
+  DropdownMenuRadioGroup,
+  DropdownMenuRadioItem,
   DropdownMenuSeparator,
+  DropdownMenuSub,
+  DropdownMenuSubContent,
+  DropdownMenuSubTrigger,
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu";
-import { useProjectStats } from "@/stores/projectStore";
-import type { BmadProject, BmadPhase, StoryStatus } from "@/types";
+import { useProjectStats, useProjectStore } from "@/stores/projectStore";
+import { useTauri } from "@/hooks/useTauri";
+import type { BmadProject, BmadPhase, StoryStatus, WatchOptions } from "@/types";
 
 interface ProjectCardProps {
   project: BmadProject;
@@ -44,6 +53,10 @@ const phaseLabels: Record<BmadPhase, string> = {
   4: "Implementation",
 };
 
+// Poll intervals offered for network mounts and other folders without native events
+const pollIntervals = [1000, 2000, 5000, 10000];
+const defaultPollInterval = 2000;
+
 const phaseColors: Record<BmadPhase, string> = {
   1: "bg-phase-1",
   2: "bg-phase-2",
@@ -61,6 +74,18 @@ export function ProjectCard({
   const storiesByStatus = stats?.storiesByStatus || defaultStoriesByStatus;
   const totalStories = stats?.totalStories || 0;
   const progressPercentage = stats?.progressPercentage || 0;
+  const watchStatus = useProjectStore((state) => state.watchStatuses[project.id]);
+  const updateProject = useProjectStore((state) => state.updateProject);
+  const watchOptions = project.watchOptions ?? {};
+  const tauri = useTauri();
+
+  // The registry restarts the watcher with the new settings
+  const setWatchOptions = (options: WatchOptions) => {
+    updateProject(project.id, { watchOptions: options });
+    tauri.updateRegisteredProject(project.id, { watchOptions: options }).catch((error) => {
+      console.error("Failed to update watch settings:", error);
+    });
+  };
 
   return (
     <Card
@@ -109,6 +134,36 @@ export function ProjectCard({
               <ExternalLink className="h-4 w-4 mr-2" />
               Open in Finder
             </DropdownMenuItem>
+            <DropdownMenuSub>
+              <DropdownMenuSubTrigger onClick={(e) => e.stopPropagation()}>
+                <Radar className="h-4 w-4 mr-2" />
+                File Watching
+              </DropdownMenuSubTrigger>
+              <DropdownMenuSubContent onClick={(e) => e.stopPropagation()}>
+                <DropdownMenuCheckboxItem
+                  checked={watchOptions.forcePoll ?? false}
+                  onCheckedChange={(checked) =>
+                    setWatchOptions({ ...watchOptions, forcePoll: checked })
+                  }
+                >
+                  Always poll
+                </DropdownMenuCheckboxItem>
+                <DropdownMenuSeparator />
+                <DropdownMenuLabel>Poll interval</DropdownMenuLabel>
+                <DropdownMenuRadioGroup
+                  value={String(watchOptions.pollIntervalMs ?? defaultPollInterval)}
+                  onValueChange={(value) =>
+                    setWatchOptions({ ...watchOptions, pollIntervalMs: Number(value) })
+                  }
+                >
+                  {pollIntervals.map((interval) => (
+                    <DropdownMenuRadioItem key={interval} value={String(interval)}>
+                      {interval / 1000}s
+                    </DropdownMenuRadioItem>
+                  ))}
+                </DropdownMenuRadioGroup>
+              </DropdownMenuSubContent>
+            </DropdownMenuSub>
             <DropdownMenuSeparator />
             <DropdownMenuItem
               className="text-destructive focus:text-destructive"
@@ -135,6 +190,19 @@ export function ProjectCard({
           >
             Phase {project.currentPhase}: {phaseLabels[project.currentPhase as BmadPhase]}
           </Badge>
+          {watchStatus?.mode === "poll" && (
+            <Badge
+              variant="outline"
+              className="text-xs"
+              title={
+                watchStatus.fallbackReason
+                  ? `Native file watching failed: ${watchStatus.fallbackReason}`
+                  : undefined
+              }
+            >
+              Polling every {(watchStatus.pollIntervalMs ?? defaultPollInterval) / 1000}s
+            </Badge>
+          )}
         </div>
 
         {/* Progress */}
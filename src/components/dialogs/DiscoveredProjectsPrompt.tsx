@@ -0,0 +1,61 @@
+import { FolderOpen, Plus, X } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import type { DiscoveredProject } from "@/types";
+
+interface DiscoveredProjectsPromptProps {
+  projects: DiscoveredProject[];
+  onAdd: (project: DiscoveredProject) => void;
+  onDismiss: (project: DiscoveredProject) => void;
+}
+
+// Projects found by background discovery, waiting for the user to add or
+// dismiss them
+export function DiscoveredProjectsPrompt({
+  projects,
+  onAdd,
+  onDismiss,
+}: DiscoveredProjectsPromptProps) {
+  if (projects.length === 0) return null;
+
+  return (
+    <div className="pointer-events-auto w-80 rounded-lg border border-border bg-card p-3 shadow-lg">
+      <p className="mb-2 text-sm font-medium text-foreground">
+        {projects.length === 1
+          ? "New BMAD project found"
+          : `${projects.length} new BMAD projects found`}
+      </p>
+      <div className="flex max-h-64 flex-col gap-2 overflow-y-auto">
+        {projects.map((project) => (
+          <div
+            key={project.path}
+            className="flex items-center gap-3 rounded-md border border-border p-2"
+          >
+            <FolderOpen className="h-4 w-4 shrink-0 text-muted-foreground" />
+            <div className="min-w-0 flex-1">
+              <p className="truncate text-sm font-medium">{project.name}</p>
+              <p className="truncate text-xs text-muted-foreground">{project.path}</p>
+            </div>
+            <Button
+              size="icon-sm"
+              variant="ghost"
+              className="shrink-0"
+              title="Add project"
+              onClick={() => onAdd(project)}
+            >
+              <Plus className="h-4 w-4" />
+            </Button>
+            <Button
+              size="icon-sm"
+              variant="ghost"
+              className="shrink-0"
+              title="Dismiss"
+              onClick={() => onDismiss(project)}
+            >
+              <X className="h-4 w-4" />
+            </Button>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
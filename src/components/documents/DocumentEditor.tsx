@@ -16,7 +16,13 @@ import { Button } from "@/components/ui/button";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Badge } from "@/components/ui/badge";
 import MDEditor from "@uiw/react-md-editor";
-import type { BmadProject, BmadDocument, DocumentType } from "@/types";
+import type {
+  BmadProject,
+  BmadDocument,
+  DocumentError,
+  DocumentType,
+  DocumentVersion,
+} from "@/types";
 import { useTauri } from "@/hooks/useTauri";
 
 interface DocumentEditorProps {
@@ -68,6 +74,7 @@ export function DocumentEditor({ project }: DocumentEditorProps) {
   const [editContent, setEditContent] = useState("");
   const [isSaving, setIsSaving] = useState(false);
   const [hasChanges, setHasChanges] = useState(false);
+  const [editVersion, setEditVersion] = useState<DocumentVersion | undefined>();
   const tauri = useTauri();
 
   // Group documents by type
@@ -106,9 +113,22 @@ export function DocumentEditor({ project }: DocumentEditorProps) {
     setSelectedDoc(updatedDoc || project.documents[0]);
   }, [project.documents, selectedDoc, isEditing, hasChanges]);
 
-  const handleEdit = () => {
+  const handleEdit = async () => {
     if (selectedDoc) {
-      setEditContent(selectedDoc.content);
+      try {
+        const { content, version } = await tauri.readDocument(selectedDoc.filePath);
+        setEditContent(content);
+        setEditVersion(version);
+      } catch (error) {
+        console.error("Failed to read document:", error);
+        const docError = error as DocumentError;
+        const message =
+          docError?.kind === "io" || docError?.kind === "forbidden"
+            ? docError.message
+            : String(error);
+        await tauri.showMessage("Error", `Failed to open document for editing: ${message}`);
+        return;
+      }
       setIsEditing(true);
       setHasChanges(false);
     }
@@ -117,22 +137,52 @@ export function DocumentEditor({ project }: DocumentEditorProps) {
   const handleCancel = () => {
     setIsEditing(false);
     setEditContent("");
+    setEditVersion(undefined);
     setHasChanges(false);
   };
 
   const handleSave = async () => {
-    if (!selectedDoc || !hasChanges) return;
+    if (!selectedDoc || !hasChanges || !editVersion) return;
 
     setIsSaving(true);
     try {
-      await tauri.writeDocument(selectedDoc.filePath, editContent);
+      let version = editVersion;
+      for (;;) {
+        try {
+          await tauri.writeDocument(selectedDoc.filePath, editContent, version);
+          break;
+        } catch (error) {
+          const docError = error as DocumentError;
+          if (docError?.kind !== "conflict") throw error;
+          if (!docError.currentVersion) {
+            await tauri.showMessage(
+              "Document deleted",
+              "This document was deleted outside the editor. Copy your changes before closing the editor."
+            );
+            return;
+          }
+
+          const overwrite = await tauri.askConfirm(
+            "Document changed on disk",
+            "This document was modified outside the editor since you started editing. Overwrite those changes with your version?"
+          );
+          if (!overwrite) return;
+          version = docError.currentVersion;
+        }
+      }
       // Update local state
       selectedDoc.content = editContent;
       setIsEditing(false);
+      setEditVersion(undefined);
       setHasChanges(false);
     } catch (error) {
       console.error("Failed to save document:", error);
-      await tauri.showMessage("Error", `Failed to save document: ${error}`);
+      const docError = error as DocumentError;
+      const message =
+        docError?.kind === "io" || docError?.kind === "forbidden"
+          ? docError.message
+          : String(error);
+      await tauri.showMessage("Error", `Failed to save document: ${message}`);
     } finally {
       setIsSaving(false);
     }
@@ -9,6 +9,7 @@ import {
   ChevronLeft,
   ChevronRight,
   Plus,
+  RotateCw,
 } from "lucide-react";
 import { cn } from "@/lib/utils";
 import { Button } from "@/components/ui/button";
@@ -21,6 +22,7 @@ import {
   TooltipTrigger,
 } from "@/components/ui/tooltip";
 import { useProjectStore, useProjectStats } from "@/stores/projectStore";
+import { useTauri } from "@/hooks/useTauri";
 import type { ViewType, BmadProject } from "@/types";
 
 interface NavItem {
@@ -205,6 +207,41 @@ function ProjectItem({
   onClick,
 }: ProjectItemProps) {
   const stats = useProjectStats(project.id);
+  const tauri = useTauri();
+  const watchStatus = useProjectStore((state) => state.watchStatuses[project.id]);
+  const watchError = useProjectStore((state) => state.watchErrors[project.id]);
+  const setWatchStatus = useProjectStore((state) => state.setWatchStatus);
+  const setWatchError = useProjectStore((state) => state.setWatchError);
+
+  const live = Boolean(watchStatus) && !watchError;
+  const watchLabel = watchError
+    ? `Not updating: ${watchError}`
+    : watchStatus
+      ? watchStatus.mode === "poll"
+        ? "Live (polling)"
+        : "Live"
+      : "Not watched";
+
+  const handleRestart = async (e: React.MouseEvent) => {
+    e.stopPropagation();
+    try {
+      const status = await tauri.restartProjectWatcher(project.id);
+      setWatchStatus(project.id, status);
+      setWatchError(project.id, null);
+    } catch (error) {
+      setWatchError(project.id, String(error));
+    }
+  };
+
+  const indicator = (
+    <span
+      className={cn(
+        "h-2 w-2 rounded-full shrink-0",
+        live ? "bg-story-done" : watchError ? "bg-destructive" : "bg-sidebar-foreground/30"
+      )}
+      title={watchLabel}
+    />
+  );
 
   const button = (
     <Button
@@ -221,7 +258,10 @@ function ProjectItem({
       <FolderOpen className="h-4 w-4 shrink-0" />
       {!collapsed && (
         <div className="flex-1 text-left min-w-0">
-          <div className="font-medium truncate">{project.name}</div>
+          <div className="flex items-center gap-2">
+            <span className="font-medium truncate">{project.name}</span>
+            {indicator}
+          </div>
           {stats && (
             <div className="text-xs text-sidebar-foreground/60">
               Phase {project.currentPhase} • {stats.progressPercentage}%
@@ -229,6 +269,16 @@ function ProjectItem({
           )}
         </div>
       )}
+      {!collapsed && watchError && (
+        <span
+          role="button"
+          className="p-1 rounded hover:bg-sidebar-accent shrink-0"
+          title="Restart file watching"
+          onClick={handleRestart}
+        >
+          <RotateCw className="h-3.5 w-3.5" />
+        </span>
+      )}
     </Button>
   );
 
@@ -244,6 +294,7 @@ function ProjectItem({
                 Phase {project.currentPhase} • {stats.progressPercentage}%
               </div>
             )}
+            <div className="text-xs text-muted-foreground">{watchLabel}</div>
           </div>
         </TooltipContent>
       </Tooltip>
@@ -81,7 +81,8 @@ function formatDate(value: string) {
   return format(date, "MMM d, yyyy");
 }
 
-function formatAgo(value: string) {
+function formatAgo(value?: string) {
+  if (!value) return "Unknown";
   const date = new Date(value);
   if (!isValid(date)) return "Unknown";
   return formatDistanceToNow(date, { addSuffix: true });
@@ -1,26 +1,132 @@
 import { invoke } from "@tauri-apps/api/core";
-import { open, ask, message } from "@tauri-apps/plugin-dialog";
-import type { BmadProject } from "@/types";
+import { ask, message } from "@tauri-apps/plugin-dialog";
+import type {
+  Agent,
+  AppSettings,
+  BmadProject,
+  DiscoveredProject,
+  DocumentVersion,
+  EpicStatus,
+  FileRevision,
+  PortfolioOverview,
+  PortfolioQuery,
+  ProjectChangesSince,
+  ProjectEntry,
+  ProjectEntryUpdate,
+  ProjectSnapshot,
+  RevisionDiff,
+  SprintPlan,
+  Story,
+  StoryRename,
+  StoryStatus,
+  Team,
+  VersionedDocument,
+  WatcherInfo,
+  WatchOptions,
+  WatchStatus,
+  WorkflowGroup,
+} from "@/types";
 
 export interface TauriAPI {
-  scanProjects: (rootPath: string, maxDepth?: number) => Promise<string[]>;
-  parseProject: (projectPath: string, bmadDocsPath?: string) => Promise<BmadProject>;
+  scanProjects: (rootPath?: string, maxDepth?: number) => Promise<string[]>;
+  parseProject: (
+    projectPath: string,
+    bmadDocsPath?: string,
+    projectId?: string
+  ) => Promise<BmadProject>;
+  getCachedProjects: () => Promise<BmadProject[]>;
+  listProjectSnapshots: (
+    projectId: string,
+    since?: string,
+    limit?: number
+  ) => Promise<ProjectSnapshot[]>;
+  getProjectChangesSince: (projectId: string, since: string) => Promise<ProjectChangesSince>;
+  getPortfolioOverview: (query?: PortfolioQuery) => Promise<PortfolioOverview>;
+  listAgents: (projectPath: string) => Promise<Agent[]>;
+  listTeams: (projectPath: string) => Promise<Team[]>;
+  listWorkflows: (projectPath: string) => Promise<WorkflowGroup[]>;
+  closeProject: (projectPath: string, bmadDocsPath?: string) => Promise<void>;
+  listRegisteredProjects: () => Promise<ProjectEntry[]>;
+  registerProject: (
+    projectPath: string,
+    bmadDocsPath?: string,
+    id?: string
+  ) => Promise<ProjectEntry>;
+  unregisterProject: (projectId: string) => Promise<void>;
+  updateRegisteredProject: (
+    projectId: string,
+    update: ProjectEntryUpdate
+  ) => Promise<ProjectEntry>;
   isBmadProject: (path: string) => Promise<boolean>;
   findBmadDocsCandidates: (projectPath: string) => Promise<string[]>;
-  readDocument: (filePath: string) => Promise<string>;
-  writeDocument: (filePath: string, content: string) => Promise<void>;
-  startProjectWatcher: (projectId: string, bmadDocsPath: string) => Promise<void>;
+  readDocument: (filePath: string) => Promise<VersionedDocument>;
+  writeDocument: (
+    filePath: string,
+    content: string,
+    expectedVersion: DocumentVersion
+  ) => Promise<DocumentVersion>;
+  setStoryStatus: (bmadDocsPath: string, storyKey: string, status: StoryStatus) => Promise<string>;
+  setEpicStatus: (bmadDocsPath: string, epicNumber: number, status: EpicStatus) => Promise<void>;
+  toggleStoryTask: (
+    bmadDocsPath: string,
+    filePath: string,
+    taskId: string,
+    completed?: boolean
+  ) => Promise<Story>;
+  createStory: (
+    projectPath: string,
+    bmadDocsPath: string,
+    epicNumber: number,
+    storyNumber?: number,
+    title?: string,
+    templatePath?: string
+  ) => Promise<Story>;
+  moveStory: (
+    bmadDocsPath: string,
+    storyKey: string,
+    toEpic: number,
+    toNumber?: number,
+    renumberSiblings?: boolean
+  ) => Promise<StoryRename[]>;
+  planSprintStatus: (
+    projectPath: string,
+    bmadDocsPath: string,
+    dryRun?: boolean
+  ) => Promise<SprintPlan>;
+  listFileHistory: (filePath: string) => Promise<FileRevision[]>;
+  diffFileRevisions: (
+    filePath: string,
+    fromRevision: string,
+    toRevision?: string
+  ) => Promise<RevisionDiff>;
+  restoreFileRevision: (
+    filePath: string,
+    revisionId: string
+  ) => Promise<DocumentVersion>;
+  startProjectWatcher: (
+    projectId: string,
+    projectPath: string,
+    bmadDocsPath?: string,
+    options?: WatchOptions
+  ) => Promise<WatchStatus>;
+  restartProjectWatcher: (projectId: string) => Promise<WatchStatus>;
+  listProjectWatches: () => Promise<WatcherInfo[]>;
   stopProjectWatcher: (projectId: string) => Promise<void>;
   stopAllWatchers: () => Promise<void>;
+  listDiscoveredProjects: () => Promise<DiscoveredProject[]>;
+  dismissDiscoveredProject: (projectPath: string) => Promise<void>;
+  getSettings: () => Promise<AppSettings>;
+  updateSettings: (settings: AppSettings) => Promise<AppSettings>;
   getHomeDirectory: () => Promise<string>;
   openFolderDialog: (title?: string) => Promise<string | null>;
+  grantProjectFolders: (paths: string[]) => Promise<string[]>;
   showMessage: (title: string, msg: string) => Promise<void>;
   askConfirm: (title: string, msg: string) => Promise<boolean>;
 }
 
 export function useTauri(): TauriAPI {
   const scanProjects = async (
-    rootPath: string,
+    rootPath?: string,
     maxDepth?: number
   ): Promise<string[]> => {
     return invoke("scan_projects", { rootPath, maxDepth });
@@ -28,9 +134,75 @@ export function useTauri(): TauriAPI {
 
   const parseProject = async (
     projectPath: string,
-    bmadDocsPath?: string
+    bmadDocsPath?: string,
+    projectId?: string
   ): Promise<BmadProject> => {
-    return invoke("parse_project", { projectPath, bmadDocsPath });
+    return invoke("parse_project", { projectPath, bmadDocsPath, projectId });
+  };
+
+  const getCachedProjects = async (): Promise<BmadProject[]> => {
+    return invoke("get_cached_projects");
+  };
+
+  const listProjectSnapshots = async (
+    projectId: string,
+    since?: string,
+    limit?: number
+  ): Promise<ProjectSnapshot[]> => {
+    return invoke("list_project_snapshots", { projectId, since, limit });
+  };
+
+  const getProjectChangesSince = async (
+    projectId: string,
+    since: string
+  ): Promise<ProjectChangesSince> => {
+    return invoke("get_project_changes_since", { projectId, since });
+  };
+
+  const getPortfolioOverview = async (query?: PortfolioQuery): Promise<PortfolioOverview> => {
+    return invoke("get_portfolio_overview", { query });
+  };
+
+  const listAgents = async (projectPath: string): Promise<Agent[]> => {
+    return invoke("list_agents", { projectPath });
+  };
+
+  const listTeams = async (projectPath: string): Promise<Team[]> => {
+    return invoke("list_teams", { projectPath });
+  };
+
+  const listWorkflows = async (projectPath: string): Promise<WorkflowGroup[]> => {
+    return invoke("list_workflows", { projectPath });
+  };
+
+  const closeProject = async (
+    projectPath: string,
+    bmadDocsPath?: string
+  ): Promise<void> => {
+    return invoke("close_project", { projectPath, bmadDocsPath });
+  };
+
+  const listRegisteredProjects = async (): Promise<ProjectEntry[]> => {
+    return invoke("list_projects");
+  };
+
+  const registerProject = async (
+    projectPath: string,
+    bmadDocsPath?: string,
+    id?: string
+  ): Promise<ProjectEntry> => {
+    return invoke("add_project", { projectPath, bmadDocsPath, id });
+  };
+
+  const unregisterProject = async (projectId: string): Promise<void> => {
+    return invoke("remove_project", { projectId });
+  };
+
+  const updateRegisteredProject = async (
+    projectId: string,
+    update: ProjectEntryUpdate
+  ): Promise<ProjectEntry> => {
+    return invoke("update_project", { projectId, update });
   };
 
   const isBmadProject = async (path: string): Promise<boolean> => {
@@ -41,22 +213,124 @@ export function useTauri(): TauriAPI {
     return invoke("find_bmad_docs_candidates", { projectPath });
   };
 
-  const readDocument = async (filePath: string): Promise<string> => {
+  const readDocument = async (filePath: string): Promise<VersionedDocument> => {
     return invoke("read_document", { filePath });
   };
 
   const writeDocument = async (
     filePath: string,
-    content: string
+    content: string,
+    expectedVersion: DocumentVersion
+  ): Promise<DocumentVersion> => {
+    return invoke("write_document", { filePath, content, expectedVersion });
+  };
+
+  const setStoryStatus = async (
+    bmadDocsPath: string,
+    storyKey: string,
+    status: StoryStatus
+  ): Promise<string> => {
+    return invoke("set_story_status", { bmadDocsPath, storyKey, status });
+  };
+
+  const setEpicStatus = async (
+    bmadDocsPath: string,
+    epicNumber: number,
+    status: EpicStatus
   ): Promise<void> => {
-    return invoke("write_document", { filePath, content });
+    return invoke("set_epic_status", { bmadDocsPath, epicNumber, status });
+  };
+
+  const toggleStoryTask = async (
+    bmadDocsPath: string,
+    filePath: string,
+    taskId: string,
+    completed?: boolean
+  ): Promise<Story> => {
+    return invoke("toggle_story_task", { bmadDocsPath, filePath, taskId, completed });
+  };
+
+  const createStory = async (
+    projectPath: string,
+    bmadDocsPath: string,
+    epicNumber: number,
+    storyNumber?: number,
+    title?: string,
+    templatePath?: string
+  ): Promise<Story> => {
+    return invoke("create_story", {
+      projectPath,
+      bmadDocsPath,
+      epicNumber,
+      storyNumber,
+      title,
+      templatePath,
+    });
+  };
+
+  const moveStory = async (
+    bmadDocsPath: string,
+    storyKey: string,
+    toEpic: number,
+    toNumber?: number,
+    renumberSiblings?: boolean
+  ): Promise<StoryRename[]> => {
+    return invoke("move_story", {
+      bmadDocsPath,
+      storyKey,
+      toEpic,
+      toNumber,
+      renumberSiblings,
+    });
+  };
+
+  const planSprintStatus = async (
+    projectPath: string,
+    bmadDocsPath: string,
+    dryRun?: boolean
+  ): Promise<SprintPlan> => {
+    return invoke("plan_sprint_status", { projectPath, bmadDocsPath, dryRun });
+  };
+
+  const listFileHistory = async (filePath: string): Promise<FileRevision[]> => {
+    return invoke("list_file_history", { filePath });
+  };
+
+  const diffFileRevisions = async (
+    filePath: string,
+    fromRevision: string,
+    toRevision?: string
+  ): Promise<RevisionDiff> => {
+    return invoke("diff_file_revisions", { filePath, fromRevision, toRevision });
+  };
+
+  const restoreFileRevision = async (
+    filePath: string,
+    revisionId: string
+  ): Promise<DocumentVersion> => {
+    return invoke("restore_file_revision", { filePath, revisionId });
   };
 
   const startProjectWatcher = async (
     projectId: string,
-    bmadDocsPath: string
-  ): Promise<void> => {
-    return invoke("start_project_watch", { projectId, bmadDocsPath });
+    projectPath: string,
+    bmadDocsPath?: string,
+    options?: WatchOptions
+  ): Promise<WatchStatus> => {
+    return invoke("start_project_watch", {
+      projectId,
+      projectPath,
+      bmadDocsPath,
+      options,
+    });
+  };
+
+  const restartProjectWatcher = async (projectId: string): Promise<WatchStatus> => {
+    return invoke("restart_project_watch", { projectId });
+  };
+
+  const listProjectWatches = async (): Promise<WatcherInfo[]> => {
+    return invoke("list_project_watches");
   };
 
   const stopProjectWatcher = async (projectId: string): Promise<void> => {
@@ -67,17 +341,33 @@ export function useTauri(): TauriAPI {
     return invoke("stop_all_watchers");
   };
 
+  const listDiscoveredProjects = async (): Promise<DiscoveredProject[]> => {
+    return invoke("list_discovered_projects");
+  };
+
+  const dismissDiscoveredProject = async (projectPath: string): Promise<void> => {
+    return invoke("dismiss_discovered_project", { projectPath });
+  };
+
+  const getSettings = async (): Promise<AppSettings> => {
+    return invoke("get_settings");
+  };
+
+  const updateSettings = async (settings: AppSettings): Promise<AppSettings> => {
+    return invoke("update_settings", { settings });
+  };
+
   const getHomeDirectory = async (): Promise<string> => {
     return invoke("get_home_directory");
   };
 
+  // The backend shows the picker so only folders the user picked can be added as projects
   const openFolderDialog = async (title?: string): Promise<string | null> => {
-    const result = await open({
-      directory: true,
-      multiple: false,
-      title: title || "Select Folder",
-    });
-    return result as string | null;
+    return invoke("pick_folder", { title: title || "Select Folder" });
+  };
+
+  const grantProjectFolders = async (paths: string[]): Promise<string[]> => {
+    return invoke("grant_project_folders", { paths });
   };
 
   const showMessage = async (title: string, msg: string): Promise<void> => {
@@ -91,15 +381,43 @@ export function useTauri(): TauriAPI {
   return {
     scanProjects,
     parseProject,
+    getCachedProjects,
+    listProjectSnapshots,
+    getProjectChangesSince,
+    getPortfolioOverview,
+    listAgents,
+    listTeams,
+    listWorkflows,
+    closeProject,
+    listRegisteredProjects,
+    registerProject,
+    unregisterProject,
+    updateRegisteredProject,
     isBmadProject,
     findBmadDocsCandidates,
     readDocument,
     writeDocument,
+    setStoryStatus,
+    setEpicStatus,
+    toggleStoryTask,
+    createStory,
+    moveStory,
+    planSprintStatus,
+    listFileHistory,
+    diffFileRevisions,
+    restoreFileRevision,
     startProjectWatcher,
+    restartProjectWatcher,
+    listProjectWatches,
     stopProjectWatcher,
     stopAllWatchers,
+    listDiscoveredProjects,
+    dismissDiscoveredProject,
+    getSettings,
+    updateSettings,
     getHomeDirectory,
     openFolderDialog,
+    grantProjectFolders,
     showMessage,
     askConfirm,
   };
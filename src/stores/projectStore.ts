@@ -9,6 +9,7 @@ import type {
   ViewType,
   FilterOptions,
   ProjectStats,
+  WatchStatus,
 } from "@/types";
 
 interface ProjectState {
@@ -16,6 +17,10 @@ interface ProjectState {
   projects: BmadProject[];
   activeProjectId: string | null;
 
+  // How each project's watcher runs, and its last unrecovered error (not persisted)
+  watchStatuses: Record<string, WatchStatus>;
+  watchErrors: Record<string, string>;
+
   // View state
   currentView: ViewType;
   sidebarOpen: boolean;
@@ -28,6 +33,8 @@ interface ProjectState {
   removeProject: (projectId: string) => void;
   setActiveProject: (projectId: string | null) => void;
   updateProject: (projectId: string, updates: Partial<BmadProject>) => void;
+  setWatchStatus: (projectId: string, status: WatchStatus) => void;
+  setWatchError: (projectId: string, message: string | null) => void;
 
   // Actions - Epics
   updateEpicStatus: (
@@ -65,6 +72,8 @@ export const useProjectStore = create<ProjectState>()(
       // Initial state
       projects: [],
       activeProjectId: null,
+      watchStatuses: {},
+      watchErrors: {},
       currentView: "dashboard",
       sidebarOpen: true,
       filters: {},
@@ -92,6 +101,19 @@ export const useProjectStore = create<ProjectState>()(
           ),
         })),
 
+      setWatchStatus: (projectId, status) =>
+        set((state) => ({
+          watchStatuses: { ...state.watchStatuses, [projectId]: status },
+        })),
+
+      setWatchError: (projectId, message) =>
+        set((state) => {
+          const { [projectId]: _previous, ...watchErrors } = state.watchErrors;
+          return {
+            watchErrors: message ? { ...watchErrors, [projectId]: message } : watchErrors,
+          };
+        }),
+
       // Epic actions
       updateEpicStatus: (projectId, epicId, status) =>
         set((state) => ({
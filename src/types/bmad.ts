@@ -41,6 +41,8 @@ export interface Story {
   valueBenefit: string;
   acceptanceCriteria: AcceptanceCriteria[];
   status: StoryStatus;
+  // Marked blocked in sprint-status.yaml or the story file; status reads as backlog
+  blocked?: boolean;
   tasks?: Task[];
   filePath?: string;
   createdAt: string;
@@ -49,9 +51,18 @@ export interface Story {
 
 // Task within a Story
 export interface Task {
-  id: string;
+  id: string; // e.g., "1", "1.2"
   title: string;
   completed: boolean;
+  subtasks?: Task[];
+}
+
+// Story key/number change from a move or renumbering
+export interface StoryRename {
+  fromKey: string;
+  toKey: string;
+  fromNumber: string;
+  toNumber: string;
 }
 
 // Epic
@@ -80,6 +91,209 @@ export interface BmadDocument {
   updatedAt: string;
 }
 
+// Version token returned by read_document / write_document; an empty hash
+// asks write_document to create a new file
+export interface DocumentVersion {
+  hash: string;
+  modified?: string;
+}
+
+export interface VersionedDocument {
+  content: string;
+  version: DocumentVersion;
+}
+
+// Copy of a file saved before the app overwrote or removed it
+export interface FileRevision {
+  id: string;
+  filePath: string;
+  createdAt: string;
+  hash: string;
+  size: number;
+}
+
+export interface RevisionDiff {
+  unified: string;
+  additions: number;
+  deletions: number;
+}
+
+// Result of generating sprint-status.yaml from the epics
+export interface SprintPlan {
+  filePath: string;
+  created: boolean;
+  written: boolean;
+  addedKeys: string[];
+  upgradedKeys: string[];
+  unplannedKeys: string[];
+  diff: RevisionDiff;
+}
+
+// Per-project file watcher settings
+export interface WatchOptions {
+  forcePoll?: boolean;
+  pollIntervalMs?: number;
+}
+
+export type WatchMode = "native" | "poll";
+
+// How a project is being watched; fallbackReason is set when native watching failed
+export interface WatchStatus {
+  mode: WatchMode;
+  pollIntervalMs?: number;
+  fallbackReason?: string;
+}
+
+// Payload of the bmad-watch-mode event
+export interface WatchModePayload extends WatchStatus {
+  projectId: string;
+}
+
+// Payload of the bmad-watch-error event; recovered errors were worked around, e.g. by polling
+export interface WatchErrorPayload {
+  projectId: string;
+  message: string;
+  recovered: boolean;
+}
+
+// A running watcher as returned by list_project_watches
+export interface WatcherInfo extends WatchStatus {
+  projectId: string;
+  projectPath: string;
+  docsPath?: string;
+  watchedPaths: string[];
+  startedAt: string;
+  lastEventAt?: string;
+  eventCount: number;
+  lastError?: string;
+  lastErrorAt?: string;
+  errorCount: number;
+  live: boolean;
+}
+
+// App-wide settings stored by the backend (settings.json in the app data directory)
+export interface AppSettings {
+  // Folders scanned for projects; the home directory when empty
+  scanRoots: string[];
+  scanDepth: number;
+  // Running watchers restart when these change
+  watch: WatchSettings;
+  // Extensions the document editor may write besides Markdown and YAML
+  // (txt, csv, json, toml, xml or excalidraw)
+  writableExtensions: string[];
+  docsFolderNames: string[];
+  // Days without activity after which an unfinished project counts as stale
+  staleAfterDays: number;
+  // Look for new projects under the scan roots in the background
+  autoDiscover: boolean;
+  discoverIntervalSecs: number;
+}
+
+export interface WatchSettings {
+  debounceMs: number;
+  // Extensions (without the dot) whose changes are reported
+  extensions: string[];
+}
+
+// Sent as bmad-project-discovered when a new project appears under a scan root
+export interface DiscoveredProject {
+  path: string;
+  name: string;
+  bmadDocsPath?: string;
+  discoveredAt: string;
+}
+
+// A project in the app's registry (projects.json in the app data directory)
+export interface ProjectEntry {
+  id: string;
+  path: string;
+  bmadDocsPath?: string;
+  name: string;
+  pinned: boolean;
+  archived: boolean;
+  addedAt: string;
+  watchOptions: WatchOptions;
+}
+
+// Fields to change on a registry entry; an empty bmadDocsPath clears it
+export interface ProjectEntryUpdate {
+  name?: string;
+  bmadDocsPath?: string;
+  pinned?: boolean;
+  archived?: boolean;
+  watchOptions?: WatchOptions;
+}
+
+// Payload of the bmad-file-change event: every path changed since the last one
+export type FileChangeKind = "create" | "modify" | "remove" | "rename-from" | "rename-to";
+
+// Part of the project model a changed file invalidates
+export type ModelArea =
+  | "project"
+  | "config"
+  | "workflow-status"
+  | "sprint-status"
+  | "epics"
+  | "stories"
+  | "documents"
+  | "agents"
+  | "workflows";
+
+// "app" when the change is the watcher seeing one of the app's own writes
+export type ChangeOrigin = "app" | "external";
+
+export interface FileChange {
+  path: string;
+  kinds: FileChangeKind[];
+  area: ModelArea;
+  origin: ChangeOrigin;
+}
+
+export interface FileChangePayload {
+  projectId: string;
+  changes: FileChange[];
+}
+
+// One semantic change between two parses of a project
+export type ProjectChange =
+  | { type: "epicAdded"; epicNumber: number; title: string }
+  | { type: "epicRemoved"; epicNumber: number }
+  | { type: "epicStatusChanged"; epicNumber: number; from: EpicStatus; to: EpicStatus }
+  | { type: "epicUpdated"; epicNumber: number }
+  | { type: "storyAdded"; storyNumber: string; title: string; status: StoryStatus }
+  | { type: "storyRemoved"; storyNumber: string }
+  | {
+      type: "storyStatusChanged";
+      storyNumber: string;
+      from: StoryStatus;
+      to: StoryStatus;
+    }
+  | { type: "storyUpdated"; storyNumber: string }
+  | { type: "taskAdded"; storyNumber: string; taskId: string; title: string }
+  | { type: "taskRemoved"; storyNumber: string; taskId: string }
+  | { type: "taskToggled"; storyNumber: string; taskId: string; completed: boolean }
+  | { type: "documentAdded"; filePath: string; docType: string; title: string }
+  | { type: "documentRemoved"; filePath: string }
+  | { type: "documentUpdated"; filePath: string };
+
+// Payload of the bmad-project-updated event
+export interface ProjectUpdatePayload {
+  projectId: string;
+  changes: ProjectChange[];
+  project: BmadProject;
+  origin: ChangeOrigin;
+}
+
+// Error returned by the document commands
+export type DocumentError =
+  | {
+      kind: "conflict";
+      currentContent: string | null;
+      currentVersion: DocumentVersion | null;
+    }
+  | { kind: "forbidden"; message: string }
+  | { kind: "io"; message: string };
+
 // PRD specific structure
 export interface PRDDocument extends BmadDocument {
   type: "prd";
@@ -115,6 +329,8 @@ export interface SprintStatus {
 export interface EpicSprintStatus {
   status: EpicStatus;
   stories: Record<string, StoryStatus>;
+  // Story keys whose value is blocked
+  blocked?: string[];
   retrospective?: RetrospectiveStatus;
 }
 
@@ -124,12 +340,14 @@ export interface BmadProject {
   name: string;
   path: string;
   bmadDocsPath?: string;
+  watchOptions?: WatchOptions;
   description?: string;
   currentPhase: BmadPhase;
   epics: Epic[];
   documents: BmadDocument[];
   sprintStatus?: SprintStatus;
-  lastActivity: string;
+  // Newest file modification time; missing when none could be read
+  lastActivity?: string;
   createdAt: string;
 }
 
@@ -143,6 +361,200 @@ export interface ProjectStats {
   phaseProgress: Record<BmadPhase, boolean>;
 }
 
+// A project's state at one point in time, from the backend snapshot store
+export interface ProjectSnapshot {
+  id: number;
+  projectId: string;
+  takenAt: string;
+  phase: BmadPhase;
+  stats: Omit<ProjectStats, "phaseProgress">;
+  // Story status by story number ("1.2")
+  storyStatuses: Record<string, StoryStatus>;
+}
+
+// from/to are missing when the story did not exist on that side
+export interface StoryStatusChange {
+  storyNumber: string;
+  from?: StoryStatus;
+  to?: StoryStatus;
+}
+
+export interface ProjectChangesSince {
+  projectId: string;
+  since: string;
+  baseline?: ProjectSnapshot;
+  latest?: ProjectSnapshot;
+  storyChanges: StoryStatusChange[];
+}
+
+// An agent installed in a project's _bmad modules
+export interface Agent {
+  id: string;
+  module: string;
+  name: string;
+  title: string;
+  icon?: string;
+  description?: string;
+  persona: AgentPersona;
+  menu: AgentMenuItem[];
+  filePath: string;
+  customized: boolean;
+}
+
+export interface AgentPersona {
+  role: string;
+  identity: string;
+  communicationStyle: string;
+  principles: string[];
+}
+
+export interface AgentMenuItem {
+  // Short command, e.g. "DS"
+  trigger: string;
+  fuzzyMatch?: string;
+  description: string;
+  workflow?: string;
+  exec?: string;
+  action?: string;
+}
+
+// A team from _bmad/<module>/teams/*.yaml
+export interface Team {
+  id: string;
+  module: string;
+  name: string;
+  icon?: string;
+  description?: string;
+  members: TeamMember[];
+  party?: PartyRoster;
+  filePath: string;
+}
+
+export interface TeamMember {
+  agentId: string;
+  // Module of the installed agent
+  module?: string;
+  installed: boolean;
+}
+
+// Party-mode roster CSV (e.g. default-party.csv)
+export interface PartyRoster {
+  filePath: string;
+  members: PartyMember[];
+}
+
+export interface PartyMember {
+  agentId: string;
+  displayName: string;
+  title: string;
+  icon?: string;
+  role: string;
+  module: string;
+  installed: boolean;
+}
+
+// A workflow installed in a project's _bmad modules
+export interface Workflow {
+  id: string;
+  name: string;
+  description: string;
+  author?: string;
+  module: string;
+  // Phase folder, e.g. "4-implementation" or "testarch"
+  phase?: string;
+  filePath: string;
+  standalone: boolean;
+  instructions?: string;
+  template?: string;
+  validation?: string;
+  config: Record<string, string>;
+  variables: Record<string, string>;
+  inputs: WorkflowInput[];
+  outputs: WorkflowOutput[];
+}
+
+export interface WorkflowInput {
+  key: string;
+  description?: string;
+  pattern?: string;
+  sharded?: string;
+  loadStrategy?: string;
+  exists: boolean;
+}
+
+export interface WorkflowOutput {
+  key: string;
+  // May contain placeholders filled at run time, e.g. {{story_key}}
+  path: string;
+  exists: boolean;
+}
+
+export interface WorkflowGroup {
+  phase?: string;
+  workflows: Workflow[];
+}
+
+// Cross-project dashboard
+export type PortfolioSort =
+  | "pinned"
+  | "name"
+  | "progress"
+  | "last-activity"
+  | "staleness"
+  | "phase";
+
+export interface PortfolioQuery {
+  sortBy?: PortfolioSort;
+  descending?: boolean;
+  search?: string;
+  phase?: number;
+  staleOnly?: boolean;
+  includeArchived?: boolean;
+}
+
+export interface StoryRef {
+  number: string;
+  title: string;
+}
+
+export interface PortfolioProject {
+  id: string;
+  name: string;
+  path: string;
+  pinned: boolean;
+  archived: boolean;
+  phase: number;
+  progressPercentage: number;
+  totalEpics: number;
+  totalStories: number;
+  doneStories: number;
+  inProgressStories: number;
+  reviewStories: StoryRef[];
+  blockedStories: StoryRef[];
+  lastActivity?: string;
+  idleDays?: number;
+  stale: boolean;
+  // Set when the project could not be read
+  error?: string;
+}
+
+export interface PortfolioTotals {
+  projects: number;
+  staleProjects: number;
+  totalStories: number;
+  doneStories: number;
+  inProgressStories: number;
+  reviewStories: number;
+  blockedStories: number;
+  progressPercentage: number;
+}
+
+export interface PortfolioOverview {
+  generatedAt: string;
+  projects: PortfolioProject[];
+  totals: PortfolioTotals;
+}
+
 // View types
 export type ViewType = "dashboard" | "kanban" | "timeline" | "editor" | "progress";
 